@@ -14,7 +14,9 @@ extern crate test;
 
 #[cfg(test)]
 mod tests {
-    use shepplogan::{shepplogan, shepplogan_modified};
+    use shepplogan::{
+        shepplogan, shepplogan_modified, BorderMode, Phantom, RenderOptions, Shape, ShapeOrder,
+    };
     use test::{black_box, Bencher};
 
     // 128x128
@@ -61,4 +63,151 @@ mod tests {
             black_box(shepplogan_modified(512, 512));
         });
     }
+
+    // Rectangles
+    fn rectangles(theta: f64) -> Vec<Shape> {
+        (0..20)
+            .map(|i| {
+                let offset = f64::from(i) * 0.05 - 0.5;
+                Shape::rectangle(offset, -offset, 0.8, 0.6, theta, 0.1)
+            })
+            .collect()
+    }
+
+    #[bench]
+    fn rectangles_axis_aligned_512(b: &mut Bencher) {
+        let shapes = rectangles(0.0);
+        b.iter(|| {
+            black_box(Phantom::new(512, 512, &shapes));
+        });
+    }
+
+    #[bench]
+    fn rectangles_rotated_512(b: &mut Bencher) {
+        let shapes = rectangles(90.0);
+        b.iter(|| {
+            black_box(Phantom::new(512, 512, &shapes));
+        });
+    }
+
+    // Ellipses, where the inner loop is dominated by the check whether a pixel is inside
+    fn ellipses() -> Vec<Shape> {
+        (0..20)
+            .map(|i| {
+                let offset = f64::from(i) * 0.05 - 0.5;
+                Shape::ellipse(offset, -offset, 0.8, 0.6, 9.0 * f64::from(i), 0.1)
+            })
+            .collect()
+    }
+
+    #[bench]
+    fn ellipses_512(b: &mut Bencher) {
+        let shapes = ellipses();
+        b.iter(|| {
+            black_box(Phantom::new(512, 512, &shapes));
+        });
+    }
+
+    #[bench]
+    fn ellipses_1024(b: &mut Bencher) {
+        let shapes = ellipses();
+        b.iter(|| {
+            black_box(Phantom::new(1024, 1024, &shapes));
+        });
+    }
+
+    // Supersampling with 4 x 4 samples per pixel, which renders the rows in parallel with the
+    // `rayon` feature
+    #[bench]
+    fn ellipses_supersampled_1024(b: &mut Bencher) {
+        let shapes = ellipses();
+        b.iter(|| {
+            black_box(Phantom::new_supersampled(1024, 1024, &shapes, 4).unwrap());
+        });
+    }
+
+    // Large phantoms, where the memory access pattern of the render loop matters
+    #[bench]
+    fn shepplogan_2048(b: &mut Bencher) {
+        b.iter(|| {
+            black_box(shepplogan(2048, 2048));
+        });
+    }
+
+    #[bench]
+    fn ellipses_2048(b: &mut Bencher) {
+        let shapes = ellipses();
+        b.iter(|| {
+            black_box(Phantom::new(2048, 2048, &shapes));
+        });
+    }
+
+    // Many small shapes at random positions
+    fn random_shapes() -> Vec<Shape> {
+        // Linear congruential generator, which is sufficient for spreading the shapes
+        let mut state = 12345u64;
+        let mut rand = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        (0..2000)
+            .map(|_| {
+                let (x, y) = (2.0 * rand() - 1.0, 2.0 * rand() - 1.0);
+                let (a, b) = (0.01 + 0.05 * rand(), 0.01 + 0.05 * rand());
+                Shape::ellipse(x, y, a, b, 360.0 * rand(), rand())
+            })
+            .collect()
+    }
+
+    fn random_shapes_ordered(b: &mut Bencher, order: ShapeOrder) {
+        let shapes = random_shapes();
+        let options = RenderOptions::new().order(order);
+        b.iter(|| {
+            black_box(Phantom::new_with_options(1024, 1024, &shapes, &options));
+        });
+    }
+
+    #[bench]
+    fn random_shapes_as_given_1024(b: &mut Bencher) {
+        random_shapes_ordered(b, ShapeOrder::AsGiven);
+    }
+
+    #[bench]
+    fn random_shapes_by_size_1024(b: &mut Bencher) {
+        random_shapes_ordered(b, ShapeOrder::BySizeAscending);
+    }
+
+    #[bench]
+    fn random_shapes_morton_1024(b: &mut Bencher) {
+        random_shapes_ordered(b, ShapeOrder::Morton);
+    }
+
+    // Gaussian blur with a separable kernel versus the two-dimensional kernel
+    #[bench]
+    fn gaussian_blur_separable_256(b: &mut Bencher) {
+        let phantom = shepplogan(256, 256);
+        b.iter(|| {
+            black_box(phantom.gaussian_blur(4.0, BorderMode::Reflect));
+        });
+    }
+
+    #[bench]
+    fn gaussian_blur_2d_256(b: &mut Bencher) {
+        let phantom = shepplogan(256, 256);
+        let (sigma, radius) = (4.0f64, 12i32);
+        let size = 2 * radius + 1;
+        let mut kernel = Vec::new();
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                kernel.push((-f64::from(x * x + y * y) / (2.0 * sigma * sigma)).exp());
+            }
+        }
+        let sum: f64 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|k| *k /= sum);
+        b.iter(|| {
+            black_box(phantom.convolve(&kernel, size as u32, size as u32, BorderMode::Reflect));
+        });
+    }
 }
@@ -0,0 +1,92 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::{fmt, io};
+
+/// Errors returned by the fallible functions of this crate
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// The number of values does not match the dimensions of the phantom
+    DimensionMismatch {
+        /// number of values required by the dimensions
+        expected: usize,
+        /// number of values provided
+        actual: usize,
+    },
+    /// The number of pixels does not fit into a `u32`
+    DimensionOverflow,
+    /// A region or pixel lies outside of the phantom
+    OutOfBounds,
+    /// The shape with the given index has a non-finite parameter
+    InvalidShape {
+        /// index of the offending shape
+        index: usize,
+    },
+    /// No shapes were given
+    NoShapes,
+    /// A parameter is outside of its valid range
+    InvalidParameter(&'static str),
+    /// An I/O error occurred
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DimensionMismatch { expected, actual } => write!(
+                f,
+                "dimension mismatch: expected {} values, got {}",
+                expected, actual
+            ),
+            Error::DimensionOverflow => write!(f, "number of pixels does not fit into a u32"),
+            Error::OutOfBounds => write!(f, "region is out of bounds"),
+            Error::InvalidShape { index } => {
+                write!(f, "shape {} has a non-finite parameter", index)
+            }
+            Error::NoShapes => write!(f, "no shapes given"),
+            Error::InvalidParameter(msg) => write!(f, "invalid parameter: {}", msg),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_error() {
+        let err = Error::DimensionMismatch {
+            expected: 4,
+            actual: 3,
+        };
+        assert_eq!(
+            err.to_string(),
+            "dimension mismatch: expected 4 values, got 3"
+        );
+        assert!(err.source().is_none());
+
+        let err: Error = std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into();
+        assert!(matches!(err, Error::Io(_)));
+        assert!(err.source().is_some());
+    }
+}
@@ -0,0 +1,90 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Sub},
+};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Floating point types in which the values of a `Phantom` can be stored
+///
+/// This trait is implemented for `f32` and `f64` and cannot be implemented outside of this crate.
+/// Only the storage and accumulation of the pixel values are generic: `Shape` and the geometry
+/// of the shapes (placing them on the pixel grid and testing which pixels they cover) always use
+/// `f64`, and the intensities are converted with `from_f64` when they are accumulated.
+pub trait Float:
+    Copy
+    + Debug
+    + PartialOrd
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + private::Sealed
+{
+    /// Converts `x` to the nearest value of this type.
+    fn from_f64(x: f64) -> Self;
+
+    /// Converts the value to `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Returns the maximum of `self` and `other`, ignoring `NaN` as `f64::max` does.
+    fn max(self, other: Self) -> Self;
+
+    /// Returns the absolute value of `self`.
+    fn abs(self) -> Self;
+}
+
+impl Float for f32 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    #[inline(always)]
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+impl Float for f64 {
+    #[inline(always)]
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    #[inline(always)]
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+}
@@ -0,0 +1,183 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{Phantom, Shape};
+
+/// Contrast-detail phantom
+///
+/// Constructs a phantom consisting of a grid of disks on a constant `background`. Each column of
+/// the grid corresponds to one of the `sizes` (radius of the disks, in the same units as the
+/// shapes) and each row corresponds to one of the `contrasts`. The contrast is added on top of the
+/// background, therefore the pixels of a disk have the value `background + contrast`.
+///
+/// The grid covers the entire canvas `[-1, 1]` and the disks are centered in their cells. The
+/// first size is on the left and the first contrast is at the top. Disks with a radius larger than
+/// half of a cell overlap with their neighbors.
+pub fn contrast_detail_phantom(
+    nx: u32,
+    ny: u32,
+    sizes: &[f64],
+    contrasts: &[f64],
+    background: f64,
+) -> Phantom {
+    let mut phantom = Phantom::new(nx, ny, &contrast_detail_shapes(sizes, contrasts));
+    phantom.data.iter_mut().for_each(|x| *x += background);
+    phantom
+}
+
+/// Disks of the contrast-detail phantom
+fn contrast_detail_shapes(sizes: &[f64], contrasts: &[f64]) -> Vec<Shape> {
+    let cell_width = 2.0 / sizes.len() as f64;
+    let cell_height = 2.0 / contrasts.len() as f64;
+    contrasts
+        .iter()
+        .enumerate()
+        .flat_map(|(row, &contrast)| {
+            let center_y = 1.0 - (row as f64 + 0.5) * cell_height;
+            sizes.iter().enumerate().map(move |(col, &radius)| {
+                let center_x = -1.0 + (col as f64 + 0.5) * cell_width;
+                Shape::ellipse(center_x, center_y, radius, radius, 0.0, contrast)
+            })
+        })
+        .collect()
+}
+
+/// Phantom consisting of randomly placed, non-overlapping disks
+///
+/// Places up to `num` disks with intensity `1.0` on a zero background. The radius of each disk is
+/// drawn uniformly from `[min_radius, max_radius]` (in the same units as the shapes) and its
+/// center is drawn uniformly such that the disk lies entirely inside of `[-1, 1] x [-1, 1]`. A
+/// candidate disk is rejected if it overlaps with a disk which was placed before. If no valid
+/// position can be found after a large number of attempts (e.g. because the canvas is full), fewer
+/// than `num` disks are placed.
+///
+/// The result is fully determined by `seed`. Requires the `rand` feature.
+///
+/// # Panics
+///
+/// Panics if `min_radius > max_radius`, if `min_radius` is not positive or if `max_radius` is
+/// larger than `1.0`.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::packed_disks_phantom;
+/// let phantom = packed_disks_phantom(256, 256, 20, 0.05, 0.15, 42);
+/// ```
+#[cfg(feature = "rand")]
+pub fn packed_disks_phantom(
+    nx: u32,
+    ny: u32,
+    num: usize,
+    min_radius: f64,
+    max_radius: f64,
+    seed: u64,
+) -> Phantom {
+    let shapes = packed_disks(num, min_radius, max_radius, seed)
+        .into_iter()
+        .map(|(x, y, r)| Shape::ellipse(x, y, r, r, 0.0, 1.0))
+        .collect::<Vec<_>>();
+    Phantom::new(nx, ny, &shapes)
+}
+
+/// Disks `(center_x, center_y, radius)` of the packed disks phantom
+#[cfg(feature = "rand")]
+fn packed_disks(num: usize, min_radius: f64, max_radius: f64, seed: u64) -> Vec<(f64, f64, f64)> {
+    use rand::{Rng, SeedableRng};
+
+    assert!(
+        0.0 < min_radius && min_radius <= max_radius && max_radius <= 1.0,
+        "radii must satisfy 0 < min_radius <= max_radius <= 1"
+    );
+    // Number of candidates which are drawn per disk before giving up
+    const ATTEMPTS_PER_DISK: usize = 1000;
+
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+    let mut disks: Vec<(f64, f64, f64)> = Vec::with_capacity(num);
+    for _ in 0..num.saturating_mul(ATTEMPTS_PER_DISK) {
+        if disks.len() == num {
+            break;
+        }
+        let r = rng.gen_range(min_radius..=max_radius);
+        let x = rng.gen_range(-1.0 + r..=1.0 - r);
+        let y = rng.gen_range(-1.0 + r..=1.0 - r);
+        let overlaps = disks
+            .iter()
+            .any(|&(dx, dy, dr)| (x - dx).hypot(y - dy) < r + dr);
+        if !overlaps {
+            disks.push((x, y, r));
+        }
+    }
+    disks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contrast_detail_phantom, contrast_detail_shapes};
+
+    #[test]
+    fn test_contrast_detail_phantom() {
+        let (nx, ny) = (128, 128);
+        let sizes = [0.05, 0.1, 0.15, 0.2];
+        let contrasts = [0.5, 1.0, 2.0];
+        let background = 10.0;
+
+        let shapes = contrast_detail_shapes(&sizes, &contrasts);
+        assert_eq!(shapes.len(), sizes.len() * contrasts.len());
+
+        for shape in shapes.iter() {
+            let bbox = shape.on_canvas(nx, ny).bounding_box();
+            assert!(bbox.x_low > 0 && bbox.x_high < nx - 1);
+            assert!(bbox.y_low > 0 && bbox.y_high < ny - 1);
+        }
+
+        let phantom = contrast_detail_phantom(nx, ny, &sizes, &contrasts, background);
+        let mut levels = phantom
+            .data
+            .iter()
+            .map(|&x| x - background)
+            .collect::<Vec<_>>();
+        levels.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        levels.dedup();
+        assert_eq!(levels, [0.0, 0.5, 1.0, 2.0]);
+
+        // The largest disk with the highest contrast is in the lower right corner
+        let (x, y) = (nx * 7 / 8, ny / 6);
+        assert_eq!(
+            phantom.data[((ny - y - 1) * nx + x) as usize],
+            background + 2.0
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_packed_disks_phantom() {
+        use super::{packed_disks, packed_disks_phantom};
+
+        let disks = packed_disks(30, 0.05, 0.2, 7);
+        assert_eq!(disks.len(), 30);
+        for (i, &(x1, y1, r1)) in disks.iter().enumerate() {
+            assert!(x1 - r1 >= -1.0 && x1 + r1 <= 1.0 && y1 - r1 >= -1.0 && y1 + r1 <= 1.0);
+            assert!((0.05..=0.2).contains(&r1));
+            for &(x2, y2, r2) in disks.iter().skip(i + 1) {
+                assert!((x1 - x2).hypot(y1 - y2) >= r1 + r2);
+            }
+        }
+
+        // Deterministic given the seed
+        assert_eq!(disks, packed_disks(30, 0.05, 0.2, 7));
+        assert_ne!(disks, packed_disks(30, 0.05, 0.2, 8));
+        let phantom = packed_disks_phantom(128, 128, 30, 0.05, 0.2, 7).into_vec();
+        assert_eq!(
+            phantom,
+            packed_disks_phantom(128, 128, 30, 0.05, 0.2, 7).into_vec()
+        );
+        // Non-overlapping disks with intensity 1.0
+        assert!(phantom.iter().all(|&x| x == 0.0 || x == 1.0));
+
+        // Not all disks fit onto the canvas
+        assert!(packed_disks(100, 0.5, 0.5, 0).len() < 100);
+    }
+}
@@ -0,0 +1,95 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{phantom::rasterize, Phantom, Shape};
+
+/// Phantom built from shapes with an explicit z-order
+///
+/// In contrast to `Phantom::new`, where the intensities of overlapping shapes are added, each
+/// pixel of a layered phantom takes the intensity of the topmost shape covering it (painter's
+/// algorithm). The topmost shape is the shape with the highest z-order; among shapes with the same
+/// z-order, the one added last is on top. Pixels which are not covered by any shape are `0.0`.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{LayeredPhantom, Shape};
+/// let phantom = LayeredPhantom::new()
+///     .layer(1, Shape::ellipse(0.0, 0.0, 0.2, 0.2, 0.0, 2.0))
+///     .layer(0, Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0))
+///     .render(64, 64);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LayeredPhantom {
+    layers: Vec<(i32, Shape)>,
+}
+
+impl LayeredPhantom {
+    /// Creates a layered phantom without any shapes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `shape` with z-order `z`.
+    pub fn layer(mut self, z: i32, shape: Shape) -> Self {
+        self.layers.push((z, shape));
+        self
+    }
+
+    /// Renders the phantom with size `nx` times `ny`.
+    pub fn render(&self, nx: u32, ny: u32) -> Phantom {
+        let mut layers = self.layers.iter().collect::<Vec<_>>();
+        // The sort is stable, therefore shapes with equal z-order keep the order they were added in.
+        layers.sort_by_key(|(z, _)| *z);
+        let shapes = layers
+            .iter()
+            .map(|(_, shape)| shape.on_canvas(nx, ny))
+            .collect::<Vec<_>>();
+        let mut data = vec![0.0; (nx * ny) as usize];
+        rasterize(&shapes, nx, ny, |idx, _, shape| {
+            data[idx] = shape.intensity()
+        });
+        let mut phantom = Phantom::from_raw(nx, ny, data);
+        phantom.shape_count = Some(shapes.len());
+        phantom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LayeredPhantom;
+    use crate::Shape;
+
+    #[test]
+    fn test_layered_phantom() {
+        let (nx, ny) = (64, 64);
+        let large = Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0);
+        let small = Shape::rectangle(0.0, 0.0, 0.3, 0.3, 0.0, 3.0);
+        let center = ((ny - ny / 2 - 1) * nx + nx / 2) as usize;
+        let ring = ((ny - ny / 2 - 1) * nx + nx / 2 + 20) as usize;
+
+        // The small shape is on top regardless of the order in which the shapes are added
+        for phantom in [
+            LayeredPhantom::new()
+                .layer(-1, large.clone())
+                .layer(5, small.clone()),
+            LayeredPhantom::new()
+                .layer(5, small.clone())
+                .layer(-1, large.clone()),
+        ] {
+            let phantom = phantom.render(nx, ny);
+            assert_eq!(phantom.data[center].to_ne_bytes(), 3.0f64.to_ne_bytes());
+            assert_eq!(phantom.data[ring].to_ne_bytes(), 1.0f64.to_ne_bytes());
+            assert_eq!(phantom.data[0].to_ne_bytes(), 0.0f64.to_ne_bytes());
+        }
+
+        // Equal z-order: the shape added last is on top
+        let phantom = LayeredPhantom::new()
+            .layer(0, small)
+            .layer(0, large)
+            .render(nx, ny);
+        assert_eq!(phantom.data[center].to_ne_bytes(), 1.0f64.to_ne_bytes());
+    }
+}
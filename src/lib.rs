@@ -66,6 +66,19 @@
 //!
 //! This will create a phantom consisting of two ellipses.
 //!
+//! # Features
+//!
+//! * `half`: Enables the conversion of phantoms to half precision floats (`into_vec_f16`).
+//! * `image`: Enables the conversion of phantoms to images of the `image` crate (e.g.
+//!   `into_luma16_range`).
+//! * `rand`: Enables generators of random phantoms (e.g. `packed_disks_phantom`).
+//! * `rayon`: Renders the rows of a phantom in parallel and computes independent phantoms (e.g.
+//!   in `shepplogan_pyramid`) in parallel. The intensities of overlapping shapes are always
+//!   accumulated in the same order as in the serial implementation (the order of the shapes or
+//!   the order chosen by `RenderOptions::order`), therefore the results are bit-identical.
+//! * `serde`: Implements `Serialize` and `Deserialize` for `Shape`, which allows storing phantom
+//!   definitions e.g. as JSON.
+//!
 //! # References
 //!
 //! [0] Shepp, LA and Logan BF, "The Fourier reconstruction of a head section." IEEE Transactions
@@ -82,12 +95,31 @@ extern crate quickcheck;
 #[macro_use(quickcheck)]
 extern crate quickcheck_macros;
 
+mod error;
+mod float;
+mod generators;
+mod layered;
+mod metrics;
 mod phantom;
+mod render;
 mod shape;
 mod shepplogan;
+mod sink;
 
+#[cfg(feature = "rand")]
+pub use crate::generators::packed_disks_phantom;
 pub use crate::{
-    phantom::Phantom,
-    shape::Shape,
-    shepplogan::{shepplogan, shepplogan_modified},
+    error::Error,
+    float::Float,
+    generators::contrast_detail_phantom,
+    layered::LayeredPhantom,
+    metrics::{rendered_shape_metrics, shapes_outside_canonical_domain},
+    phantom::{label_map, shapes_mask, BorderMode, ImageMoments, Phantom},
+    render::{BBoxRounding, Boundary, EdgeMode, Overlap, RenderOptions, Scale, ShapeOrder},
+    shape::{phantom_to_svg, sinogram, Shape, ShapeType},
+    shepplogan::{
+        make_ct_testcase, shepplogan, shepplogan_f32, shepplogan_hu, shepplogan_modified,
+        shepplogan_modified_f32, shepplogan_pyramid,
+    },
+    sink::{render_to, PixelSink, VecSink},
 };
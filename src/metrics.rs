@@ -0,0 +1,128 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::Shape;
+
+/// Computes the centroid and the area of each of the `shapes` when rendered onto a phantom of size
+/// `nx` times `ny`.
+///
+/// For each shape, `(centroid_x, centroid_y, num_pixels)` is returned, where the centroid is the
+/// mean position of all pixels covered by the shape and `num_pixels` is the number of these
+/// pixels. As for the shapes, `(0, 0)` is the lower left pixel. Each shape is considered on its
+/// own, i.e. overlaps with other shapes are ignored. The centroid of a shape which does not cover
+/// any pixel is `(NaN, NaN)`.
+///
+/// This is useful for verifying that shapes end up where they are intended to be.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{rendered_shape_metrics, Shape};
+/// let shapes = [Shape::rectangle(0.5, 0.5, 0.25, 0.25, 0.0, 1.0)];
+/// let metrics = rendered_shape_metrics(&shapes, 64, 64);
+/// assert_eq!(metrics[0], (48.0, 48.0, 81));
+/// ```
+pub fn rendered_shape_metrics(shapes: &[Shape], nx: u32, ny: u32) -> Vec<(f64, f64, usize)> {
+    shapes
+        .iter()
+        .map(|shape| {
+            let shape = shape.on_canvas(nx, ny);
+            let bbox = shape.bounding_box();
+            let (mut sum_x, mut sum_y, mut count) = (0.0, 0.0, 0);
+            for x in bbox.x_low..=bbox.x_high {
+                for y in bbox.y_low..=bbox.y_high {
+                    if shape.inside_pixel(x, y) {
+                        sum_x += f64::from(x);
+                        sum_y += f64::from(y);
+                        count += 1;
+                    }
+                }
+            }
+            if count == 0 {
+                (f64::NAN, f64::NAN, 0)
+            } else {
+                (sum_x / count as f64, sum_y / count as f64, count)
+            }
+        })
+        .collect()
+}
+
+/// Returns the indices of the `shapes` which are not entirely inside of the canonical domain
+/// `[-1, 1] x [-1, 1]`.
+///
+/// This is a quality check for the definition of phantoms, see
+/// `Shape::is_within_canonical_domain`. An empty result means that no shape is clipped by the
+/// canonical domain.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{shapes_outside_canonical_domain, Shape};
+/// let shapes = [
+///     Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+///     Shape::ellipse(0.0, 0.0, 1.2, 0.5, 0.0, 1.0),
+/// ];
+/// assert_eq!(shapes_outside_canonical_domain(&shapes), [1]);
+/// ```
+pub fn shapes_outside_canonical_domain(shapes: &[Shape]) -> Vec<usize> {
+    shapes
+        .iter()
+        .enumerate()
+        .filter(|(_, shape)| !shape.is_within_canonical_domain())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rendered_shape_metrics, shapes_outside_canonical_domain};
+    use crate::{shepplogan::Preset, Shape};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_rendered_shape_metrics() {
+        let (nx, ny) = (256, 256);
+        let radius = 0.5;
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, radius, radius, 0.0, 1.0),
+            Shape::ellipse(-0.5, 0.25, 0.2, 0.1, 30.0, 1.0),
+            Shape::ellipse(3.0, 3.0, 0.1, 0.1, 0.0, 1.0),
+        ];
+        let metrics = rendered_shape_metrics(&shapes, nx, ny);
+        assert_eq!(metrics.len(), shapes.len());
+
+        // Centered circle: The canvas center is at pixel (128, 128)
+        let (cx, cy, area) = metrics[0];
+        assert_abs_diff_eq!(cx, 128.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(cy, 128.0, epsilon = 1e-9);
+        let r = radius * 128.0;
+        let expected = std::f64::consts::PI * r * r;
+        assert!((area as f64 - expected).abs() / expected < 0.01);
+
+        // Off-center ellipse
+        let (cx, cy, _) = metrics[1];
+        assert_abs_diff_eq!(cx, 128.0 - 0.5 * 128.0, epsilon = 0.5);
+        assert_abs_diff_eq!(cy, 128.0 + 0.25 * 128.0, epsilon = 0.5);
+
+        // Outside of the canvas
+        let (cx, cy, area) = metrics[2];
+        assert!(cx.is_nan() && cy.is_nan());
+        assert_eq!(area, 0);
+    }
+
+    #[test]
+    fn test_shapes_outside_canonical_domain() {
+        assert!(shapes_outside_canonical_domain(&Preset::Original.shapes()).is_empty());
+        assert!(shapes_outside_canonical_domain(&Preset::Modified.shapes()).is_empty());
+
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+            Shape::rectangle(0.8, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(0.0, 0.0, 0.2, 0.2, 0.0, 1.0),
+            Shape::ellipse(0.0, 0.0, 3.0, 3.0, 0.0, 1.0),
+        ];
+        assert_eq!(shapes_outside_canonical_domain(&shapes), [1, 3]);
+    }
+}
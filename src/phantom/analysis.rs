@@ -0,0 +1,465 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::collections::HashMap;
+
+use super::Phantom;
+use crate::Error;
+
+/// Image moments of a phantom up to third order, see `Phantom::moments`
+///
+/// The moments are indexed as `[p][q]` for the moment of order `p` in `x` and order `q` in `y`.
+/// Only the entries with `p + q <= 3` are computed, all other entries are zero.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ImageMoments {
+    /// raw moments `m_pq = sum(x^p * y^q * value)`
+    pub raw: [[f64; 4]; 4],
+    /// central moments `mu_pq`, i.e. the raw moments about the center of mass
+    pub central: [[f64; 4]; 4],
+    /// normalized central moments `eta_pq = mu_pq / mu_00^(1 + (p + q) / 2)`, which are invariant
+    /// under translation and uniform scaling of the coordinates
+    pub normalized: [[f64; 4]; 4],
+    /// the seven Hu invariants, which are additionally invariant under rotation
+    pub hu: [f64; 7],
+}
+
+impl Phantom {
+    /// Computes the radially averaged intensity profile around `center`.
+    ///
+    /// The distances from `center = (x, y)` (in pixels, `(0, 0)` is the lower left pixel) up to
+    /// `max_radius` are divided into `bins` bins of equal width. Bin `i` contains the mean value of
+    /// all pixels whose distance `r` to the center satisfies `i * w <= r < (i + 1) * w` with
+    /// `w = max_radius / bins`. Pixels further away than `max_radius` are ignored. Bins which do
+    /// not contain any pixel are `NaN`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let disk = Phantom::new(64, 64, &[Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)]);
+    /// let profile = disk.radial_profile((32.0, 32.0), 4, 32.0);
+    /// assert_eq!(profile[0], 1.0);
+    /// assert_eq!(profile[3], 0.0);
+    /// ```
+    pub fn radial_profile(&self, center: (f64, f64), bins: usize, max_radius: f64) -> Vec<f64> {
+        if bins == 0 {
+            return Vec::new();
+        }
+        let mut sums = vec![0.0; bins];
+        let mut counts = vec![0usize; bins];
+        let width = max_radius / bins as f64;
+        for x in 0..self.nx {
+            for y in 0..self.ny {
+                let r = (f64::from(x) - center.0).hypot(f64::from(y) - center.1);
+                if r >= max_radius {
+                    continue;
+                }
+                // Guard against rounding up to `bins` for `r` just below `max_radius`
+                let bin = ((r / width) as usize).min(bins - 1);
+                sums[bin] += self.data[self.index(x, y)];
+                counts[bin] += 1;
+            }
+        }
+        sums.iter()
+            .zip(counts.iter())
+            .map(|(&sum, &count)| {
+                if count > 0 {
+                    sum / count as f64
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect()
+    }
+
+    /// Computes the Dice coefficient of the foregrounds of the phantom and `other`.
+    ///
+    /// The foreground of a phantom consists of all pixels whose value is larger than `threshold`.
+    /// The Dice coefficient of the foregrounds `A` and `B` is `2 |A ∩ B| / (|A| + |B|)`, which is
+    /// `1.0` for identical and `0.0` for disjoint foregrounds. If both foregrounds are empty, the
+    /// result is `1.0`.
+    ///
+    /// Returns `Error::DimensionMismatch` if the dimensions of the phantoms differ.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, shepplogan_modified};
+    /// let dice = shepplogan(64, 64)
+    ///     .dice(&shepplogan_modified(64, 64), 0.05)
+    ///     .unwrap();
+    /// assert!(dice > 0.9);
+    /// ```
+    pub fn dice(&self, other: &Phantom, threshold: f64) -> Result<f64, Error> {
+        let (intersection, union) = self.foreground_overlap(other, threshold)?;
+        Ok(if union == 0 {
+            1.0
+        } else {
+            2.0 * intersection as f64 / (union + intersection) as f64
+        })
+    }
+
+    /// Computes the Jaccard index of the foregrounds of the phantom and `other`.
+    ///
+    /// The Jaccard index of the foregrounds `A` and `B` is `|A ∩ B| / |A ∪ B|`, see `dice` for the
+    /// definition of the foregrounds. If both foregrounds are empty, the result is `1.0`.
+    ///
+    /// Returns `Error::DimensionMismatch` if the dimensions of the phantoms differ.
+    pub fn jaccard(&self, other: &Phantom, threshold: f64) -> Result<f64, Error> {
+        let (intersection, union) = self.foreground_overlap(other, threshold)?;
+        Ok(if union == 0 {
+            1.0
+        } else {
+            intersection as f64 / union as f64
+        })
+    }
+
+    /// Returns the distinct values of the phantom and the number of pixels with each value.
+    ///
+    /// The values are compared by their bit patterns, therefore `0.0` and `-0.0` are separate
+    /// levels and `NaN` values are grouped as well. The levels are sorted by value, where the order
+    /// of `f64::total_cmp` is used for `-0.0` and `NaN`. This is useful for verifying that a
+    /// phantom built from discrete intensities contains the expected classes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let phantom = Phantom::new(64, 64, &[Shape::rectangle(0.0, 0.0, 1.0, 1.0, 0.0, 1.0)]);
+    /// assert_eq!(phantom.intensity_levels(), vec![(0.0, 64 * 64 - 33 * 33), (1.0, 33 * 33)]);
+    /// ```
+    pub fn intensity_levels(&self) -> Vec<(f64, usize)> {
+        let mut counts = HashMap::new();
+        for x in self.data.iter() {
+            *counts.entry(x.to_bits()).or_insert(0) += 1;
+        }
+        let mut levels = counts
+            .into_iter()
+            .map(|(bits, count)| (f64::from_bits(bits), count))
+            .collect::<Vec<_>>();
+        levels.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        levels
+    }
+
+    /// Computes the intensity weighted centroid of the phantom.
+    ///
+    /// Returns the center of mass `(x, y)` in pixels, where, as for the shapes, `(0, 0)` is the
+    /// lower left pixel. The values are used as weights without any clamping. Returns `None` if
+    /// the sum of all values is zero or not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let disk = Phantom::new(64, 64, &[Shape::ellipse(0.5, 0.0, 0.2, 0.2, 0.0, 1.0)]);
+    /// assert_eq!(disk.center_of_mass(), Some((48.0, 32.0)));
+    /// ```
+    pub fn center_of_mass(&self) -> Option<(f64, f64)> {
+        let (mut mass, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+        for y in 0..self.ny {
+            for x in 0..self.nx {
+                let value = self.data[self.index(x, y)];
+                mass += value;
+                sum_x += value * f64::from(x);
+                sum_y += value * f64::from(y);
+            }
+        }
+        if mass == 0.0 || !mass.is_finite() {
+            return None;
+        }
+        Some((sum_x / mass, sum_y / mass))
+    }
+
+    /// Computes the image moments of the phantom up to third order and the Hu invariants.
+    ///
+    /// As for `center_of_mass`, the values are used as weights and the coordinates are given in
+    /// pixels, where `(0, 0)` is the lower left pixel. If the sum of all values is zero, the
+    /// central and normalized moments and the Hu invariants are `NaN`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let phantom = shepplogan(128, 128);
+    /// let hu = phantom.moments().hu;
+    /// let rotated = phantom.rotate_90().moments().hu;
+    /// assert!((hu[0] - rotated[0]).abs() < 1e-12);
+    /// ```
+    pub fn moments(&self) -> ImageMoments {
+        // Sums `sum((x - cx)^p * (y - cy)^q * value)` for `p + q <= 3`
+        let sums = |cx: f64, cy: f64| {
+            let mut sums = [[0.0; 4]; 4];
+            for y in 0..self.ny {
+                for x in 0..self.nx {
+                    let value = self.data[self.index(x, y)];
+                    let (dx, dy) = (f64::from(x) - cx, f64::from(y) - cy);
+                    for (p, row) in sums.iter_mut().enumerate() {
+                        for (q, sum) in row.iter_mut().take(4 - p).enumerate() {
+                            *sum += dx.powi(p as i32) * dy.powi(q as i32) * value;
+                        }
+                    }
+                }
+            }
+            sums
+        };
+        let raw = sums(0.0, 0.0);
+        let m00 = raw[0][0];
+        let central = sums(raw[1][0] / m00, raw[0][1] / m00);
+        let mut normalized = [[0.0; 4]; 4];
+        for (p, row) in normalized.iter_mut().enumerate() {
+            for (q, eta) in row.iter_mut().take(4 - p).enumerate() {
+                *eta = central[p][q] / m00.powf(1.0 + (p + q) as f64 / 2.0);
+            }
+        }
+
+        let n = &normalized;
+        let (a, b) = (n[3][0] + n[1][2], n[2][1] + n[0][3]);
+        let (c, d) = (n[3][0] - 3.0 * n[1][2], 3.0 * n[2][1] - n[0][3]);
+        let hu = [
+            n[2][0] + n[0][2],
+            (n[2][0] - n[0][2]).powi(2) + 4.0 * n[1][1].powi(2),
+            c.powi(2) + d.powi(2),
+            a.powi(2) + b.powi(2),
+            c * a * (a.powi(2) - 3.0 * b.powi(2)) + d * b * (3.0 * a.powi(2) - b.powi(2)),
+            (n[2][0] - n[0][2]) * (a.powi(2) - b.powi(2)) + 4.0 * n[1][1] * a * b,
+            d * a * (a.powi(2) - 3.0 * b.powi(2)) - c * b * (3.0 * a.powi(2) - b.powi(2)),
+        ];
+        ImageMoments {
+            raw,
+            central,
+            normalized,
+            hu,
+        }
+    }
+
+    /// Computes a checksum of the dimensions and the values of the phantom.
+    ///
+    /// The checksum is the 64 bit FNV-1a hash of `nx` and `ny` followed by the bit patterns of all
+    /// values in the order of `into_vec`, each in little endian byte order. It therefore only
+    /// depends on the exact bits of the data and not on the platform, which allows asserting that
+    /// a phantom has not changed without storing a reference image. Note that `0.0` and `-0.0` as
+    /// well as different `NaN` payloads result in different checksums. The pixel spacing is not
+    /// taken into account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// assert_eq!(shepplogan(64, 64).checksum(), shepplogan(64, 64).checksum());
+    /// assert_ne!(shepplogan(64, 64).checksum(), shepplogan(64, 32).checksum());
+    /// ```
+    pub fn checksum(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+        let update = |hash: u64, bytes: &[u8]| {
+            bytes.iter().fold(hash, |hash, &byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            })
+        };
+        let hash = update(OFFSET_BASIS, &self.nx.to_le_bytes());
+        let hash = update(hash, &self.ny.to_le_bytes());
+        self.data
+            .iter()
+            .fold(hash, |hash, x| update(hash, &x.to_bits().to_le_bytes()))
+    }
+
+    /// Returns the number of pixels in the intersection and in the union of the foregrounds of
+    /// the phantom and `other`.
+    fn foreground_overlap(&self, other: &Phantom, threshold: f64) -> Result<(usize, usize), Error> {
+        if (self.nx, self.ny) != (other.nx, other.ny) {
+            return Err(Error::DimensionMismatch {
+                expected: self.data.len(),
+                actual: other.data.len(),
+            });
+        }
+        Ok(self.data.iter().zip(other.data.iter()).fold(
+            (0, 0),
+            |(intersection, union), (&a, &b)| {
+                let (a, b) = (a > threshold, b > threshold);
+                (
+                    intersection + usize::from(a && b),
+                    union + usize::from(a || b),
+                )
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{shepplogan, Error, Phantom, Shape};
+
+    #[test]
+    fn test_radial_profile() {
+        let (nx, ny) = (64, 48);
+        let center = (30.0, 20.0);
+        let sigma = 5.0f64;
+        let mut blob = Phantom::from_raw(nx, ny, vec![0.0; (nx * ny) as usize]);
+        for x in 0..nx {
+            for y in 0..ny {
+                let r2 = (f64::from(x) - center.0).powi(2) + (f64::from(y) - center.1).powi(2);
+                let idx = blob.index(x, y);
+                blob.data[idx] = (-r2 / (2.0 * sigma.powi(2))).exp();
+            }
+        }
+
+        let profile = blob.radial_profile(center, 10, 20.0);
+        assert_eq!(profile.len(), 10);
+        assert!(profile.windows(2).all(|w| w[0] > w[1]));
+
+        // The only pixel at distance zero is the center
+        let profile = blob.radial_profile(center, 40, 20.0);
+        assert_eq!(profile[0].to_ne_bytes(), 1.0f64.to_ne_bytes());
+        // There are no pixels at distances in [0.5, 1.0)
+        assert!(profile[1].is_nan());
+
+        assert!(blob.radial_profile(center, 0, 20.0).is_empty());
+    }
+
+    #[test]
+    fn test_dice_jaccard() {
+        let phantom = shepplogan(64, 48);
+        assert_eq!(phantom.dice(&phantom, 0.5).unwrap(), 1.0);
+        assert_eq!(phantom.jaccard(&phantom, 0.5).unwrap(), 1.0);
+
+        let left = Phantom::new(64, 48, &[Shape::rectangle(-0.5, 0.0, 0.5, 0.5, 0.0, 1.0)]);
+        let right = Phantom::new(64, 48, &[Shape::rectangle(0.5, 0.0, 0.5, 0.5, 0.0, 1.0)]);
+        assert_eq!(left.dice(&right, 0.5).unwrap(), 0.0);
+        assert_eq!(left.jaccard(&right, 0.5).unwrap(), 0.0);
+
+        // Two halves of the foreground of `large` overlap with `small`
+        let large = Phantom::from_data(4, 1, vec![1.0, 1.0, 1.0, 1.0]).unwrap();
+        let small = Phantom::from_data(4, 1, vec![1.0, 1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(large.dice(&small, 0.5).unwrap(), 2.0 * 2.0 / 6.0);
+        assert_eq!(large.jaccard(&small, 0.5).unwrap(), 0.5);
+        // Empty foregrounds
+        assert_eq!(small.dice(&small, 1.0).unwrap(), 1.0);
+
+        assert!(matches!(
+            phantom.dice(&shepplogan(48, 64), 0.5),
+            Err(Error::DimensionMismatch { .. })
+        ));
+        assert!(phantom.jaccard(&shepplogan(64, 64), 0.5).is_err());
+    }
+
+    #[test]
+    fn test_intensity_levels() {
+        let shapes = [
+            Shape::rectangle(-0.5, 0.0, 0.5, 1.0, 0.0, 2.0),
+            Shape::rectangle(0.5, 0.0, 0.5, 1.0, 0.0, -1.0),
+        ];
+        let phantom = Phantom::new(64, 64, &shapes);
+        let count = |value: f64| phantom.data.iter().filter(|&&x| x == value).count();
+        let (low, high) = (count(-1.0), count(2.0));
+        assert!(low > 0 && low == high);
+        assert_eq!(
+            phantom.intensity_levels(),
+            vec![(-1.0, low), (0.0, 64 * 64 - low - high), (2.0, high)]
+        );
+
+        let phantom = Phantom::from_data(5, 1, vec![0.0, f64::NAN, -0.0, 0.0, f64::NAN]).unwrap();
+        let levels = phantom.intensity_levels();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels[0].0.to_ne_bytes(), (-0.0f64).to_ne_bytes());
+        assert_eq!(levels[1].0.to_ne_bytes(), 0.0f64.to_ne_bytes());
+        assert!(levels[2].0.is_nan());
+        assert_eq!(levels.iter().map(|l| l.1).collect::<Vec<_>>(), [1, 2, 2]);
+        assert!(Phantom::from_raw(0, 0, Vec::new())
+            .intensity_levels()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_center_of_mass() {
+        // Pixel `(nx / 2, ny / 2)` samples the center of the canvas
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.8, 0.6, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 0.4, 0.4, 0.0, 0.5),
+        ];
+        let (x, y) = Phantom::new(64, 48, &shapes).center_of_mass().unwrap();
+        assert_abs_diff_eq!(x, 32.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(y, 24.0, epsilon = 1e-9);
+        // The Shepp-Logan phantom is approximately symmetric along `x`
+        let (x, _) = shepplogan(64, 64).center_of_mass().unwrap();
+        assert_abs_diff_eq!(x, 32.0, epsilon = 0.1);
+
+        // Off-center disk centered on pixel (16, 40)
+        let disk = [Shape::ellipse(-0.5, 0.25, 0.2, 0.2, 0.0, 3.0)];
+        let (x, y) = Phantom::new(64, 64, &disk).center_of_mass().unwrap();
+        assert_abs_diff_eq!(x, 16.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(y, 40.0, epsilon = 1e-9);
+        assert!(Phantom::new(64, 64, &[]).center_of_mass().is_none());
+        assert!(Phantom::from_raw(0, 0, Vec::new())
+            .center_of_mass()
+            .is_none());
+    }
+
+    #[test]
+    fn test_moments() {
+        // Two pixels of value 1 at (1, 0) and (3, 2)
+        let phantom = Phantom::from_data(
+            4,
+            3,
+            vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        )
+        .unwrap();
+        let moments = phantom.moments();
+        assert_eq!(moments.raw[0][0], 2.0);
+        assert_eq!(moments.raw[1][0], 4.0);
+        assert_eq!(moments.raw[0][1], 2.0);
+        assert_eq!(moments.raw[1][1], 6.0);
+        assert_eq!(moments.raw[2][0], 10.0);
+        assert_eq!(moments.raw[0][3], 8.0);
+        assert_eq!(moments.raw[2][2], 0.0);
+        // Both pixels are at a distance of (1, 1) from the center of mass at (2, 1)
+        assert_eq!(moments.central[1][0], 0.0);
+        assert_eq!(moments.central[2][0], 2.0);
+        assert_eq!(moments.central[1][1], 2.0);
+        assert_eq!(moments.central[2][1], 0.0);
+        assert_eq!(moments.normalized[0][0], 1.0);
+        assert_eq!(moments.normalized[2][0], 2.0 / 4.0);
+        assert_eq!(moments.hu[0], 1.0);
+
+        // The Hu invariants do not change under rotations
+        let phantom = shepplogan(96, 64);
+        let hu = phantom.moments().hu;
+        let mut rotated = phantom.clone();
+        for _ in 0..3 {
+            rotated = rotated.rotate_90();
+            for (a, b) in hu.iter().zip(rotated.moments().hu.iter()) {
+                assert_abs_diff_eq!(a, b, epsilon = 1e-9 * a.abs().max(1e-12));
+            }
+        }
+
+        let moments = Phantom::new(8, 8, &[]).moments();
+        assert_eq!(moments.raw, [[0.0; 4]; 4]);
+        assert!(moments.hu.iter().all(|h| h.is_nan()));
+    }
+
+    #[test]
+    fn test_checksum() {
+        // Reference values of the FNV-1a hash
+        assert_eq!(
+            Phantom::from_raw(0, 0, Vec::new()).checksum(),
+            0xa8c7_f832_281a_39c5
+        );
+        assert_eq!(shepplogan(128, 128).checksum(), 0x54e2_57a8_6e16_34c3);
+
+        let phantom = shepplogan(64, 48);
+        let checksum = phantom.checksum();
+        assert_eq!(phantom.clone().with_spacing(2.0, 2.0).checksum(), checksum);
+        for idx in [0, 1000, 64 * 48 - 1] {
+            let mut changed = phantom.clone();
+            changed.data[idx] = f64::from_bits(changed.data[idx].to_bits() ^ 1);
+            assert_ne!(changed.checksum(), checksum);
+        }
+        let mut changed = phantom.clone();
+        changed.data[0] = -0.0;
+        assert_ne!(changed.checksum(), checksum);
+        assert_ne!(phantom.transpose().checksum(), checksum);
+    }
+}
@@ -0,0 +1,296 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::Phantom;
+
+/// Defines how pixels outside of the phantom are treated by filters
+///
+/// The examples show how the row `a b c d` is extended to the left and to the right.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BorderMode {
+    /// Pixels outside of the phantom are zero: `0 0 | a b c d | 0 0`
+    Zero,
+    /// The phantom is mirrored at its border: `b a | a b c d | d c`
+    Reflect,
+    /// The border pixels are repeated: `a a | a b c d | d d`
+    Replicate,
+    /// The phantom is periodically repeated: `c d | a b c d | a b`
+    Wrap,
+}
+
+impl BorderMode {
+    /// Maps the (possibly out of range) index `i` onto `[0, n)`.
+    ///
+    /// Returns `None` if the pixel is outside of the phantom and should be treated as zero.
+    #[inline(always)]
+    pub(crate) fn index(&self, i: i64, n: i64) -> Option<usize> {
+        if (0..n).contains(&i) {
+            return Some(i as usize);
+        }
+        match self {
+            BorderMode::Zero => None,
+            BorderMode::Reflect => {
+                let i = i.rem_euclid(2 * n);
+                Some(if i >= n { 2 * n - 1 - i } else { i } as usize)
+            }
+            BorderMode::Replicate => Some(i.clamp(0, n - 1) as usize),
+            BorderMode::Wrap => Some(i.rem_euclid(n) as usize),
+        }
+    }
+}
+
+impl Phantom {
+    /// Convolves the phantom with `kernel`.
+    ///
+    /// The kernel has `kx` columns and `ky` rows and is stored in the same layout as the phantom,
+    /// i.e. row by row starting at the top. Both `kx` and `ky` must be odd such that the kernel has
+    /// a well defined center. Pixels outside of the phantom are treated according to `border`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `kx` or `ky` are even or if the length of `kernel` is not `kx * ky`.
+    pub fn convolve(&self, kernel: &[f64], kx: u32, ky: u32, border: BorderMode) -> Phantom {
+        assert!(kx % 2 == 1 && ky % 2 == 1, "kernel dimensions must be odd");
+        assert_eq!(
+            kernel.len(),
+            kx as usize * ky as usize,
+            "invalid kernel size"
+        );
+
+        let (nx, ny) = (i64::from(self.nx), i64::from(self.ny));
+        let (kx, ky) = (i64::from(kx), i64::from(ky));
+        let (cx, cy) = (kx / 2, ky / 2);
+        let mut data = vec![0.0; self.data.len()];
+
+        for row in 0..ny {
+            for col in 0..nx {
+                let mut acc = 0.0;
+                for krow in 0..ky {
+                    let Some(r) = border.index(row + cy - krow, ny) else {
+                        continue;
+                    };
+                    for kcol in 0..kx {
+                        let Some(c) = border.index(col + cx - kcol, nx) else {
+                            continue;
+                        };
+                        acc += kernel[(krow * kx + kcol) as usize] * self.data[r * nx as usize + c];
+                    }
+                }
+                data[(row * nx + col) as usize] = acc;
+            }
+        }
+
+        let mut phantom = Phantom::from_raw(self.nx, self.ny, data);
+        phantom.spacing = self.spacing;
+        phantom.shape_count = self.shape_count;
+        phantom
+    }
+
+    /// Blurs the phantom with a Gaussian kernel with standard deviation `sigma` (in pixels).
+    ///
+    /// The kernel is truncated at a radius of `ceil(3 * sigma)` pixels, see
+    /// `gaussian_blur_with_radius` for details.
+    pub fn gaussian_blur(&self, sigma: f64, border: BorderMode) -> Phantom {
+        if sigma.is_nan() || sigma <= 0.0 {
+            return self.clone();
+        }
+        self.gaussian_blur_with_radius(sigma, (3.0 * sigma).ceil() as u32, border)
+    }
+
+    /// Blurs the phantom with a Gaussian kernel with standard deviation `sigma` (in pixels), which
+    /// is truncated at `radius` pixels.
+    ///
+    /// The kernel covers `2 * radius + 1` pixels in each direction and is normalized to sum up to
+    /// one, therefore the truncation slightly sharpens the blur compared to an infinite Gaussian
+    /// (a radius of `3 * sigma` covers 99.7 % of its weight). Since the Gaussian is separable, the
+    /// phantom is first convolved with a one-dimensional kernel along `x` and then along `y`,
+    /// which requires `O(radius)` instead of `O(radius²)` operations per pixel. Pixels outside of
+    /// the phantom are treated according to `border` in both passes, which gives the same result
+    /// as a convolution with the two-dimensional kernel (up to rounding errors). A `sigma` which
+    /// is not positive leaves the phantom unchanged. The radius is limited to the larger
+    /// dimension of the phantom, which bounds the size of the kernel for very large values of
+    /// `radius` (or `sigma` in `gaussian_blur`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, BorderMode};
+    /// let blurred = shepplogan(128, 128).gaussian_blur_with_radius(2.0, 4, BorderMode::Zero);
+    /// ```
+    pub fn gaussian_blur_with_radius(
+        &self,
+        sigma: f64,
+        radius: u32,
+        border: BorderMode,
+    ) -> Phantom {
+        if sigma.is_nan() || sigma <= 0.0 {
+            return self.clone();
+        }
+        // Keeps `2 * radius + 1` within a `u32`
+        let radius = radius.min(self.nx.max(self.ny)).min(u32::MAX / 2);
+        let radius = i64::from(radius);
+        let mut kernel = (-radius..=radius)
+            .map(|x| (-((x * x) as f64) / (2.0 * sigma.powi(2))).exp())
+            .collect::<Vec<_>>();
+        let sum: f64 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|k| *k /= sum);
+        let size = u32::try_from(kernel.len()).expect("radius is limited to u32::MAX / 2");
+        self.convolve(&kernel, size, 1, border)
+            .convolve(&kernel, 1, size, border)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use super::BorderMode;
+    use crate::{shepplogan, Phantom, Shape};
+
+    #[test]
+    fn test_border_mode_index() {
+        let n = 4;
+        let indices = |mode: BorderMode| {
+            (-3..7)
+                .map(|i| mode.index(i, n))
+                .collect::<Vec<Option<usize>>>()
+        };
+        assert_eq!(
+            indices(BorderMode::Zero),
+            [-1, -1, -1, 0, 1, 2, 3, -1, -1, -1].map(|i: i64| usize::try_from(i).ok())
+        );
+        assert_eq!(
+            indices(BorderMode::Reflect),
+            [2, 1, 0, 0, 1, 2, 3, 3, 2, 1].map(Some)
+        );
+        assert_eq!(
+            indices(BorderMode::Replicate),
+            [0, 0, 0, 0, 1, 2, 3, 3, 3, 3].map(Some)
+        );
+        assert_eq!(
+            indices(BorderMode::Wrap),
+            [1, 2, 3, 0, 1, 2, 3, 0, 1, 2].map(Some)
+        );
+    }
+
+    #[test]
+    fn test_convolve_constant() {
+        let (nx, ny) = (16, 12);
+        let phantom = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 0.0, 3.0, 3.0, 0.0, 1.0)]);
+        assert!(phantom.data.iter().all(|&x| x == 1.0));
+
+        let kernel = [1.0 / 9.0; 9];
+        for mode in [BorderMode::Reflect, BorderMode::Replicate, BorderMode::Wrap] {
+            let convolved = phantom.convolve(&kernel, 3, 3, mode);
+            for &x in convolved.data.iter() {
+                assert_abs_diff_eq!(x, 1.0, epsilon = 1e-12);
+            }
+        }
+
+        let convolved = phantom.convolve(&kernel, 3, 3, BorderMode::Zero);
+        let index = |x: u32, y: u32| ((ny - y - 1) * nx + x) as usize;
+        assert_abs_diff_eq!(convolved.data[index(0, 0)], 4.0 / 9.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(convolved.data[index(5, 0)], 6.0 / 9.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(convolved.data[index(0, 5)], 6.0 / 9.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(convolved.data[index(5, 5)], 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_convolve_orientation() {
+        let (nx, ny) = (5, 5);
+        let mut data = vec![0.0; 25];
+        data[12] = 1.0;
+        let phantom = Phantom::from_raw(nx, ny, data);
+
+        // Convolution with an impulse reproduces the (non-symmetric) kernel
+        let kernel = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+        let convolved = phantom.convolve(&kernel, 3, 3, BorderMode::Zero);
+        for row in 0..3 {
+            for col in 0..3 {
+                assert_eq!(
+                    convolved.data[(row + 1) * 5 + col + 1],
+                    kernel[row * 3 + col]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_gaussian_blur() {
+        let (nx, ny) = (32, 32);
+        let phantom = Phantom::new(nx, ny, &[Shape::rectangle(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)]);
+
+        let blurred = phantom.gaussian_blur(1.5, BorderMode::Zero);
+        let sum: f64 = phantom.data.iter().sum();
+        let sum_blurred: f64 = blurred.data.iter().sum();
+        assert_abs_diff_eq!(sum, sum_blurred, epsilon = 1e-9);
+        assert!(blurred.data.iter().any(|&x| x > 0.0 && x < 1.0));
+
+        assert_eq!(
+            phantom.gaussian_blur(0.0, BorderMode::Zero).data,
+            phantom.data
+        );
+
+        // The kernel is limited by the size of the phantom for very large sigmas
+        for sigma in [1e200, f64::INFINITY] {
+            let blurred = phantom.gaussian_blur(sigma, BorderMode::Wrap);
+            assert_eq!(blurred.dimensions(), (nx, ny));
+            let sum_blurred: f64 = blurred.data.iter().sum();
+            assert_abs_diff_eq!(sum, sum_blurred, epsilon = 1e-9);
+        }
+        assert_eq!(
+            phantom
+                .gaussian_blur_with_radius(4.0, u32::MAX, BorderMode::Reflect)
+                .data,
+            phantom
+                .gaussian_blur_with_radius(4.0, nx, BorderMode::Reflect)
+                .data
+        );
+    }
+
+    #[test]
+    fn test_gaussian_blur_separable() {
+        let phantom = shepplogan(40, 30);
+        for (sigma, radius) in [(1.5, 5), (0.7, 1), (2.0, 3)] {
+            // Two-dimensional kernel
+            let r = radius as i64;
+            let size = 2 * radius + 1;
+            let mut kernel = Vec::new();
+            for y in -r..=r {
+                for x in -r..=r {
+                    kernel.push((-((x * x + y * y) as f64) / (2.0 * sigma * sigma)).exp());
+                }
+            }
+            let sum: f64 = kernel.iter().sum();
+            kernel.iter_mut().for_each(|k| *k /= sum);
+
+            for mode in [
+                BorderMode::Zero,
+                BorderMode::Reflect,
+                BorderMode::Replicate,
+                BorderMode::Wrap,
+            ] {
+                let separable = phantom.gaussian_blur_with_radius(sigma, radius, mode);
+                let direct = phantom.convolve(&kernel, size, size, mode);
+                for (a, b) in separable.data.iter().zip(direct.data.iter()) {
+                    assert_abs_diff_eq!(a, b, epsilon = 1e-12);
+                }
+            }
+        }
+        assert_eq!(
+            phantom
+                .gaussian_blur_with_radius(f64::NAN, 2, BorderMode::Zero)
+                .data,
+            phantom.data
+        );
+        // A radius of zero is the identity
+        assert_eq!(
+            phantom
+                .gaussian_blur_with_radius(1.0, 0, BorderMode::Zero)
+                .data,
+            phantom.data
+        );
+    }
+}
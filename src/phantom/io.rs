@@ -0,0 +1,271 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use std::io::Write;
+
+use super::Phantom;
+use crate::Error;
+
+impl Phantom {
+    /// Writes the phantom as binary portable graymap (PGM, `P5`).
+    ///
+    /// The values of the phantom are linearly mapped from `[min, max]` onto `[0, max_val]`. If all
+    /// values are equal, all pixels are set to `0`. For `max_val < 256` each pixel is written as a
+    /// single byte, otherwise as two bytes in big endian order (as required by the format). The
+    /// image is written upright, i.e. the top row of the phantom is written first.
+    ///
+    /// Returns `Error::InvalidParameter` if `max_val` is `0` and `Error::Io` if writing fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan_modified;
+    /// let mut buffer = Vec::new();
+    /// shepplogan_modified(64, 80).write_pgm(&mut buffer, 255).unwrap();
+    /// assert!(buffer.starts_with(b"P5\n64 80\n255\n"));
+    /// ```
+    pub fn write_pgm<W: Write>(&self, mut w: W, max_val: u16) -> Result<(), Error> {
+        if max_val == 0 {
+            return Err(Error::InvalidParameter(
+                "maximum value of PGM must be positive",
+            ));
+        }
+        let (min, max) = self.compute_extrema();
+        let range = max - min;
+        let max_val_f = f64::from(max_val);
+        let to_int = |x: f64| {
+            if range > 0.0 {
+                ((x - min) / range * max_val_f).round() as u16
+            } else {
+                0
+            }
+        };
+
+        write!(w, "P5\n{} {}\n{}\n", self.nx, self.ny, max_val)?;
+        let mut row = Vec::with_capacity(self.nx as usize * 2);
+        for chunk in self.data.chunks(self.nx.max(1) as usize) {
+            row.clear();
+            for &x in chunk {
+                let val = to_int(x);
+                if max_val < 256 {
+                    row.push(val as u8);
+                } else {
+                    row.extend_from_slice(&val.to_be_bytes());
+                }
+            }
+            w.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the phantom as NumPy array file (`.npy`, version 1.0).
+    ///
+    /// The array has the shape `(ny, nx)`, the data type `<f8` (little endian `f64`) and is
+    /// stored in C order. The phantom is written upright, i.e. the first row of the array is the
+    /// top row of the phantom, such that `numpy.load` returns the same layout as `into_vec`. As
+    /// required by the format, the header is padded with spaces such that the data starts at a
+    /// multiple of 64 bytes.
+    ///
+    /// Returns `Error::Io` if writing fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let mut buffer = Vec::new();
+    /// shepplogan(64, 48).write_npy(&mut buffer).unwrap();
+    /// assert!(buffer.starts_with(b"\x93NUMPY\x01\x00"));
+    /// assert_eq!(buffer.len(), 128 + 64 * 48 * 8);
+    /// ```
+    pub fn write_npy<W: Write>(&self, mut w: W) -> Result<(), Error> {
+        const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+        let header = format!(
+            "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.ny, self.nx
+        );
+        // Magic string, header length (u16) and header terminated by a newline
+        let unpadded = MAGIC.len() + 2 + header.len() + 1;
+        let padding = (64 - unpadded % 64) % 64;
+        let header = format!("{:<width$}\n", header, width = header.len() + padding);
+
+        w.write_all(MAGIC)?;
+        w.write_all(&(header.len() as u16).to_le_bytes())?;
+        w.write_all(header.as_bytes())?;
+        let mut row = Vec::with_capacity(self.nx as usize * 8);
+        for chunk in self.data.chunks(self.nx.max(1) as usize) {
+            row.clear();
+            for x in chunk {
+                row.extend_from_slice(&x.to_le_bytes());
+            }
+            w.write_all(&row)?;
+        }
+        Ok(())
+    }
+
+    /// Converts the phantom into a 16 bit grayscale image.
+    ///
+    /// The values are linearly mapped from `[lo, hi]` onto `[0, 65535]` and rounded to the nearest
+    /// integer. Values outside of `[lo, hi]` are clamped to the nearest bound and `NaN` is mapped
+    /// to `0`. In contrast to `write_pgm`, the range is fixed, which keeps the mapping consistent
+    /// across several phantoms. The image has the dimensions of the phantom and is upright, i.e.
+    /// the top row of the phantom is the first row of the image.
+    ///
+    /// Requires the `image` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let image = shepplogan(64, 48).into_luma16_range(0.0, 2.0);
+    /// assert_eq!(image.dimensions(), (64, 48));
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn into_luma16_range(
+        self,
+        lo: f64,
+        hi: f64,
+    ) -> image::ImageBuffer<image::Luma<u16>, Vec<u16>> {
+        let max = f64::from(u16::MAX);
+        let data = self
+            .data
+            .into_iter()
+            .map(|x| ((x - lo) / (hi - lo) * max).round().clamp(0.0, max) as u16)
+            .collect();
+        image::ImageBuffer::from_raw(self.nx, self.ny, data)
+            .expect("number of values matches the dimensions")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shepplogan, Error, Phantom, Shape};
+
+    /// Parses the header of a binary PGM and returns `(nx, ny, max_val, header_length)`
+    fn parse_pgm_header(buffer: &[u8]) -> (u32, u32, u16, usize) {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while fields.len() < 4 {
+            let start = pos;
+            while !buffer[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+            fields.push(
+                std::str::from_utf8(&buffer[start..pos])
+                    .unwrap()
+                    .to_string(),
+            );
+            // exactly one whitespace character follows each field
+            pos += 1;
+        }
+        assert_eq!(fields[0], "P5");
+        (
+            fields[1].parse().unwrap(),
+            fields[2].parse().unwrap(),
+            fields[3].parse().unwrap(),
+            pos,
+        )
+    }
+
+    #[test]
+    fn test_write_pgm() {
+        let (nx, ny) = (48, 64);
+        // Only the upper half of the phantom is bright
+        let phantom = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 2.0, 4.0, 2.0, 0.0, 2.0)]);
+
+        let mut buffer = Vec::new();
+        phantom.write_pgm(&mut buffer, 255).unwrap();
+        let (width, height, max_val, header_length) = parse_pgm_header(&buffer);
+        assert_eq!((width, height, max_val), (nx, ny, 255));
+        let pixels = &buffer[header_length..];
+        assert_eq!(pixels.len(), (nx * ny) as usize);
+        // upright: first row is the top of the phantom
+        assert_eq!(pixels[0], 255);
+        assert_eq!(pixels[pixels.len() - 1], 0);
+
+        let mut buffer = Vec::new();
+        phantom.write_pgm(&mut buffer, 1000).unwrap();
+        let (width, height, max_val, header_length) = parse_pgm_header(&buffer);
+        assert_eq!((width, height, max_val), (nx, ny, 1000));
+        let pixels = &buffer[header_length..];
+        assert_eq!(pixels.len(), (2 * nx * ny) as usize);
+        assert_eq!(u16::from_be_bytes([pixels[0], pixels[1]]), 1000);
+
+        assert!(matches!(
+            phantom.write_pgm(&mut Vec::new(), 0),
+            Err(Error::InvalidParameter(_))
+        ));
+        let mut full = [0u8; 8];
+        assert!(matches!(
+            phantom.write_pgm(&mut full[..], 255),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_npy() {
+        for (nx, ny) in [(48, 64), (7, 3), (0, 0)] {
+            let phantom = shepplogan(nx, ny);
+            let mut buffer = Vec::new();
+            phantom.write_npy(&mut buffer).unwrap();
+
+            assert_eq!(buffer[..8], *b"\x93NUMPY\x01\x00");
+            let header_length = usize::from(u16::from_le_bytes([buffer[8], buffer[9]]));
+            let data_start = 10 + header_length;
+            assert_eq!(data_start % 64, 0);
+            let header = std::str::from_utf8(&buffer[10..data_start]).unwrap();
+            assert!(header.ends_with('\n'));
+            assert_eq!(
+                header.trim_end(),
+                format!(
+                    "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+                    ny, nx
+                )
+            );
+
+            let data = buffer[data_start..]
+                .chunks_exact(8)
+                .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+                .collect::<Vec<_>>();
+            assert_eq!(buffer[data_start..].len(), 8 * (nx * ny) as usize);
+            assert_eq!(data, phantom.into_vec());
+        }
+
+        let mut full = [0u8; 8];
+        assert!(matches!(
+            shepplogan(8, 8).write_npy(&mut full[..]),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_into_luma16_range() {
+        let (nx, ny) = (64, 48);
+        let phantom = shepplogan(nx, ny);
+        let image = phantom.clone().into_luma16_range(0.0, 2.0);
+        assert_eq!(image.dimensions(), (nx, ny));
+        // The skull at the top center and the background in the corners
+        let skull = image.get_pixel(nx / 2, ny - 1 - 45).0[0];
+        assert_eq!(phantom.data[phantom.index(nx / 2, 45)], 2.0);
+        assert_eq!(skull, u16::MAX);
+        assert_eq!(image.get_pixel(0, 0).0[0], 0);
+
+        // Clamping and rounding
+        let phantom = Phantom::from_data(4, 1, vec![-1.0, 0.5, 1.0, f64::NAN]).unwrap();
+        let image = phantom.into_luma16_range(0.0, 0.75);
+        assert_eq!(image.into_raw(), [0, 43690, u16::MAX, 0]);
+
+        // Round trip through a 16 bit PNG
+        let image = shepplogan(nx, ny).into_luma16_range(-0.5, 2.5);
+        let path =
+            std::env::temp_dir().join(format!("shepplogan_luma16_{}.png", std::process::id()));
+        image.save(&path).unwrap();
+        let reloaded = image::open(&path).unwrap().into_luma16();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.dimensions(), (nx, ny));
+        assert_eq!(reloaded.get_pixel(nx / 2, ny - 1 - 45).0[0], 54613);
+        assert_eq!(reloaded.into_raw(), image.into_raw());
+    }
+}
@@ -0,0 +1,278 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{accumulate, rasterize, Phantom};
+use crate::{RenderOptions, Shape};
+use std::collections::HashMap;
+
+/// Creates a label map of size `nx` times `ny` given a set of `shapes`.
+///
+/// Each pixel holds the label of the topmost shape covering it, which is the last shape in
+/// `shapes` covering the pixel. The shape `shapes[i]` has label `i + 1`, pixels which are not
+/// covered by any shape have label `0`. The layout of the label map is the same as the layout of
+/// the data of a `Phantom`.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{label_map, Shape};
+/// let shapes = [
+///     Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0),
+///     Shape::ellipse(0.0, 0.0, 0.2, 0.2, 0.0, 1.0),
+/// ];
+/// let labels = label_map(64, 64, &shapes);
+/// assert_eq!(labels[0], 0);
+/// assert_eq!(labels[32 * 64 + 32], 2);
+/// ```
+pub fn label_map(nx: u32, ny: u32, shapes: &[Shape]) -> Vec<u32> {
+    let shapes = shapes
+        .iter()
+        .map(|shape| shape.on_canvas(nx, ny))
+        .collect::<Vec<_>>();
+    let mut labels = vec![0; (nx * ny) as usize];
+    rasterize(&shapes, nx, ny, |idx, shape_idx, _| {
+        labels[idx] = shape_idx as u32 + 1
+    });
+    labels
+}
+
+/// Creates a mask of size `nx` times `ny` which is `true` for every pixel covered by at least one
+/// of `shapes`.
+///
+/// This is cheaper than rendering a phantom if only the occupancy of the pixels matters. The
+/// intensities of the shapes are ignored, therefore pixels where the intensities of overlapping
+/// shapes cancel out are still `true`. The layout of the mask is the same as the layout of the
+/// data of a `Phantom`.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{shapes_mask, Shape};
+/// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+/// let mask = shapes_mask(&shapes, 64, 64);
+/// assert!(!mask[0]);
+/// assert!(mask[32 * 64 + 32]);
+/// ```
+pub fn shapes_mask(shapes: &[Shape], nx: u32, ny: u32) -> Vec<bool> {
+    let shapes = shapes
+        .iter()
+        .map(|shape| shape.on_canvas(nx, ny))
+        .collect::<Vec<_>>();
+    let mut mask = vec![false; (nx * ny) as usize];
+    rasterize(&shapes, nx, ny, |idx, _, _| mask[idx] = true);
+    mask
+}
+
+impl Phantom {
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes` together with its
+    /// label map.
+    ///
+    /// This is equivalent to calling `Phantom::new` and `label_map`, but the shapes are only
+    /// traversed once. See `label_map` for the definition of the labels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 2.0)];
+    /// let (phantom, labels) = Phantom::new_with_labels(64, 64, &shapes);
+    /// assert_eq!(labels.len(), phantom.into_vec().len());
+    /// ```
+    pub fn new_with_labels(nx: u32, ny: u32, shapes: &[Shape]) -> (Phantom, Vec<u32>) {
+        let options = RenderOptions::default();
+        let shapes = shapes
+            .iter()
+            .map(|shape| shape.on_canvas(nx, ny))
+            .collect::<Vec<_>>();
+        let mut data = vec![0.0; (nx * ny) as usize];
+        let mut labels = vec![0; (nx * ny) as usize];
+        rasterize(&shapes, nx, ny, |idx, shape_idx, shape| {
+            accumulate(&mut data[idx], shape, &options);
+            labels[idx] = shape_idx as u32 + 1;
+        });
+        let mut phantom = Phantom::from_raw(nx, ny, data);
+        phantom.shape_count = Some(shapes.len());
+        (phantom, labels)
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes` together with a
+    /// legend.
+    ///
+    /// The legend contains `(index, intensity)` for each shape, where `index` is the index of the
+    /// shape in `shapes`. This allows building class mappings for annotated datasets, e.g. in
+    /// combination with `label_map`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [
+    ///     Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0),
+    ///     Shape::ellipse(0.0, 0.0, 0.2, 0.2, 0.0, 0.5),
+    /// ];
+    /// let (phantom, legend) = Phantom::new_with_legend(64, 64, &shapes);
+    /// assert_eq!(legend, [(0, 1.0), (1, 0.5)]);
+    /// ```
+    pub fn new_with_legend(nx: u32, ny: u32, shapes: &[Shape]) -> (Phantom, Vec<(usize, f64)>) {
+        let legend = shapes.iter().map(Shape::intensity).enumerate().collect();
+        (Phantom::new(nx, ny, shapes), legend)
+    }
+
+    /// Create a phantom with size `nx` times `ny` from a label map by mapping each label onto an
+    /// intensity.
+    ///
+    /// Each pixel of the phantom is set to `class_intensities[label]`, where `label` is the
+    /// corresponding pixel of `label_map`. Labels which are not contained in `class_intensities`
+    /// are mapped to `0`. The layout of `label_map` is the same as the layout of the data of a
+    /// `Phantom`, which is also the layout returned by `label_map`. This allows recoloring
+    /// segmentation ground truth.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `label_map` is not `nx * ny`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{label_map, Phantom, Shape};
+    /// # use std::collections::HashMap;
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+    /// let labels = label_map(64, 64, &shapes);
+    /// let phantom = Phantom::from_labels(&labels, 64, 64, &HashMap::from([(1, 0.3)]));
+    /// assert_eq!(phantom.into_vec()[32 * 64 + 32], 0.3);
+    /// ```
+    pub fn from_labels(
+        label_map: &[u32],
+        nx: u32,
+        ny: u32,
+        class_intensities: &HashMap<u32, f64>,
+    ) -> Phantom {
+        assert_eq!(
+            label_map.len(),
+            nx as usize * ny as usize,
+            "size of the label map does not match the dimensions"
+        );
+        let data = label_map
+            .iter()
+            .map(|label| class_intensities.get(label).copied().unwrap_or(0.0))
+            .collect();
+        Phantom::from_raw(nx, ny, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{label_map, shapes_mask, Overlap, Phantom, RenderOptions, Shape};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_label_map() {
+        let (nx, ny) = (64, 64);
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 0.4, 0.4, 0.0, 1.0),
+        ];
+        let labels = label_map(nx, ny, &shapes);
+        let phantom = Phantom::new(nx, ny, &shapes);
+        assert_eq!(labels[phantom.index(0, 0)], 0);
+        assert_eq!(labels[phantom.index(nx / 2, ny / 2)], 2);
+        assert_eq!(labels[phantom.index(nx / 2, ny / 2 + 20)], 1);
+    }
+
+    #[test]
+    fn test_new_with_labels() {
+        let (nx, ny) = (80, 64);
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 1.0),
+            Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.8),
+            Shape::ellipse(0.22, 0.0, 0.11, 0.31, -18.0, -0.2),
+            Shape::rectangle(-0.2, 0.1, 0.3, 0.5, 30.0, 0.3),
+            Shape::lune(0.1, -0.4, 0.3, 0.2, -0.4, 0.25, 0.5),
+        ];
+        let (phantom, labels) = Phantom::new_with_labels(nx, ny, &shapes);
+        assert_eq!(labels, label_map(nx, ny, &shapes));
+        assert_eq!(phantom.data, Phantom::new(nx, ny, &shapes).data);
+    }
+
+    #[test]
+    fn test_new_with_legend() {
+        let (nx, ny) = (48, 64);
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 1.0),
+            Shape::rectangle(-0.2, 0.1, 0.3, 0.5, 30.0, -0.3),
+            Shape::group(vec![Shape::ellipse(0.3, 0.3, 0.1, 0.1, 0.0, 9.0)], 0.25),
+        ];
+        let (phantom, legend) = Phantom::new_with_legend(nx, ny, &shapes);
+        assert_eq!(legend.len(), shapes.len());
+        for (i, &(index, intensity)) in legend.iter().enumerate() {
+            assert_eq!(index, i);
+            assert_eq!(intensity.to_ne_bytes(), shapes[i].intensity().to_ne_bytes());
+        }
+        assert_eq!(legend[2].1.to_ne_bytes(), 0.25f64.to_ne_bytes());
+        assert_eq!(phantom.data, Phantom::new(nx, ny, &shapes).data);
+    }
+
+    #[test]
+    fn test_from_labels() {
+        let (nx, ny) = (64, 48);
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 1.0),
+            Shape::rectangle(-0.2, 0.1, 0.3, 0.5, 30.0, -0.3),
+            Shape::lune(0.1, -0.4, 0.3, 0.2, -0.4, 0.25, 0.5),
+        ];
+        let labels = label_map(nx, ny, &shapes);
+        // Mapping the labels onto the intensities of the shapes is the same as rendering the
+        // shapes with replacing overlaps
+        let classes = shapes
+            .iter()
+            .enumerate()
+            .map(|(i, shape)| (i as u32 + 1, shape.intensity()))
+            .collect::<HashMap<_, _>>();
+        let phantom = Phantom::from_labels(&labels, nx, ny, &classes);
+        let options = RenderOptions::new().overlap(Overlap::Replace);
+        assert_eq!(
+            phantom.data,
+            Phantom::new_with_options(nx, ny, &shapes, &options).data
+        );
+
+        // Recoloring, where unmapped labels become zero
+        let classes = HashMap::from([(0, -1.0), (2, 7.0)]);
+        let phantom = Phantom::from_labels(&labels, nx, ny, &classes);
+        for (value, label) in phantom.data.iter().zip(labels.iter()) {
+            let expected: f64 = match label {
+                0 => -1.0,
+                2 => 7.0,
+                _ => 0.0,
+            };
+            assert_eq!(value.to_ne_bytes(), expected.to_ne_bytes());
+        }
+        assert!(labels.contains(&2));
+    }
+
+    #[test]
+    fn test_shapes_mask() {
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+            Shape::rectangle(0.5, -0.5, 0.6, 0.3, 30.0, 0.5),
+            Shape::lune(-0.4, 0.4, 0.4, -0.2, 0.4, 0.3, 1.0),
+            Shape::ellipse(0.9, 0.9, 0.3, 0.2, 0.0, 0.25),
+        ];
+        for (nx, ny) in [(64, 64), (50, 33), (0, 0)] {
+            let mask = shapes_mask(&shapes, nx, ny);
+            let expected = Phantom::new(nx, ny, &shapes)
+                .into_vec()
+                .into_iter()
+                .map(|v| v != 0.0)
+                .collect::<Vec<_>>();
+            assert_eq!(mask, expected);
+        }
+        // Cancelling intensities still count as covered
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, -1.0),
+        ];
+        assert!(shapes_mask(&shapes, 8, 8)[4 * 8 + 4]);
+    }
+}
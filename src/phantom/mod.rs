@@ -0,0 +1,2824 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+mod analysis;
+mod filter;
+mod io;
+mod labels;
+mod region;
+mod resample;
+mod visualize;
+
+use std::collections::HashSet;
+
+use crate::{
+    shape::{BoundingBox, ShapeOnCanvas},
+    shepplogan::Preset,
+    EdgeMode, Error, Float, RenderOptions, Shape,
+};
+
+pub use analysis::ImageMoments;
+pub use filter::BorderMode;
+pub use labels::{label_map, shapes_mask};
+
+/// General phantom
+///
+/// The values of the pixels are stored as `T`, which is either `f64` (default) or `f32`. Phantoms
+/// with `f32` values, which require half of the memory, can be created with `Phantom::new_typed`.
+/// Note that only the pixel storage is generic: The shapes are always defined and placed in
+/// `f64` (see `Float`), and apart from the constructors and accessors such as `get_pixel`,
+/// `rows` and `into_vec`, the methods (e.g. `scale`, the filters, the analysis and the I/O
+/// functions) are only available for `f64`.
+#[derive(Clone, Debug)]
+pub struct Phantom<T = f64> {
+    pub(crate) data: Vec<T>,
+    pub(crate) nx: u32,
+    pub(crate) ny: u32,
+    minmax: Option<(T, T)>,
+    spacing: Option<(f64, f64)>,
+    /// standard phantom this phantom was rendered from
+    pub(crate) preset: Option<Preset>,
+    /// number of shapes this phantom was rendered from
+    pub(crate) shape_count: Option<usize>,
+}
+
+impl<T: Float> Phantom<T> {
+    /// Create a new phantom with size `nx` times `ny` and values of type `T` given a set of
+    /// `shapes`.
+    ///
+    /// The geometry of the shapes is computed in `f64`, therefore the covered pixels are the same
+    /// as for `Phantom::new`. Only the intensities are converted to `T` and accumulated in `T`.
+    /// For `f32`, this avoids allocating the phantom in `f64` and converting it afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.5)];
+    /// let phantom = Phantom::<f32>::new_typed(64, 64, &shapes);
+    /// assert_eq!(phantom.get_pixel(32, 32), Some(1.5f32));
+    /// ```
+    pub fn new_typed(nx: u32, ny: u32, shapes: &[Shape]) -> Self {
+        Phantom::new_typed_with_options(nx, ny, shapes, &RenderOptions::default())
+    }
+
+    /// Create a new phantom with size `nx` times `ny` and values of type `T` given a set of
+    /// `shapes` and render `options`.
+    ///
+    /// See `new_typed` and `new_with_options` for details.
+    pub fn new_typed_with_options(
+        nx: u32,
+        ny: u32,
+        shapes: &[Shape],
+        options: &RenderOptions,
+    ) -> Self {
+        let canvas = options.canvas(nx, ny);
+        let mut shapes = shapes
+            .iter()
+            .map(|shape| shape.on_canvas_with(&canvas))
+            .collect::<Vec<_>>();
+        options.reorder(&mut shapes);
+        Phantom {
+            data: phantom(&shapes, nx, ny, options),
+            nx,
+            ny,
+            minmax: None,
+            spacing: None,
+            preset: None,
+            shape_count: Some(shapes.len()),
+        }
+    }
+
+    /// Returns the index into `data` of pixel `(x, y)`.
+    ///
+    /// Pixel `(0, 0)` is the lower left corner of the phantom, however, `data` is stored row by
+    /// row starting at the top.
+    #[inline(always)]
+    fn index(&self, x: u32, y: u32) -> usize {
+        (self.ny - y - 1) as usize * self.nx as usize + x as usize
+    }
+
+    /// Returns the dimensions `(nx, ny)` of the phantom in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.nx, self.ny)
+    }
+
+    /// Returns the number of pixels `nx` in `x` direction.
+    pub fn width(&self) -> u32 {
+        self.nx
+    }
+
+    /// Returns the number of pixels `ny` in `y` direction.
+    pub fn height(&self) -> u32 {
+        self.ny
+    }
+
+    /// Returns the number of shapes the phantom was rendered from.
+    ///
+    /// Returns `None` if the phantom was not rendered from shapes, e.g. for `Phantom::from_data`
+    /// or phantoms derived from other phantoms with a different pixel grid (e.g. `crop` or
+    /// `transpose`). Modifications of the pixel values (e.g. `scale`) keep the count. Shapes
+    /// which do not cover any pixel are counted as well.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, Phantom};
+    /// assert_eq!(shepplogan(64, 64).shape_count(), Some(10));
+    /// assert_eq!(Phantom::from_data(2, 1, vec![0.0; 2]).unwrap().shape_count(), None);
+    /// ```
+    pub fn shape_count(&self) -> Option<usize> {
+        self.shape_count
+    }
+
+    /// Returns the value of pixel `(x, y)` or `None` if it is outside of the phantom.
+    ///
+    /// As for the shapes, `(0, 0)` is the lower left pixel of the phantom, whereas `into_vec`
+    /// returns the rows starting at the top. Therefore pixel `(x, y)` is stored at index
+    /// `(ny - y - 1) * nx + x` of `into_vec`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Phantom;
+    /// let phantom = Phantom::from_data(2, 2, vec![1.0, 2.0, 3.0, 4.0]).unwrap();
+    /// assert_eq!(phantom.get_pixel(0, 0), Some(3.0));
+    /// assert_eq!(phantom.get_pixel(1, 1), Some(2.0));
+    /// assert_eq!(phantom.get_pixel(2, 0), None);
+    /// ```
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<T> {
+        (x < self.nx && y < self.ny).then(|| self.data[self.index(x, y)])
+    }
+
+    /// Attaches the physical pixel spacing `(dx, dy)` (in mm per pixel) to the phantom.
+    pub fn with_spacing(mut self, dx: f64, dy: f64) -> Phantom<T> {
+        self.spacing = Some((dx, dy));
+        self
+    }
+
+    /// Returns the physical pixel spacing `(dx, dy)` in mm per pixel, if available.
+    pub fn spacing(&self) -> Option<(f64, f64)> {
+        self.spacing
+    }
+
+    /// Returns an iterator over the rows of the phantom.
+    ///
+    /// The rows are returned upright, i.e. from the top of the phantom to the bottom, and each row
+    /// is a contiguous slice from left to right. Since `(0, 0)` is the lower left pixel, pixel
+    /// `(x, y)` is element `x` of row `ny - 1 - y`. Concatenating the rows gives the same layout
+    /// as `into_vec`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Phantom;
+    /// let phantom = Phantom::from_data(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    /// let rows = phantom.rows().collect::<Vec<_>>();
+    /// assert_eq!(rows, [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    /// ```
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = &[T]> + '_ {
+        let nx = self.nx as usize;
+        (0..self.ny as usize).map(move |row| &self.data[row * nx..(row + 1) * nx])
+    }
+
+    /// Returns the phantom as a flattened `Vec`.
+    ///
+    /// The rows are stored from top to bottom and each row from left to right.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl Phantom {
+    /// Create a new phantom with size `nx` times `ny` given a set of `ellipses`.
+    ///
+    /// Pixel `(x, y)` samples the shapes at the point `(x, y)` of the canvas, where the canvas
+    /// `[-1, 1]` is mapped onto `[0, n]` along the smaller dimension `n`. This matters for very
+    /// small phantoms: A 1x1 phantom samples the lower left corner `(-1, -1)` of the canvas, a 2x2
+    /// phantom samples the origin in pixel `(1, 1)`. See `RenderOptions::sample_at_center` for
+    /// sampling at the pixel centers instead, in which case a 1x1 phantom samples the origin.
+    pub fn new(nx: u32, ny: u32, shapes: &[Shape]) -> Self {
+        Phantom::new_with_options(nx, ny, shapes, &RenderOptions::default())
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes`, checking the inputs.
+    ///
+    /// In contrast to `Phantom::new`, this returns `Error::DimensionOverflow` if the number of
+    /// pixels does not fit into a `u32` and `Error::InvalidShape` if a shape has a parameter which
+    /// is not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Error, Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, f64::NAN, 0.5, 0.0, 1.0)];
+    /// assert!(matches!(
+    ///     Phantom::try_new(64, 64, &shapes),
+    ///     Err(Error::InvalidShape { index: 0 })
+    /// ));
+    /// ```
+    pub fn try_new(nx: u32, ny: u32, shapes: &[Shape]) -> Result<Self, Error> {
+        check_dimensions(nx, ny)?;
+        if let Some(index) = shapes.iter().position(|shape| !shape.is_finite()) {
+            return Err(Error::InvalidShape { index });
+        }
+        Ok(Phantom::new(nx, ny, shapes))
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a non-empty set of `shapes`.
+    ///
+    /// In contrast to `Phantom::new`, which silently creates a phantom of zeros, this returns
+    /// `Error::NoShapes` if `shapes` is empty. This helps catching errors in the setup of the
+    /// shapes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Error, Phantom};
+    /// assert!(matches!(
+    ///     Phantom::new_non_empty(64, 64, &[]),
+    ///     Err(Error::NoShapes)
+    /// ));
+    /// ```
+    pub fn new_non_empty(nx: u32, ny: u32, shapes: &[Shape]) -> Result<Self, Error> {
+        if shapes.is_empty() {
+            return Err(Error::NoShapes);
+        }
+        Ok(Phantom::new(nx, ny, shapes))
+    }
+
+    /// Create a phantom with size `nx` times `ny` from `data`.
+    ///
+    /// The layout of `data` is the same as the layout of `into_vec`: The rows are stored from top
+    /// to bottom and each row from left to right. Returns `Error::DimensionMismatch` if the length
+    /// of `data` is not `nx * ny` and `Error::DimensionOverflow` if the number of pixels does not
+    /// fit into a `u32`.
+    pub fn from_data(nx: u32, ny: u32, data: Vec<f64>) -> Result<Self, Error> {
+        let expected = check_dimensions(nx, ny)?;
+        if data.len() != expected {
+            return Err(Error::DimensionMismatch {
+                expected,
+                actual: data.len(),
+            });
+        }
+        Ok(Phantom::from_raw(nx, ny, data))
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes` and render
+    /// `options`.
+    ///
+    /// The intensities of all shapes covering a pixel are accumulated in the order of `shapes`,
+    /// unless `RenderOptions::order` selects a different order (which is only applied if it does
+    /// not change the result up to rounding errors, see `ShapeOrder`). The order is the same
+    /// regardless of whether the phantom is rendered in parallel (`rayon` feature) or not.
+    pub fn new_with_options(nx: u32, ny: u32, shapes: &[Shape], options: &RenderOptions) -> Self {
+        Phantom::new_typed_with_options(nx, ny, shapes, options)
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes`, where each pixel is
+    /// the average of `samples_per_axis` times `samples_per_axis` samples.
+    ///
+    /// The samples of a pixel are placed on a regular grid of spacing `1 / samples_per_axis`
+    /// pixels, which is centered on the point sampled by `Phantom::new`. Pixels at the edges of
+    /// the shapes are therefore weighted by the (approximate) fraction of the pixel covered by
+    /// the shapes, which smooths the edges. With `samples_per_axis == 1`, the phantom is identical
+    /// to `Phantom::new`. The samples are averaged row by row, therefore only the samples of a
+    /// single row of the phantom are stored at a time.
+    ///
+    /// Returns `Error::InvalidParameter` if `samples_per_axis` is zero and
+    /// `Error::DimensionOverflow` if the number of pixels, or the number of samples along `x` or
+    /// `y`, does not fit into a `u32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 1.0)];
+    /// let phantom = Phantom::new_supersampled(64, 64, &shapes, 4).unwrap();
+    /// let values = phantom.into_vec();
+    /// // Some pixels at the edge are partially covered by the ellipse
+    /// assert!(values.iter().any(|&x| x > 0.0 && x < 1.0));
+    /// ```
+    pub fn new_supersampled(
+        nx: u32,
+        ny: u32,
+        shapes: &[Shape],
+        samples_per_axis: u32,
+    ) -> Result<Self, Error> {
+        if samples_per_axis == 0 {
+            return Err(Error::InvalidParameter(
+                "number of samples must be positive",
+            ));
+        }
+        let data =
+            phantom_supersampled(shapes, nx, ny, &RenderOptions::default(), samples_per_axis)?;
+        let mut phantom = Phantom::from_raw(nx, ny, data);
+        phantom.shape_count = Some(shapes.len());
+        Ok(phantom)
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes`, where the pixels
+    /// at the edges of the shapes are rendered according to `mode`.
+    ///
+    /// With `EdgeMode::AnalyticArea`, every shape adds its intensity weighted by the fraction of
+    /// the pixel which it covers. This models the partial volume effect, e.g. the sum of the
+    /// pixels of an ellipse with intensity `1.0` is its area in pixels.
+    ///
+    /// Returns `Error::DimensionOverflow` if the number of pixels does not fit into a `u32` and
+    /// the errors of `Phantom::new_supersampled` for `EdgeMode::Supersample`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{EdgeMode, Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.25, 30.0, 1.0)];
+    /// let phantom = Phantom::new_with_mode(64, 64, &shapes, EdgeMode::AnalyticArea).unwrap();
+    /// let area = std::f64::consts::PI * 0.5 * 0.25 * 32.0 * 32.0;
+    /// assert!((phantom.into_vec().iter().sum::<f64>() - area).abs() < 1e-9);
+    /// ```
+    pub fn new_with_mode(
+        nx: u32,
+        ny: u32,
+        shapes: &[Shape],
+        mode: EdgeMode,
+    ) -> Result<Self, Error> {
+        check_dimensions(nx, ny)?;
+        match mode {
+            EdgeMode::Binary => Ok(Phantom::new(nx, ny, shapes)),
+            EdgeMode::Supersample(samples) => Phantom::new_supersampled(nx, ny, shapes, samples),
+            EdgeMode::AnalyticArea => {
+                let mut phantom = Phantom::from_raw(nx, ny, phantom_area(shapes, nx, ny));
+                phantom.shape_count = Some(shapes.len());
+                Ok(phantom)
+            }
+        }
+    }
+
+    /// Renders the phantom of size `nx` times `ny` shape by shape, returning the phantom after
+    /// each shape is added.
+    ///
+    /// The `i`-th phantom contains the shapes `shapes[..=i]`, therefore the last phantom is the
+    /// same as `Phantom::new(nx, ny, shapes)` and an empty slice of shapes results in no phantoms
+    /// at all. This is useful for animations which show how a phantom is built up. Note that the
+    /// memory required grows linearly with the number of shapes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [
+    ///     Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+    ///     Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.98),
+    /// ];
+    /// let steps = Phantom::render_incremental(64, 64, &shapes);
+    /// assert_eq!(steps.len(), 2);
+    /// ```
+    pub fn render_incremental(nx: u32, ny: u32, shapes: &[Shape]) -> Vec<Phantom> {
+        let options = RenderOptions::default();
+        let mut data = vec![0.0; (nx * ny) as usize];
+        shapes
+            .iter()
+            .enumerate()
+            .map(|(i, shape)| {
+                let shape = shape.on_canvas(nx, ny);
+                rasterize(std::slice::from_ref(&shape), nx, ny, |idx, _, shape| {
+                    accumulate(&mut data[idx], shape, &options)
+                });
+                let mut phantom = Phantom::from_raw(nx, ny, data.clone());
+                phantom.shape_count = Some(i + 1);
+                phantom
+            })
+            .collect()
+    }
+
+    /// Create a new phantom covering the physical field of view `fov_mm = (width, height)` (in
+    /// mm) with the pixel spacing `spacing_mm = (dx, dy)` (in mm per pixel) given a set of
+    /// `shapes`.
+    ///
+    /// The dimensions of the phantom are `round(width / dx)` times `round(height / dy)` and the
+    /// spacing is attached to the phantom (see `with_spacing`). As in `Phantom::new`, the shapes
+    /// are scaled onto the smaller dimension, therefore `[-1, 1]` corresponds to the smaller side
+    /// of the field of view.
+    ///
+    /// Returns `Error::InvalidParameter` if the spacing is not finite and positive or if the
+    /// field of view is not finite and non-negative, and `Error::DimensionOverflow` if the number
+    /// of pixels does not fit into a `u32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0)];
+    /// let phantom = Phantom::new_physical((250.0, 250.0), (0.5, 0.5), &shapes).unwrap();
+    /// assert_eq!(phantom.physical_extent(), (250.0, 250.0));
+    /// assert!(Phantom::new_physical((250.0, 250.0), (0.0, 0.5), &shapes).is_err());
+    /// ```
+    pub fn new_physical(
+        fov_mm: (f64, f64),
+        spacing_mm: (f64, f64),
+        shapes: &[Shape],
+    ) -> Result<Self, Error> {
+        let pixels = |fov: f64, spacing: f64| {
+            if !(spacing.is_finite() && spacing > 0.0) {
+                return Err(Error::InvalidParameter(
+                    "spacing must be finite and positive",
+                ));
+            }
+            if !(fov.is_finite() && fov >= 0.0) {
+                return Err(Error::InvalidParameter(
+                    "field of view must be finite and non-negative",
+                ));
+            }
+            let n = (fov / spacing).round();
+            if n > f64::from(u32::MAX) {
+                return Err(Error::DimensionOverflow);
+            }
+            Ok(n as u32)
+        };
+        let nx = pixels(fov_mm.0, spacing_mm.0)?;
+        let ny = pixels(fov_mm.1, spacing_mm.1)?;
+        check_dimensions(nx, ny)?;
+        Ok(Phantom::new(nx, ny, shapes).with_spacing(spacing_mm.0, spacing_mm.1))
+    }
+
+    /// Create a new phantom with size `nx` times `ny` given a set of `shapes`, where duplicated
+    /// shapes are only rendered once.
+    ///
+    /// Shapes are considered duplicates if they compare equal (including their intensity, see
+    /// `Shape` for details on the comparison). Only the first occurrence of each shape is kept, the
+    /// order of the remaining shapes is preserved. This avoids doubling the intensity of shapes
+    /// which accidentally appear multiple times, e.g. when phantoms are assembled from multiple
+    /// sources.
+    pub fn new_dedup(nx: u32, ny: u32, shapes: &[Shape]) -> Self {
+        let mut seen = HashSet::with_capacity(shapes.len());
+        let unique = shapes
+            .iter()
+            .filter(|&shape| seen.insert(shape))
+            .cloned()
+            .collect::<Vec<_>>();
+        Phantom::new(nx, ny, &unique)
+    }
+
+    /// Create a new phantom with size `nx` times `ny` from an ellipse table in the format used by
+    /// MATLAB's `phantom` function.
+    ///
+    /// Each row is of the form `[A, a, b, x0, y0, phi]`, where `A` is the intensity, `a` and `b`
+    /// are the semi-axes in `x` and `y` direction, `(x0, y0)` is the center and `phi` is the
+    /// rotation angle in degrees. Note that this column order differs from the order of the
+    /// parameters of `Shape::ellipse`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Phantom;
+    /// let phantom = Phantom::from_matlab_ellipses(
+    ///     256,
+    ///     256,
+    ///     &[[2.0, 0.69, 0.92, 0.0, 0.0, 0.0], [-0.98, 0.6624, 0.874, 0.0, -0.0184, 0.0]],
+    /// );
+    /// ```
+    pub fn from_matlab_ellipses(nx: u32, ny: u32, rows: &[[f64; 6]]) -> Self {
+        let shapes = rows
+            .iter()
+            .map(|&[intensity, a, b, x0, y0, phi]| Shape::ellipse(x0, y0, a, b, phi, intensity))
+            .collect::<Vec<_>>();
+        Phantom::new(nx, ny, &shapes)
+    }
+
+    /// Wraps already rendered `data` of size `nx` times `ny` into a phantom.
+    pub(crate) fn from_raw(nx: u32, ny: u32, data: Vec<f64>) -> Self {
+        debug_assert_eq!(data.len(), nx as usize * ny as usize);
+        Phantom {
+            data,
+            nx,
+            ny,
+            minmax: None,
+            spacing: None,
+            preset: None,
+            shape_count: None,
+        }
+    }
+
+    /// Re-renders a phantom created by `shepplogan` or `shepplogan_modified` as modified
+    /// Shepp-Logan phantom with the same dimensions.
+    ///
+    /// Returns `None` if the phantom was not created by one of these functions. Modifications of
+    /// the pixel values (e.g. `scale`) are not carried over.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, shepplogan_modified};
+    /// let modified = shepplogan(64, 64).to_modified().unwrap();
+    /// assert_eq!(modified.into_vec(), shepplogan_modified(64, 64).into_vec());
+    /// ```
+    pub fn to_modified(&self) -> Option<Phantom> {
+        self.preset
+            .map(|_| Preset::Modified.render(self.nx, self.ny))
+    }
+
+    /// Re-renders a phantom created by `shepplogan` or `shepplogan_modified` as original
+    /// Shepp-Logan phantom with the same dimensions.
+    ///
+    /// See `to_modified` for details.
+    pub fn to_original(&self) -> Option<Phantom> {
+        self.preset
+            .map(|_| Preset::Original.render(self.nx, self.ny))
+    }
+
+    /// Returns the physical extent `(width, height)` of the phantom in mm.
+    ///
+    /// If no spacing is attached to the phantom, a spacing of 1 mm per pixel is assumed, which
+    /// means that the extent equals the dimensions of the phantom in pixels.
+    pub fn physical_extent(&self) -> (f64, f64) {
+        let (dx, dy) = self.spacing.unwrap_or((1.0, 1.0));
+        (f64::from(self.nx) * dx, f64::from(self.ny) * dy)
+    }
+
+    /// Converts world coordinates `(x, y)` (in mm) to (fractional) pixel coordinates.
+    ///
+    /// The origin of the world coordinate system is pixel `(0, 0)`, which is the lower left corner
+    /// of the phantom. The returned coordinates may be outside of the phantom. If no spacing is
+    /// attached to the phantom, a spacing of 1 mm per pixel is assumed.
+    pub fn world_to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = self.spacing.unwrap_or((1.0, 1.0));
+        (x / dx, y / dy)
+    }
+
+    /// Converts (fractional) pixel coordinates `(x, y)` to world coordinates (in mm).
+    ///
+    /// This is the inverse of `world_to_pixel`.
+    pub fn pixel_to_world(&self, x: f64, y: f64) -> (f64, f64) {
+        let (dx, dy) = self.spacing.unwrap_or((1.0, 1.0));
+        (x * dx, y * dy)
+    }
+
+    /// Scales the value of the phantom with `factor`.
+    pub fn scale(mut self, factor: f64) -> Phantom {
+        self.scale_mut(factor);
+        self
+    }
+
+    /// Scales the value of the phantom with `factor` in place.
+    pub fn scale_mut(&mut self, factor: f64) {
+        self.data.iter_mut().for_each(|x| *x *= factor);
+        self.minmax = self.minmax.map(|(min, max)| {
+            if factor < 0.0 {
+                (max * factor, min * factor)
+            } else {
+                (min * factor, max * factor)
+            }
+        });
+    }
+
+    /// Linearly maps the values of the phantom onto `[0, 1]`.
+    ///
+    /// The minimum is mapped to `0.0` and the maximum is mapped to `1.0`. If all values are equal,
+    /// all values are set to `0.0`.
+    pub fn normalize(mut self) -> Phantom {
+        self.normalize_mut();
+        self
+    }
+
+    /// Linearly maps the values of the phantom onto `[0, 1]` in place.
+    ///
+    /// See `normalize` for details.
+    pub fn normalize_mut(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+        let (min, max) = self.extrema();
+        if min == max {
+            self.data.iter_mut().for_each(|x| *x = 0.0);
+            self.minmax = Some((0.0, 0.0));
+        } else {
+            let range = max - min;
+            self.data.iter_mut().for_each(|x| *x = (*x - min) / range);
+            self.minmax = Some((0.0, 1.0));
+        }
+    }
+
+    /// Inverts the values of the phantom relative to its range.
+    ///
+    /// Each value `v` is mapped onto `max + min - v`, where `min` and `max` are the minimum and
+    /// maximum of the phantom, therefore the brightest pixel becomes the darkest and vice versa
+    /// while the range is preserved (up to rounding errors).
+    pub fn invert(mut self) -> Phantom {
+        self.invert_mut();
+        self
+    }
+
+    /// Inverts the values of the phantom relative to its range in place.
+    ///
+    /// See `invert` for details.
+    pub fn invert_mut(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+        let (min, max) = self.extrema();
+        self.invert_range_mut(min, max);
+    }
+
+    /// Inverts the values of the phantom relative to the range `[lo, hi]`.
+    ///
+    /// Each value `v` is mapped onto `lo + hi - v`. Values inside of `[lo, hi]` therefore stay
+    /// inside of this range, but values outside of it are not clamped.
+    pub fn invert_range(mut self, lo: f64, hi: f64) -> Phantom {
+        self.invert_range_mut(lo, hi);
+        self
+    }
+
+    /// Inverts the values of the phantom relative to the range `[lo, hi]` in place.
+    ///
+    /// See `invert_range` for details.
+    pub fn invert_range_mut(&mut self, lo: f64, hi: f64) {
+        let sum = lo + hi;
+        self.data.iter_mut().for_each(|x| *x = sum - *x);
+        // The mapping is monotonically decreasing, therefore the extrema swap their roles
+        self.minmax = self.minmax.map(|(min, max)| (sum - max, sum - min));
+    }
+
+    /// Clamps the values of the phantom to `[lo, hi]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi`, `lo` is `NaN`, or `hi` is `NaN`.
+    pub fn clamp(mut self, lo: f64, hi: f64) -> Phantom {
+        self.clamp_mut(lo, hi);
+        self
+    }
+
+    /// Clamps the values of the phantom to `[lo, hi]` in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi`, `lo` is `NaN`, or `hi` is `NaN`.
+    pub fn clamp_mut(&mut self, lo: f64, hi: f64) {
+        self.data.iter_mut().for_each(|x| *x = x.clamp(lo, hi));
+        self.minmax = self
+            .minmax
+            .map(|(min, max)| (min.clamp(lo, hi), max.clamp(lo, hi)));
+    }
+
+    /// Returns `true` if all values of the phantom are finite, i.e. neither `NaN` nor infinite.
+    ///
+    /// Arithmetic on phantoms (e.g. scaling by infinity or dividing by zero) may introduce such
+    /// values, which corrupt downstream processing and saved files. An empty phantom is finite.
+    pub fn is_finite(&self) -> bool {
+        self.data.iter().all(|x| x.is_finite())
+    }
+
+    /// Replaces all values which are `NaN` or infinite by `with` in place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Phantom;
+    /// let mut phantom = Phantom::from_data(2, 1, vec![f64::NAN, 1.0]).unwrap();
+    /// assert!(!phantom.is_finite());
+    /// phantom.replace_non_finite(0.0);
+    /// assert!(phantom.is_finite());
+    /// ```
+    pub fn replace_non_finite(&mut self, with: f64) {
+        let mut replaced = false;
+        for x in self.data.iter_mut().filter(|x| !x.is_finite()) {
+            *x = with;
+            replaced = true;
+        }
+        if replaced {
+            self.minmax = None;
+        }
+    }
+
+    /// Sets all pixels for which `keep(x, y)` returns `false` to `fill`.
+    ///
+    /// The coordinates `x` and `y` passed to `keep` follow the same convention as the shapes, i.e.
+    /// `(0, 0)` is the lower left corner of the phantom.
+    pub fn mask(mut self, keep: impl Fn(u32, u32) -> bool, fill: f64) -> Phantom {
+        let Phantom { nx, ny, .. } = self;
+        for (i, val) in self.data.iter_mut().enumerate() {
+            let x = i as u32 % nx;
+            let y = ny - i as u32 / nx - 1;
+            if !keep(x, y) {
+                *val = fill;
+            }
+        }
+        self.minmax = None;
+        self
+    }
+
+    /// Restricts the phantom to a circular field of view.
+    ///
+    /// All pixels outside of a centered circle with `radius` are set to `fill`. The radius is
+    /// given in the same units as the shapes, therefore a `radius` of `1.0` corresponds to the
+    /// largest circle which fits onto the canvas.
+    pub fn circular_fov(self, radius: f64, fill: f64) -> Phantom {
+        let fov = Shape::ellipse(0.0, 0.0, radius, radius, 0.0, 0.0).on_canvas(self.nx, self.ny);
+        self.mask(|x, y| fov.inside(f64::from(x), f64::from(y)), fill)
+    }
+
+    /// Returns the transposed phantom with dimensions `(ny, nx)`.
+    ///
+    /// Pixel `(x, y)` of the transposed phantom corresponds to pixel `(y, x)` of the original
+    /// phantom.
+    pub fn transpose(&self) -> Phantom {
+        let mut transposed = Phantom::from_raw(self.ny, self.nx, vec![0.0; self.data.len()]);
+        for x in 0..self.nx {
+            for y in 0..self.ny {
+                let idx = transposed.index(y, x);
+                transposed.data[idx] = self.data[self.index(x, y)];
+            }
+        }
+        transposed.minmax = self.minmax;
+        transposed.spacing = self.spacing.map(|(dx, dy)| (dy, dx));
+        transposed
+    }
+
+    /// Returns the phantom rotated counterclockwise by 90 degrees with dimensions `(ny, nx)`.
+    ///
+    /// Pixel `(x, y)` of the original phantom ends up at `(ny - 1 - y, x)` of the rotated phantom.
+    pub fn rotate_90(&self) -> Phantom {
+        let mut rotated = Phantom::from_raw(self.ny, self.nx, vec![0.0; self.data.len()]);
+        for x in 0..self.nx {
+            for y in 0..self.ny {
+                let idx = rotated.index(self.ny - 1 - y, x);
+                rotated.data[idx] = self.data[self.index(x, y)];
+            }
+        }
+        rotated.minmax = self.minmax;
+        rotated.spacing = self.spacing.map(|(dx, dy)| (dy, dx));
+        rotated
+    }
+
+    /// Returns the minimum and maximum value of the phantom as `(min, max)`.
+    ///
+    /// This function takes `&mut self` because once minium and maximum are calculated, they values
+    /// are cached internally to avoid recomputation when calling this function multiple times.
+    pub fn extrema(&mut self) -> (f64, f64) {
+        let minmax = self.compute_extrema();
+        self.minmax = Some(minmax);
+        minmax
+    }
+
+    /// Returns the position `(x, y)` and value of the largest pixel.
+    ///
+    /// As for the shapes, `(0, 0)` is the lower left pixel of the phantom. If several pixels share
+    /// the maximum, the first one in storage order (the topmost row first, then left to right) is
+    /// returned. `NaN` values are ignored. Returns `None` if the phantom contains no (non-`NaN`)
+    /// pixels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let phantom = Phantom::new(64, 64, &[Shape::ellipse(0.5, 0.5, 0.1, 0.1, 0.0, 1.0)]);
+    /// let (x, y, value) = phantom.argmax().unwrap();
+    /// assert!(x > 32 && y > 32);
+    /// assert_eq!(value, 1.0);
+    /// ```
+    pub fn argmax(&self) -> Option<(u32, u32, f64)> {
+        self.arg_extremum(|x, best| x > best)
+    }
+
+    /// Returns the position `(x, y)` and value of the smallest pixel.
+    ///
+    /// See `argmax` for the conventions used.
+    pub fn argmin(&self) -> Option<(u32, u32, f64)> {
+        self.arg_extremum(|x, best| x < best)
+    }
+
+    /// Returns the position and value of the first pixel which is not `NaN` and for which
+    /// `better(value, best)` does not hold for any later pixel.
+    fn arg_extremum(&self, better: impl Fn(f64, f64) -> bool) -> Option<(u32, u32, f64)> {
+        let (idx, value) = self
+            .data
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, x)| !x.is_nan())
+            .fold(None, |best, (idx, x)| match best {
+                Some((_, best_x)) if !better(x, best_x) => best,
+                _ => Some((idx, x)),
+            })?;
+        let nx = self.nx as usize;
+        let x = (idx % nx) as u32;
+        let y = self.ny - (idx / nx) as u32 - 1;
+        Some((x, y, value))
+    }
+
+    /// Returns the fraction of pixels whose value differs from `background`.
+    ///
+    /// This is a quick check how much of the phantom is covered by shapes. Returns `0.0` for a
+    /// phantom without pixels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let coverage = shepplogan(128, 128).coverage(0.0);
+    /// assert!(coverage > 0.4 && coverage < 0.6);
+    /// ```
+    pub fn coverage(&self, background: f64) -> f64 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        let foreground = self.data.iter().filter(|&&x| x != background).count();
+        foreground as f64 / self.data.len() as f64
+    }
+
+    /// Returns the cached extrema if available, otherwise computes them without caching.
+    fn compute_extrema(&self) -> (f64, f64) {
+        if let Some(minmax) = self.minmax {
+            return minmax;
+        }
+        self.data.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(acc_min, acc_max), &x| {
+                (
+                    if x < acc_min { x } else { acc_min },
+                    if x > acc_max { x } else { acc_max },
+                )
+            },
+        )
+    }
+
+    /// Returns the phantom as a flattened `Vec<f64>` in column-major order.
+    ///
+    /// The orientation is the same as for `into_vec`, i.e. the first row is the top of the
+    /// phantom. However, the values are stored column by column starting at the left, therefore
+    /// the value in row `row` (counted from the top) and column `col` is at index
+    /// `col * ny + row`. This is the layout expected by FORTRAN and BLAS/LAPACK routines.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Phantom;
+    /// let phantom = Phantom::from_data(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    /// assert_eq!(phantom.into_vec_col_major(), [1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    /// ```
+    pub fn into_vec_col_major(self) -> Vec<f64> {
+        let (nx, ny) = (self.nx as usize, self.ny as usize);
+        let mut data = Vec::with_capacity(self.data.len());
+        for col in 0..nx {
+            data.extend((0..ny).map(|row| self.data[row * nx + col]));
+        }
+        data
+    }
+
+    /// Returns the phantom as a flattened `Vec<half::f16>`.
+    ///
+    /// Each value is rounded to the nearest half precision float. Note that `f16` only has a
+    /// precision of about three decimal digits (a relative rounding error of up to `2^-11`) and a
+    /// largest finite value of `65504`, larger values are converted to infinity. This is usually
+    /// sufficient for inference pipelines but not for accurate ground truth.
+    ///
+    /// Requires the `half` feature.
+    #[cfg(feature = "half")]
+    pub fn into_vec_f16(self) -> Vec<half::f16> {
+        self.data.into_iter().map(half::f16::from_f64).collect()
+    }
+
+    /// Returns the phantom as a `Vec<u8>`
+    ///
+    /// Note that this will cast `f64` to `u8`, therefore the caller must ensure that the current
+    /// `f64` values of the phantom are within the range `[0, 255)`.
+    pub fn into_vec_u8(self) -> Vec<u8> {
+        self.data.into_iter().map(|x| x as u8).collect()
+    }
+
+    /// Converts the phantom row by row to `u8` and passes each row to `f`.
+    ///
+    /// The rows are passed from top to bottom as `f(row, pixels)`, where `row` is the index of
+    /// the row counted from the top. The values are converted as in `into_vec_u8`, where values
+    /// outside of `[0, 255]` are clamped to the nearest bound (`NaN` is mapped to `0`). Only a
+    /// single row is allocated, which allows streaming the phantom into an encoder.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let mut rows = 0;
+    /// shepplogan(64, 48).scale(100.0).for_each_row_u8(|_, pixels| {
+    ///     assert_eq!(pixels.len(), 64);
+    ///     rows += 1;
+    /// });
+    /// assert_eq!(rows, 48);
+    /// ```
+    pub fn for_each_row_u8(&self, mut f: impl FnMut(u32, &[u8])) {
+        let mut row = Vec::with_capacity(self.nx as usize);
+        for (i, chunk) in self.data.chunks(self.nx.max(1) as usize).enumerate() {
+            row.clear();
+            // `as` saturates at the bounds of `u8`
+            row.extend(chunk.iter().map(|&x| x as u8));
+            f(i as u32, &row);
+        }
+    }
+
+    /// Returns the phantom as a `Vec<u8>` together with the number of clamped pixels.
+    ///
+    /// In contrast to `into_vec_u8`, values outside of `[0, 255]` are clamped to the nearest
+    /// bound. The second element of the returned tuple counts how many pixels were out of range
+    /// (including `NaN`, which is mapped to `0`). This allows batch pipelines to detect data
+    /// quality problems without having to scan the data themselves.
+    pub fn into_vec_u8_lossy_report(self) -> (Vec<u8>, usize) {
+        let mut clamped = 0;
+        let data = self
+            .data
+            .into_iter()
+            .map(|x| {
+                if (0.0..=255.0).contains(&x) {
+                    x as u8
+                } else {
+                    clamped += 1;
+                    if x > 255.0 {
+                        255
+                    } else {
+                        0
+                    }
+                }
+            })
+            .collect();
+        (data, clamped)
+    }
+
+    /// Returns the phantom as a `Vec<u8>`, where the values are mapped from `[lo, hi]` onto
+    /// `[0, 255]` with ordered dithering.
+    ///
+    /// Quantizing smooth gradients to 8 bit results in visible bands. To avoid them, a threshold
+    /// from a 4x4 Bayer matrix, which is tiled over the phantom starting at the upper left
+    /// corner, is added to each value before rounding down. For a constant value, the mean over a
+    /// block of 4x4 pixels matches the original value up to `1/16`. The result is
+    /// deterministic and each pixel only depends on its own value and position. In contrast, error
+    /// diffusion (e.g. Floyd–Steinberg) depends on the order in which the pixels are processed and
+    /// is therefore not used. Values outside of `[lo, hi]` are clamped to the nearest bound and
+    /// `NaN` is mapped to `0`. A degenerate range, i.e. `hi <= lo` or a bound which is `NaN`,
+    /// maps every pixel to `0` (as in `write_pgm` for a constant phantom). The pixels are in the
+    /// same order as for `into_vec`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let data = shepplogan(64, 48).into_vec_u8_dithered(0.0, 1.0);
+    /// assert_eq!(data.len(), 64 * 48);
+    /// ```
+    pub fn into_vec_u8_dithered(self, lo: f64, hi: f64) -> Vec<u8> {
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+        let range = hi - lo;
+        if range.is_nan() || range <= 0.0 {
+            return vec![0; self.data.len()];
+        }
+        let nx = self.nx.max(1) as usize;
+        self.data
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let (row, col) = (i / nx, i % nx);
+                let threshold = (f64::from(BAYER[row % 4][col % 4]) + 0.5) / 16.0;
+                let value = (x - lo) / range * 255.0;
+                // `as` maps `NaN` to `0`
+                (value + threshold).floor().clamp(0.0, 255.0) as u8
+            })
+            .collect()
+    }
+}
+
+/// Checks that the number of pixels of a phantom of size `nx` times `ny` fits into a `u32` and
+/// returns it.
+pub(crate) fn check_dimensions(nx: u32, ny: u32) -> Result<usize, Error> {
+    nx.checked_mul(ny)
+        .map(|n| n as usize)
+        .ok_or(Error::DimensionOverflow)
+}
+
+/// Creates a phantom based on given ellipses
+///
+/// Besides `nx` and `ny`, which define the number of pixels in `x` and `y` direction, this
+/// function also requires array of ShapeOnCanvas and the render options.
+///
+/// The intensities of the shapes covering a pixel are always accumulated in the order of
+/// `shapes`, therefore the serial and the parallel implementation yield bit-identical results.
+fn phantom<T: Float>(
+    shapes: &[ShapeOnCanvas],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+) -> Vec<T> {
+    #[cfg(feature = "rayon")]
+    {
+        phantom_parallel(shapes, nx, ny, options)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        phantom_serial(shapes, nx, ny, options)
+    }
+}
+
+/// Serial implementation of `phantom`, which traverses the shapes in order
+#[cfg_attr(feature = "rayon", allow(dead_code))]
+fn phantom_serial<T: Float>(
+    shapes: &[ShapeOnCanvas],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+) -> Vec<T> {
+    let mut arr = vec![T::from_f64(options.overlap.initial()); (nx * ny) as usize];
+    rasterize(shapes, nx, ny, |idx, _, shape| {
+        accumulate(&mut arr[idx], shape, options)
+    });
+    arr
+}
+
+/// Parallel implementation of `phantom`, which renders the rows in parallel
+///
+/// Within each row, the shapes are traversed in order, which means that every pixel accumulates
+/// the intensities in the same order as in `phantom_serial`.
+#[cfg(feature = "rayon")]
+fn phantom_parallel<T: Float>(
+    shapes: &[ShapeOnCanvas],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+) -> Vec<T> {
+    use rayon::prelude::*;
+
+    let mut arr = vec![T::from_f64(options.overlap.initial()); (nx * ny) as usize];
+    if nx == 0 || ny == 0 {
+        return arr;
+    }
+    let shapes = visible_shapes(shapes);
+    arr.par_chunks_mut(nx as usize)
+        .enumerate()
+        .for_each(|(row, vals)| {
+            // Rows are stored from top to bottom
+            let y = ny - row as u32 - 1;
+            for (shape, bbox) in shapes.iter() {
+                if bbox.y_low > y || y > bbox.y_high {
+                    continue;
+                }
+                for x in bbox.x_low..=bbox.x_high {
+                    if shape.inside_pixel(x, y) {
+                        accumulate(&mut vals[x as usize], shape, options);
+                    }
+                }
+            }
+        });
+    arr
+}
+
+/// Renders `shapes` with `samples` times `samples` samples per pixel and averages them.
+///
+/// The samples are taken on the canvas of a phantom of size `samples * nx` times `samples * ny`,
+/// see `Canvas::supersample`, therefore a single sample yields the same result as `phantom`.
+/// Only the `samples` rows of samples of a single row of the phantom are stored at a time. Returns
+/// `Error::DimensionOverflow` if the number of pixels or samples along an axis does not fit into
+/// a `u32`.
+fn phantom_supersampled(
+    shapes: &[Shape],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+    samples: u32,
+) -> Result<Vec<f64>, Error> {
+    check_dimensions(nx, ny)?;
+    let canvas = options.canvas(nx, ny).supersample(samples)?;
+    let mut shapes = shapes
+        .iter()
+        .map(|shape| shape.on_canvas_with(&canvas))
+        .collect::<Vec<_>>();
+    options.reorder(&mut shapes);
+    if samples == 1 {
+        return Ok(phantom(&shapes, nx, ny, options));
+    }
+    #[cfg(feature = "rayon")]
+    {
+        Ok(phantom_supersampled_parallel(
+            &shapes, nx, ny, options, samples,
+        ))
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        Ok(phantom_supersampled_serial(
+            &shapes, nx, ny, options, samples,
+        ))
+    }
+}
+
+/// Returns the shapes together with their bounding boxes, omitting the shapes outside of the
+/// canvas, which do not cover any pixel.
+fn visible_shapes(shapes: &[ShapeOnCanvas]) -> Vec<(&ShapeOnCanvas, BoundingBox)> {
+    shapes
+        .iter()
+        .map(|shape| (shape, shape.bounding_box()))
+        .filter(|(_, bbox)| !bbox.is_empty())
+        .collect()
+}
+
+/// Renders row `row` (counted from the top) of a supersampled phantom into `vals`.
+///
+/// `shapes` are placed on the canvas of the samples, see `phantom_supersampled`. The `samples`
+/// rows of samples are rendered one after the other from the top, each into `fine_row`, and
+/// summed up from left to right before the sum is divided by the number of samples per pixel.
+fn supersampled_row(
+    shapes: &[(&ShapeOnCanvas, BoundingBox)],
+    ny: u32,
+    options: &RenderOptions,
+    samples: u32,
+    row: usize,
+    vals: &mut [f64],
+) {
+    let norm = f64::from(samples) * f64::from(samples);
+    let mut fine_row = vec![options.overlap.initial(); vals.len() * samples as usize];
+    // Rows are stored from top to bottom, which also applies to the rows of samples
+    let y_top = (ny - row as u32) * samples - 1;
+    for y in (y_top + 1 - samples..=y_top).rev() {
+        fine_row.fill(options.overlap.initial());
+        for (shape, bbox) in shapes.iter() {
+            if bbox.y_low > y || y > bbox.y_high {
+                continue;
+            }
+            for x in bbox.x_low..=bbox.x_high {
+                if shape.inside_pixel(x, y) {
+                    accumulate(&mut fine_row[x as usize], shape, options);
+                }
+            }
+        }
+        for (val, sub) in vals.iter_mut().zip(fine_row.chunks(samples as usize)) {
+            *val += sub.iter().sum::<f64>();
+        }
+    }
+    vals.iter_mut().for_each(|val| *val /= norm);
+}
+
+/// Serial implementation of the supersampled renderer, which renders the rows one after the other
+///
+/// `shapes` are placed on the canvas of the samples, see `phantom_supersampled`.
+#[cfg_attr(feature = "rayon", allow(dead_code))]
+fn phantom_supersampled_serial(
+    shapes: &[ShapeOnCanvas],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+    samples: u32,
+) -> Vec<f64> {
+    let mut arr = vec![0.0; nx as usize * ny as usize];
+    if nx == 0 || ny == 0 {
+        return arr;
+    }
+    let shapes = visible_shapes(shapes);
+    arr.chunks_mut(nx as usize)
+        .enumerate()
+        .for_each(|(row, vals)| supersampled_row(&shapes, ny, options, samples, row, vals));
+    arr
+}
+
+/// Parallel implementation of the supersampled renderer, which renders the rows in parallel
+///
+/// Every row is rendered by `supersampled_row` as in `phantom_supersampled_serial`, therefore
+/// the results are bit-identical.
+#[cfg(feature = "rayon")]
+fn phantom_supersampled_parallel(
+    shapes: &[ShapeOnCanvas],
+    nx: u32,
+    ny: u32,
+    options: &RenderOptions,
+    samples: u32,
+) -> Vec<f64> {
+    use rayon::prelude::*;
+
+    let mut arr = vec![0.0; nx as usize * ny as usize];
+    if nx == 0 || ny == 0 {
+        return arr;
+    }
+    let shapes = visible_shapes(shapes);
+    arr.par_chunks_mut(nx as usize)
+        .enumerate()
+        .for_each(|(row, vals)| supersampled_row(&shapes, ny, options, samples, row, vals));
+    arr
+}
+
+/// Renders `shapes` such that every shape adds its intensity weighted by the fraction of the
+/// pixel which it covers.
+///
+/// Since a pixel covers the area up to half a pixel around its sampling point, the bounding boxes
+/// of the shapes are extended by one pixel.
+fn phantom_area(shapes: &[Shape], nx: u32, ny: u32) -> Vec<f64> {
+    let mut arr = vec![0.0; (nx * ny) as usize];
+    if nx == 0 || ny == 0 {
+        return arr;
+    }
+    for shape in shapes {
+        let shape = shape.on_canvas(nx, ny);
+        let bbox = shape.bounding_box();
+        if bbox.is_empty() {
+            continue;
+        }
+        let bbox = bbox.grow(nx, ny);
+        for y in (bbox.y_low..=bbox.y_high).rev() {
+            let row = (ny - y - 1) as usize * nx as usize;
+            for x in bbox.x_low..=bbox.x_high {
+                let coverage = shape.coverage(x, y);
+                if coverage > 0.0 {
+                    arr[row + x as usize] += coverage * shape.intensity();
+                }
+            }
+        }
+    }
+    arr
+}
+
+/// Adds the intensity of `shape` to the pixel value `val` according to the render `options`.
+#[inline(always)]
+fn accumulate<T: Float>(val: &mut T, shape: &ShapeOnCanvas, options: &RenderOptions) {
+    *val = options.overlap.apply(*val, T::from_f64(shape.intensity()));
+    if let Some(floor) = options.floor {
+        *val = val.max(T::from_f64(floor));
+    }
+}
+
+/// Calls `hit(idx, shape_idx, shape)` for every pixel covered by a shape, where `idx` is the index
+/// of the pixel in the data of a phantom of size `nx` times `ny` and `shape_idx` is the index of
+/// `shape` in `shapes`.
+///
+/// The shapes are traversed in order, therefore all hits of a shape happen after the hits of the
+/// preceding shapes. The pixels of each shape are visited row by row from the top, such that
+/// `idx` increases monotonically and the data is accessed contiguously.
+pub(crate) fn rasterize<F>(shapes: &[ShapeOnCanvas], nx: u32, ny: u32, mut hit: F)
+where
+    F: FnMut(usize, usize, &ShapeOnCanvas),
+{
+    // Bounding boxes cannot represent an empty canvas
+    if nx == 0 || ny == 0 {
+        return;
+    }
+    for (shape_idx, shape) in shapes.iter().enumerate() {
+        let bbox = shape.bounding_box();
+        // Shapes outside of the canvas do not cover any pixel
+        if bbox.is_empty() {
+            continue;
+        }
+        for y in (bbox.y_low..=bbox.y_high).rev() {
+            let row = ((ny - y - 1) * nx) as usize;
+            for x in bbox.x_low..=bbox.x_high {
+                if shape.inside_pixel(x, y) {
+                    hit(row + x as usize, shape_idx, shape);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_abs_diff_eq;
+
+    use crate::{
+        shepplogan, BorderMode, Boundary, EdgeMode, Error, Overlap, Phantom, RenderOptions, Scale,
+        Shape, ShapeOrder,
+    };
+
+    use super::{phantom, rasterize};
+    use crate::shepplogan::Preset;
+
+    #[derive(Debug, Copy, Clone)]
+    struct FloatNotNanSmall(f64);
+
+    impl quickcheck::Arbitrary for FloatNotNanSmall {
+        fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+            loop {
+                let val = f64::arbitrary(g).abs() % 10.0;
+                if !val.is_nan() && val.is_finite() {
+                    return FloatNotNanSmall(val);
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Copy, Clone)]
+    struct UnsignedInt32(u32);
+
+    impl quickcheck::Arbitrary for UnsignedInt32 {
+        fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+            UnsignedInt32(u32::arbitrary(g) % 128)
+        }
+    }
+
+    #[quickcheck]
+    // Add a reason why this lint is allowed once the feature `lint_reasons` is stabilized.
+    #[allow(clippy::too_many_arguments)]
+    fn test_phantom(
+        center_x: FloatNotNanSmall,
+        center_y: FloatNotNanSmall,
+        major_axis: FloatNotNanSmall,
+        minor_axis: FloatNotNanSmall,
+        theta: FloatNotNanSmall,
+        nx: UnsignedInt32,
+        ny: UnsignedInt32,
+    ) {
+        let nx = nx.0;
+        let ny = ny.0;
+        let center_x = center_x.0;
+        let center_y = center_y.0;
+        let major_axis = major_axis.0;
+        let minor_axis = minor_axis.0;
+        let theta = theta.0;
+
+        let shape = Shape::ellipse(center_x, center_y, major_axis, minor_axis, theta, 1.0)
+            .on_canvas(nx, ny);
+
+        let phantom: Vec<f64> = phantom(
+            std::slice::from_ref(&shape),
+            nx,
+            ny,
+            &RenderOptions::default(),
+        );
+
+        for x in 0..nx {
+            for y in 0..ny {
+                let val = phantom[((ny - y - 1) * nx + x) as usize];
+                let inside = shape.inside(f64::from(x), f64::from(y));
+                // println!("x: {} | y: {} | val: {} | inside: {}", x, y, val, inside);
+                if inside {
+                    assert_eq!(val.to_ne_bytes(), 1.0f64.to_ne_bytes());
+                } else {
+                    assert_eq!(val.to_ne_bytes(), 0.0f64.to_ne_bytes());
+                }
+            }
+        }
+    }
+
+    #[quickcheck]
+    // Add a reason why this lint is allowed once the feature `lint_reasons` is stabilized.
+    #[allow(clippy::too_many_arguments)]
+    fn test_phantom_new(
+        center_x: FloatNotNanSmall,
+        center_y: FloatNotNanSmall,
+        major_axis: FloatNotNanSmall,
+        minor_axis: FloatNotNanSmall,
+        theta: FloatNotNanSmall,
+        nx: UnsignedInt32,
+        ny: UnsignedInt32,
+    ) {
+        let nx = nx.0;
+        let ny = ny.0;
+        let center_x = center_x.0;
+        let center_y = center_y.0;
+        let major_axis = major_axis.0;
+        let minor_axis = minor_axis.0;
+        let theta = theta.0;
+
+        let shape = Shape::ellipse(center_x, center_y, major_axis, minor_axis, theta, 1.0);
+        let shape_on_canvas = shape.on_canvas(nx, ny);
+
+        let phantom = Phantom::new(nx, ny, &[shape]);
+
+        assert!(phantom.minmax.is_none());
+
+        for x in 0..nx {
+            for y in 0..ny {
+                let val = phantom.data[((ny - y - 1) * nx + x) as usize];
+                let inside = shape_on_canvas.inside(f64::from(x), f64::from(y));
+                // println!("x: {} | y: {} | val: {} | inside: {}", x, y, val, inside);
+                if inside {
+                    assert_eq!(val.to_ne_bytes(), 1.0f64.to_ne_bytes());
+                } else {
+                    assert_eq!(val.to_ne_bytes(), 0.0f64.to_ne_bytes());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_phantom_extrema_scale() {
+        let nx = 64;
+        let ny = 64;
+        let center_x = 0.0;
+        let center_y = 0.0;
+        let major_axis = 0.9;
+        let minor_axis = 0.7;
+        let theta = 30.0;
+
+        let shape = Shape::ellipse(center_x, center_y, major_axis, minor_axis, theta, 1.0);
+
+        let phantom = Phantom::new(nx, ny, &[shape]);
+
+        assert!(phantom.minmax.is_none());
+
+        let mut phantom = phantom.scale(2.0);
+
+        assert!(phantom.minmax.is_none());
+
+        let (min, max) = phantom.extrema();
+
+        assert_eq!(min.to_ne_bytes(), 0.0f64.to_ne_bytes());
+        assert_eq!(max.to_ne_bytes(), 2.0f64.to_ne_bytes());
+
+        assert_eq!(
+            phantom.minmax.unwrap().0.to_ne_bytes(),
+            0.0f64.to_ne_bytes()
+        );
+        assert_eq!(
+            phantom.minmax.unwrap().1.to_ne_bytes(),
+            2.0f64.to_ne_bytes()
+        );
+
+        let mut phantom = phantom.scale(2.0);
+
+        assert_eq!(
+            phantom.minmax.unwrap().0.to_ne_bytes(),
+            0.0f64.to_ne_bytes()
+        );
+        assert_eq!(
+            phantom.minmax.unwrap().1.to_ne_bytes(),
+            4.0f64.to_ne_bytes()
+        );
+
+        let (min, max) = phantom.extrema();
+
+        assert_eq!(min.to_ne_bytes(), 0.0f64.to_ne_bytes());
+        assert_eq!(max.to_ne_bytes(), 4.0f64.to_ne_bytes());
+    }
+
+    #[quickcheck]
+    // Add a reason why this lint is allowed once the feature `lint_reasons` is stabilized.
+    #[allow(clippy::too_many_arguments)]
+    fn test_phantom_into_vec(
+        center_x: FloatNotNanSmall,
+        center_y: FloatNotNanSmall,
+        major_axis: FloatNotNanSmall,
+        minor_axis: FloatNotNanSmall,
+        theta: FloatNotNanSmall,
+        nx: UnsignedInt32,
+        ny: UnsignedInt32,
+    ) {
+        let nx = nx.0;
+        let ny = ny.0;
+        let center_x = center_x.0;
+        let center_y = center_y.0;
+        let major_axis = major_axis.0;
+        let minor_axis = minor_axis.0;
+        let theta = theta.0;
+
+        let shape = Shape::ellipse(center_x, center_y, major_axis, minor_axis, theta, 1.0);
+
+        let phantom = Phantom::new(nx, ny, std::slice::from_ref(&shape));
+
+        let data1 = phantom.data.clone();
+        let data2: Vec<f64> = phantom.into_vec();
+
+        data1
+            .into_iter()
+            .zip(data2.into_iter())
+            .map(|(d1, d2)| assert_eq!(d1.to_ne_bytes(), d2.to_ne_bytes()))
+            .count();
+
+        let phantom = Phantom::new(nx, ny, &[shape]);
+
+        let data1 = phantom.data.clone();
+        let data2: Vec<u8> = phantom.into_vec_u8();
+
+        data1
+            .into_iter()
+            .zip(data2.into_iter())
+            .map(|(d1, d2)| assert_eq!(d1.to_ne_bytes(), (d2 as f64).to_ne_bytes()))
+            .count();
+    }
+
+    #[test]
+    fn test_phantom_into_vec_u8_lossy_report() {
+        let shapes = [
+            Shape::rectangle(-0.5, 0.0, 0.8, 1.8, 0.0, 300.0),
+            Shape::rectangle(0.5, 0.0, 0.8, 1.8, 0.0, -20.0),
+            Shape::rectangle(0.0, 0.0, 0.1, 0.1, 0.0, 100.0),
+        ];
+        let (nx, ny) = (64, 64);
+
+        let phantom = Phantom::new(nx, ny, &shapes);
+        let out_of_range = phantom
+            .data
+            .iter()
+            .filter(|&&x| !(0.0..=255.0).contains(&x))
+            .count();
+        let expected: Vec<u8> = phantom
+            .data
+            .iter()
+            .map(|&x| {
+                if x > 255.0 {
+                    255
+                } else if x < 0.0 {
+                    0
+                } else {
+                    x as u8
+                }
+            })
+            .collect();
+
+        assert!(out_of_range > 0);
+        assert!(phantom.data.contains(&100.0));
+
+        let (data, clamped) = phantom.into_vec_u8_lossy_report();
+
+        assert_eq!(clamped, out_of_range);
+        assert_eq!(data, expected);
+        assert!(data.contains(&255));
+        assert!(data.contains(&100));
+    }
+
+    #[test]
+    fn test_phantom_circular_fov() {
+        let (nx, ny) = (64, 80);
+        let phantom = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 0.0, 3.0, 3.0, 0.0, 1.0)]);
+
+        assert!(phantom.data.iter().all(|&x| x == 1.0));
+
+        let mut phantom = phantom.circular_fov(1.0, 0.0);
+
+        let index = |x: u32, y: u32| ((ny - y - 1) * nx + x) as usize;
+        for (x, y) in [(0, 0), (nx - 1, 0), (0, ny - 1), (nx - 1, ny - 1)] {
+            assert_eq!(
+                phantom.data[index(x, y)].to_ne_bytes(),
+                0.0f64.to_ne_bytes()
+            );
+        }
+        assert_eq!(
+            phantom.data[index(nx / 2, ny / 2)].to_ne_bytes(),
+            1.0f64.to_ne_bytes()
+        );
+        // The whole height of the circle is outside of the FOV (the circle is limited by `nx`)
+        assert_eq!(
+            phantom.data[index(nx / 2, 4)].to_ne_bytes(),
+            0.0f64.to_ne_bytes()
+        );
+        assert_eq!(phantom.extrema(), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_phantom_mask() {
+        let (nx, ny) = (16, 8);
+        let phantom = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 0.0, 3.0, 3.0, 0.0, 1.0)]);
+
+        let phantom = phantom.mask(|x, y| x < 4 && y < 2, -1.0);
+
+        for x in 0..nx {
+            for y in 0..ny {
+                let expected = if x < 4 && y < 2 { 1.0f64 } else { -1.0 };
+                assert_eq!(
+                    phantom.data[((ny - y - 1) * nx + x) as usize].to_ne_bytes(),
+                    expected.to_ne_bytes()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_phantom_spacing() {
+        let phantom = Phantom::new(64, 80, &[]);
+
+        assert!(phantom.spacing().is_none());
+        assert_eq!(phantom.physical_extent(), (64.0, 80.0));
+        assert_eq!(phantom.world_to_pixel(3.0, 5.0), (3.0, 5.0));
+
+        let phantom = phantom.scale(2.0).with_spacing(0.5, 1.25);
+
+        assert_eq!(phantom.spacing(), Some((0.5, 1.25)));
+        assert_eq!(phantom.physical_extent(), (32.0, 100.0));
+
+        let (x, y) = phantom.world_to_pixel(12.3, 45.6);
+        assert_abs_diff_eq!(x, 24.6, epsilon = 1e-12);
+        assert_abs_diff_eq!(y, 36.48, epsilon = 1e-12);
+
+        let (wx, wy) = phantom.pixel_to_world(x, y);
+        assert_abs_diff_eq!(wx, 12.3, epsilon = 1e-12);
+        assert_abs_diff_eq!(wy, 45.6, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_phantom_floor() {
+        let (nx, ny) = (32, 32);
+        // The negative shape comes first, therefore the pixels in the overlap would temporarily be
+        // negative without a floor.
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, -1.0),
+            Shape::ellipse(0.0, 0.0, 0.25, 0.25, 0.0, 1.0),
+        ];
+        let center = ((ny - ny / 2 - 1) * nx + nx / 2) as usize;
+        let ring = ((ny - ny / 2 - 1) * nx + nx / 2 + 6) as usize;
+
+        let phantom = Phantom::new(nx, ny, &shapes);
+        assert_eq!(phantom.data[center].to_ne_bytes(), 0.0f64.to_ne_bytes());
+        assert_eq!(phantom.data[ring].to_ne_bytes(), (-1.0f64).to_ne_bytes());
+
+        let phantom = Phantom::new_with_options(nx, ny, &shapes, &RenderOptions::new().floor(0.0));
+        assert!(phantom.data.iter().all(|&x| x >= 0.0));
+        // Never went below zero before the second shape was added
+        assert_eq!(phantom.data[center].to_ne_bytes(), 1.0f64.to_ne_bytes());
+        assert_eq!(phantom.data[ring].to_ne_bytes(), 0.0f64.to_ne_bytes());
+
+        let phantom = Phantom::new_with_options(nx, ny, &shapes, &RenderOptions::new());
+        assert_eq!(phantom.data, Phantom::new(nx, ny, &shapes).data);
+    }
+
+    #[test]
+    fn test_phantom_transpose() {
+        let (nx, ny) = (48, 80);
+        let phantom = Phantom::new(
+            nx,
+            ny,
+            &[
+                Shape::ellipse(0.3, -0.2, 0.6, 0.3, 25.0, 1.0),
+                Shape::rectangle(-0.4, 0.5, 0.3, 0.2, 0.0, 2.0),
+            ],
+        )
+        .with_spacing(0.5, 2.0);
+
+        let transposed = phantom.transpose();
+        assert_eq!((transposed.nx, transposed.ny), (ny, nx));
+        assert_eq!(transposed.spacing, Some((2.0, 0.5)));
+        for x in 0..nx {
+            for y in 0..ny {
+                assert_eq!(
+                    transposed.data[transposed.index(y, x)].to_ne_bytes(),
+                    phantom.data[phantom.index(x, y)].to_ne_bytes()
+                );
+            }
+        }
+
+        let twice = transposed.transpose();
+        assert_eq!((twice.nx, twice.ny), (nx, ny));
+        assert_eq!(twice.spacing, phantom.spacing);
+        assert_eq!(twice.data, phantom.data);
+    }
+
+    #[test]
+    fn test_phantom_new_dedup() {
+        let (nx, ny) = (64, 64);
+        let ellipse = Shape::ellipse(0.1, -0.2, 0.5, 0.3, 10.0, 1.5);
+        let rectangle = Shape::rectangle(0.0, 0.0, 0.4, 0.4, 0.0, 1.0);
+        let shapes = [ellipse.clone(), rectangle.clone(), ellipse.clone()];
+
+        let additive = Phantom::new(nx, ny, &shapes);
+        let dedup = Phantom::new_dedup(nx, ny, &shapes);
+        let ellipse_only = Phantom::new(nx, ny, std::slice::from_ref(&ellipse));
+        let rectangle_only = Phantom::new(nx, ny, &[rectangle]);
+
+        assert_eq!(dedup.data, Phantom::new(nx, ny, &shapes[..2]).data);
+        for i in 0..additive.data.len() {
+            let doubled = additive.data[i] - rectangle_only.data[i];
+            let single = dedup.data[i] - rectangle_only.data[i];
+            assert_eq!(doubled.to_ne_bytes(), (2.0 * single).to_ne_bytes());
+            assert_eq!(single.to_ne_bytes(), ellipse_only.data[i].to_ne_bytes());
+        }
+
+        // Shapes which only differ in intensity are not duplicates
+        let shapes = [
+            ellipse.clone(),
+            Shape::ellipse(0.1, -0.2, 0.5, 0.3, 10.0, 1.0),
+        ];
+        assert_eq!(
+            Phantom::new_dedup(nx, ny, &shapes).data,
+            Phantom::new(nx, ny, &shapes).data
+        );
+    }
+
+    #[test]
+    fn test_phantom_scale_mut() {
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.7, 0.4, 30.0, 1.5),
+            Shape::rectangle(0.0, 0.3, 0.5, 0.2, 0.0, -0.5),
+        ];
+        let scaled = Phantom::new(48, 64, &shapes).scale(-2.5);
+        let mut phantom = Phantom::new(48, 64, &shapes);
+        phantom.extrema();
+        phantom.scale_mut(-2.5);
+
+        assert_eq!(phantom.data, scaled.data);
+        // The cached extrema must still be ordered correctly for negative factors
+        let minmax = phantom.minmax.unwrap();
+        phantom.minmax = None;
+        assert_eq!(minmax, phantom.extrema());
+    }
+
+    #[test]
+    fn test_phantom_normalize_clamp() {
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.7, 0.4, 30.0, 1.5),
+            Shape::rectangle(0.0, 0.3, 0.5, 0.2, 0.0, -0.5),
+        ];
+        let mut phantom = Phantom::new(48, 64, &shapes).normalize();
+        assert_eq!(phantom.minmax, Some((0.0, 1.0)));
+        phantom.minmax = None;
+        assert_eq!(phantom.extrema(), (0.0, 1.0));
+        assert_eq!(
+            phantom.data,
+            Phantom::new(48, 64, &shapes)
+                .data
+                .iter()
+                .map(|x| (x + 0.5) / 2.0)
+                .collect::<Vec<_>>()
+        );
+
+        let mut phantom = phantom.clamp(0.2, 0.5);
+        assert_eq!(phantom.minmax, Some((0.2, 0.5)));
+        phantom.minmax = None;
+        assert_eq!(phantom.extrema(), (0.2, 0.5));
+
+        let mut constant = Phantom::new(8, 8, &[]);
+        constant.data.iter_mut().for_each(|x| *x = 3.0);
+        constant.normalize_mut();
+        assert!(constant.data.iter().all(|&x| x == 0.0));
+        assert_eq!(constant.extrema(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_phantom_is_finite() {
+        let mut phantom = shepplogan(32, 32);
+        assert!(phantom.is_finite());
+        let reference = phantom.clone();
+        phantom.extrema();
+
+        phantom.data[5] = f64::NAN;
+        phantom.data[7] = f64::INFINITY;
+        phantom.data[9] = f64::NEG_INFINITY;
+        assert!(!phantom.is_finite());
+        phantom.replace_non_finite(0.5);
+        assert!(phantom.is_finite());
+        for (i, (x, y)) in phantom.data.iter().zip(reference.data.iter()).enumerate() {
+            let expected = if [5, 7, 9].contains(&i) { 0.5 } else { *y };
+            assert_eq!(x.to_ne_bytes(), expected.to_ne_bytes());
+        }
+        // The cached extrema are recomputed
+        assert_eq!(phantom.minmax, None);
+
+        let mut phantom = Phantom::from_raw(0, 0, Vec::new());
+        assert!(phantom.is_finite());
+        phantom.replace_non_finite(1.0);
+        assert!(phantom.data.is_empty());
+    }
+
+    #[test]
+    fn test_phantom_from_matlab_ellipses() {
+        // The original Shepp-Logan table in the column layout of MATLAB's `phantom` function; note
+        // that MATLAB's own table differs in its intensities
+        let rows = [
+            [2.0, 0.69, 0.92, 0.0, 0.0, 0.0],
+            [-0.98, 0.6624, 0.8740, 0.0, -0.0184, 0.0],
+            [-0.02, 0.1100, 0.3100, 0.22, 0.0, -18.0],
+            [-0.02, 0.1600, 0.4100, -0.22, 0.0, 18.0],
+            [0.01, 0.2100, 0.2500, 0.0, 0.35, 0.0],
+            [0.01, 0.0460, 0.0460, 0.0, 0.1, 0.0],
+            [0.01, 0.0460, 0.0460, 0.0, -0.1, 0.0],
+            [0.01, 0.0460, 0.0230, -0.08, -0.605, 0.0],
+            [0.01, 0.0230, 0.0230, 0.0, -0.605, 0.0],
+            [0.01, 0.0230, 0.0460, 0.06, -0.605, 0.0],
+        ];
+
+        for (nx, ny) in [(128, 128), (96, 160)] {
+            let phantom = Phantom::from_matlab_ellipses(nx, ny, &rows);
+            let reference = shepplogan(nx, ny);
+            assert_eq!(phantom.data.len(), reference.data.len());
+            for (a, b) in phantom.data.iter().zip(reference.data.iter()) {
+                // The order of the ellipses differs, therefore the sums may differ slightly
+                assert_abs_diff_eq!(a, b, epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_phantom_sample_at_center() {
+        let (nx, ny) = (64, 48);
+        let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        // If pixel `i` covers `[i, i + 1]`, the center of the canvas is at `n / 2`, which
+        // corresponds to the pixel index `n / 2 - 0.5`.
+        let analytic = (f64::from(nx) / 2.0 - 0.5, f64::from(ny) / 2.0 - 0.5);
+
+        let centroid = |phantom: &Phantom| {
+            let (mut cx, mut cy, mut count) = (0.0, 0.0, 0.0);
+            for x in 0..nx {
+                for y in 0..ny {
+                    if phantom.data[phantom.index(x, y)] > 0.0 {
+                        cx += f64::from(x);
+                        cy += f64::from(y);
+                        count += 1.0;
+                    }
+                }
+            }
+            (cx / count, cy / count)
+        };
+
+        let corner = Phantom::new(nx, ny, &shapes);
+        let (cx, cy) = centroid(&corner);
+        assert_abs_diff_eq!(cx, analytic.0 + 0.5, epsilon = 1e-9);
+        assert_abs_diff_eq!(cy, analytic.1 + 0.5, epsilon = 1e-9);
+
+        let options = RenderOptions::new().sample_at_center(true);
+        let center = Phantom::new_with_options(nx, ny, &shapes, &options);
+        let (cx, cy) = centroid(&center);
+        assert_abs_diff_eq!(cx, analytic.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(cy, analytic.1, epsilon = 1e-9);
+
+        let options = RenderOptions::new().sample_at_center(false);
+        assert_eq!(
+            Phantom::new_with_options(nx, ny, &shapes, &options).data,
+            corner.data
+        );
+    }
+
+    #[test]
+    fn test_phantom_group() {
+        let (nx, ny) = (64, 64);
+        let circles = vec![
+            Shape::ellipse(-0.15, 0.0, 0.3, 0.3, 0.0, 1.0),
+            Shape::ellipse(0.15, 0.0, 0.3, 0.3, 0.0, 1.0),
+        ];
+        let summed = Phantom::new(nx, ny, &circles);
+        let group = Phantom::new(nx, ny, &[Shape::group(circles, 0.5)]);
+
+        // The center is covered by both circles
+        let center = group.index(nx / 2, ny / 2);
+        assert_eq!(summed.data[center].to_ne_bytes(), 2.0f64.to_ne_bytes());
+        assert_eq!(group.data[center].to_ne_bytes(), 0.5f64.to_ne_bytes());
+        for (&s, &g) in summed.data.iter().zip(group.data.iter()) {
+            let expected: f64 = if s > 0.0 { 0.5 } else { 0.0 };
+            assert_eq!(g.to_ne_bytes(), expected.to_ne_bytes());
+        }
+    }
+
+    #[test]
+    fn test_phantom_argmax_argmin() {
+        let (nx, ny) = (64, 48);
+        let mut phantom = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0)]);
+        let hot = phantom.index(10, 40);
+        phantom.data[hot] = 10.0;
+        let cold = phantom.index(50, 3);
+        phantom.data[cold] = -2.0;
+
+        assert_eq!(phantom.argmax(), Some((10, 40, 10.0)));
+        assert_eq!(phantom.argmin(), Some((50, 3, -2.0)));
+
+        // NaNs are ignored
+        phantom.data[0] = f64::NAN;
+        assert_eq!(phantom.argmax(), Some((10, 40, 10.0)));
+        assert_eq!(phantom.argmin(), Some((50, 3, -2.0)));
+
+        // Ties are resolved in favor of the first pixel in storage order
+        let constant = Phantom::from_raw(3, 2, vec![1.0; 6]);
+        assert_eq!(constant.argmax(), Some((0, 1, 1.0)));
+        assert_eq!(constant.argmin(), Some((0, 1, 1.0)));
+
+        assert_eq!(Phantom::new(0, 0, &[]).argmax(), None);
+        assert_eq!(Phantom::from_raw(1, 1, vec![f64::NAN]).argmin(), None);
+    }
+
+    #[test]
+    fn test_phantom_boundary() {
+        let (nx, ny) = (64, 64);
+        let inclusive = RenderOptions::new().boundary(Boundary::Inclusive);
+        let exclusive = RenderOptions::new().boundary(Boundary::Exclusive);
+        let covered = |shape: &Shape, options: &RenderOptions, x: u32, y: u32| {
+            let phantom = Phantom::new_with_options(nx, ny, std::slice::from_ref(shape), options);
+            phantom.data[phantom.index(x, y)] > 0.0
+        };
+
+        // The boundaries of the following shapes pass exactly through the sampled pixel positions
+        // (48, 32), (32, 48) and (16, 16) respectively, whereas (40, 32) is strictly inside.
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 1.0, 1.0, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 1.0, 1.0, 90.0, 1.0),
+        ];
+        for shape in shapes.iter() {
+            assert!(covered(shape, &inclusive, 48, 32));
+            assert!(!covered(shape, &exclusive, 48, 32));
+            assert!(covered(shape, &inclusive, 40, 32));
+            assert!(covered(shape, &exclusive, 40, 32));
+        }
+        assert!(covered(&shapes[1], &inclusive, 16, 16));
+        assert!(!covered(&shapes[1], &exclusive, 16, 16));
+        assert_eq!(
+            Phantom::new_with_options(nx, ny, &shapes, &inclusive).data,
+            Phantom::new(nx, ny, &shapes).data
+        );
+
+        // (24, 32) lies on the boundary of the circle which is cut out of the lune
+        let lune = Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 1.0);
+        assert!(covered(&lune, &inclusive, 24, 32));
+        assert!(!covered(&lune, &exclusive, 24, 32));
+        assert!(covered(&lune, &exclusive, 20, 32));
+    }
+
+    #[test]
+    fn test_phantom_try_new_from_data() {
+        let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        assert_eq!(
+            Phantom::try_new(32, 48, &shapes).unwrap().data,
+            Phantom::new(32, 48, &shapes).data
+        );
+        assert!(matches!(
+            Phantom::try_new(1 << 16, 1 << 16, &shapes),
+            Err(Error::DimensionOverflow)
+        ));
+        let invalid = [
+            shapes[0].clone(),
+            Shape::group(
+                vec![Shape::rectangle(0.0, f64::INFINITY, 0.1, 0.1, 0.0, 0.0)],
+                1.0,
+            ),
+        ];
+        assert!(matches!(
+            Phantom::try_new(32, 32, &invalid),
+            Err(Error::InvalidShape { index: 1 })
+        ));
+
+        let phantom = Phantom::from_data(3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        assert_eq!(
+            phantom.data[phantom.index(0, 1)].to_ne_bytes(),
+            1.0f64.to_ne_bytes()
+        );
+        assert!(matches!(
+            Phantom::from_data(3, 2, vec![0.0; 5]),
+            Err(Error::DimensionMismatch {
+                expected: 6,
+                actual: 5
+            })
+        ));
+        assert!(matches!(
+            Phantom::from_data(u32::MAX, 2, vec![]),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_phantom_scale() {
+        let (nx, ny) = (128, 64);
+        let shapes = [Shape::ellipse(0.0, 0.0, 1.0, 1.0, 0.0, 1.0)];
+        let render =
+            |scale| Phantom::new_with_options(nx, ny, &shapes, &RenderOptions::new().scale(scale));
+        let covered = |phantom: &Phantom, x: u32, y: u32| phantom.data[phantom.index(x, y)] > 0.0;
+
+        // The full-canvas circle fits into the shorter dimension
+        let fit_min = render(Scale::FitMin);
+        assert_eq!(fit_min.data, Phantom::new(nx, ny, &shapes).data);
+        assert!(covered(&fit_min, nx / 2, 0) && covered(&fit_min, nx / 2, ny - 1));
+        assert!(covered(&fit_min, nx / 2 - 32, ny / 2) && !covered(&fit_min, nx / 2 - 34, ny / 2));
+
+        // The circle exceeds the shorter dimension, but fits into the longer one
+        let fit_max = render(Scale::FitMax);
+        assert!(covered(&fit_max, 1, ny / 2) && covered(&fit_max, nx - 1, ny / 2));
+        assert!(covered(&fit_max, nx / 2 - 40, 0) && covered(&fit_max, nx / 2 + 40, ny - 1));
+        assert!(!covered(&fit_max, 0, 0));
+
+        let fixed = render(Scale::Fixed(10.0));
+        assert!(covered(&fixed, nx / 2 + 10, ny / 2) && !covered(&fixed, nx / 2 + 11, ny / 2));
+        assert_eq!(render(Scale::Fixed(32.0)).data, fit_min.data);
+    }
+
+    #[test]
+    fn test_phantom_coverage() {
+        let (nx, ny) = (256, 256);
+        assert_eq!(Phantom::new(nx, ny, &[]).coverage(0.0), 0.0);
+        assert_eq!(Phantom::new(0, 0, &[]).coverage(0.0), 0.0);
+        assert_eq!(Phantom::new(nx, ny, &[]).coverage(1.0), 1.0);
+
+        let circle = Phantom::new(nx, ny, &[Shape::ellipse(0.0, 0.0, 1.0, 1.0, 0.0, 1.0)]);
+        assert_abs_diff_eq!(
+            circle.coverage(0.0),
+            std::f64::consts::FRAC_PI_4,
+            epsilon = 0.01
+        );
+        assert_abs_diff_eq!(
+            circle.coverage(1.0),
+            1.0 - std::f64::consts::FRAC_PI_4,
+            epsilon = 0.01
+        );
+    }
+
+    #[test]
+    fn test_phantom_tiny() {
+        let center = RenderOptions::new().sample_at_center(true);
+
+        let phantom = shepplogan(1, 1).into_vec();
+        assert_eq!(phantom, [0.0]);
+        let phantom = Phantom::new_with_options(1, 1, &Preset::Original.shapes(), &center);
+        assert_abs_diff_eq!(phantom.data[0], 1.02, epsilon = 1e-12);
+
+        let phantom = shepplogan(2, 2);
+        assert_abs_diff_eq!(phantom.data[phantom.index(1, 1)], 1.02, epsilon = 1e-12);
+        for (x, y) in [(0, 0), (1, 0), (0, 1)] {
+            assert_eq!(phantom.data[phantom.index(x, y)], 0.0);
+        }
+        let phantom = Phantom::new_with_options(2, 2, &Preset::Original.shapes(), &center);
+        assert!(phantom.data.iter().all(|&x| x > 1.0));
+
+        // Shapes exceeding the canvas must not lead to out of bounds pixels
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 3.0, 3.0, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 4.0, 4.0, 0.0, 1.0),
+            Shape::rectangle(0.0, 0.0, 4.0, 4.0, 10.0, 1.0),
+            Shape::lune(0.0, 0.0, 3.0, 0.5, 0.0, 1.0, 1.0),
+        ];
+        for n in 0..4 {
+            let phantom = Phantom::new(n, n, &shapes);
+            assert_eq!(phantom.data.len(), (n * n) as usize);
+            assert!(phantom.data.iter().all(|&x| x == 3.0 || x == 4.0));
+            let phantom = Phantom::new(n, 2 * n, &shapes);
+            assert_eq!(phantom.data.len(), (2 * n * n) as usize);
+        }
+    }
+
+    #[test]
+    fn test_phantom_get_pixel() {
+        let (nx, ny) = (48, 32);
+        let shapes = [Shape::rectangle(-0.5, -0.5, 0.5, 0.5, 0.0, 1.0)];
+        let phantom = Phantom::new(nx, ny, &shapes);
+        assert_eq!(phantom.dimensions(), (nx, ny));
+        assert_eq!((phantom.width(), phantom.height()), (nx, ny));
+        for x in 0..nx {
+            for y in 0..ny {
+                let expected = if shapes[0].contains(nx, ny, x, y) {
+                    1.0
+                } else {
+                    0.0
+                };
+                assert_eq!(phantom.get_pixel(x, y), Some(expected));
+            }
+        }
+        // The square covers the lower left part of the phantom
+        assert_eq!(phantom.get_pixel(16, 8), Some(1.0));
+        assert_eq!(phantom.get_pixel(16, ny - 9), Some(0.0));
+        assert_eq!(phantom.get_pixel(nx, 0), None);
+        assert_eq!(phantom.get_pixel(0, ny), None);
+        assert_eq!(phantom.get_pixel(u32::MAX, u32::MAX), None);
+        assert_eq!(Phantom::from_raw(0, 0, Vec::new()).get_pixel(0, 0), None);
+    }
+
+    #[test]
+    fn test_phantom_shape_count() {
+        let (nx, ny) = (48, 32);
+        let shapes = [
+            Shape::rectangle(-0.5, -0.5, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(0.2, 0.1, 0.3, 0.2, 10.0, 0.5),
+            // Outside of the phantom
+            Shape::ellipse(5.0, 0.0, 0.3, 0.2, 0.0, 0.5),
+        ];
+        let phantom = Phantom::new(nx, ny, &shapes);
+        assert_eq!(phantom.shape_count(), Some(3));
+        assert_eq!(
+            Phantom::<f32>::new_typed(nx, ny, &shapes).shape_count(),
+            Some(3)
+        );
+        assert_eq!(Phantom::new(nx, ny, &[]).shape_count(), Some(0));
+        assert_eq!(
+            Phantom::new_supersampled(nx, ny, &shapes, 2)
+                .unwrap()
+                .shape_count(),
+            Some(3)
+        );
+        assert_eq!(
+            Phantom::new_with_mode(nx, ny, &shapes, EdgeMode::AnalyticArea)
+                .unwrap()
+                .shape_count(),
+            Some(3)
+        );
+        let steps = Phantom::render_incremental(nx, ny, &shapes);
+        assert_eq!(
+            steps.iter().map(Phantom::shape_count).collect::<Vec<_>>(),
+            [Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(
+            Phantom::new_with_labels(nx, ny, &shapes).0.shape_count(),
+            Some(3)
+        );
+        assert_eq!(shepplogan(nx, ny).shape_count(), Some(10));
+
+        // Modifications of the values keep the count, other pixel grids do not
+        assert_eq!(phantom.clone().scale(2.0).shape_count(), Some(3));
+        assert_eq!(
+            phantom.gaussian_blur(1.0, BorderMode::Zero).shape_count(),
+            Some(3)
+        );
+        assert_eq!(phantom.crop(0, 0, 4, 4).unwrap().shape_count(), None);
+        assert_eq!(phantom.transpose().shape_count(), None);
+        assert_eq!(
+            Phantom::from_data(nx, ny, phantom.into_vec())
+                .unwrap()
+                .shape_count(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_phantom_supersampled() {
+        let mut shapes = Preset::Modified.shapes().to_vec();
+        shapes.push(Shape::rectangle(0.0, 0.3, 0.5, 0.2, 20.0, -0.7));
+        shapes.push(Shape::lune(-0.2, 0.1, 0.4, -0.1, 0.2, 0.3, 0.9));
+        for (nx, ny) in [(64, 80), (33, 17), (1, 1), (0, 5)] {
+            let bits = |phantom: Phantom| {
+                phantom
+                    .into_vec()
+                    .iter()
+                    .map(|x| x.to_ne_bytes())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                bits(Phantom::new_supersampled(nx, ny, &shapes, 1).unwrap()),
+                bits(Phantom::new(nx, ny, &shapes))
+            );
+            assert_eq!(
+                Phantom::new_supersampled(nx, ny, &shapes, 3)
+                    .unwrap()
+                    .into_vec()
+                    .len(),
+                (nx * ny) as usize
+            );
+        }
+
+        // The sum of the pixels approximates the area of the ellipse
+        let (nx, ny) = (64, 64);
+        let ellipse = [Shape::ellipse(0.1, -0.2, 0.5, 0.3, 30.0, 1.0)];
+        let area = std::f64::consts::PI * 0.5 * 0.3 * 32.0 * 32.0;
+        let phantom = Phantom::new_supersampled(nx, ny, &ellipse, 8).unwrap();
+        assert_eq!(phantom.get_pixel(35, 26), Some(1.0));
+        assert_eq!(phantom.get_pixel(0, 0), Some(0.0));
+        let values = phantom.into_vec();
+        assert!(values.iter().all(|&x| (0.0..=1.0).contains(&x)));
+        assert!(values.iter().any(|&x| x > 0.0 && x < 1.0));
+        let sum: f64 = values.iter().sum();
+        assert_abs_diff_eq!(sum, area, epsilon = 1.0);
+
+        // Each subpixel of the boundary pixels samples a quarter of the pixel
+        let square = [Shape::rectangle(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        let phantom = Phantom::new_supersampled(nx, ny, &square, 2).unwrap();
+        let binary = Phantom::new(nx, ny, &square);
+        assert_eq!(binary.get_pixel(24, 32), Some(1.0));
+        assert_eq!(phantom.get_pixel(24, 32), Some(0.5));
+        assert_eq!(phantom.get_pixel(24, 24), Some(0.25));
+        assert_eq!(phantom.get_pixel(32, 32), Some(1.0));
+
+        assert!(matches!(
+            Phantom::new_supersampled(nx, ny, &square, 0),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            Phantom::new_with_mode(nx, ny, &square, EdgeMode::Supersample(0)),
+            Err(Error::InvalidParameter(_))
+        ));
+        // The number of samples along `x` does not fit into a `u32`
+        assert!(matches!(
+            Phantom::new_supersampled(1 << 20, 1, &square, 1 << 12),
+            Err(Error::DimensionOverflow)
+        ));
+        assert!(matches!(
+            Phantom::new_supersampled(1 << 16, 1 << 16, &square, 1),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_phantom_new_with_mode() {
+        let (nx, ny) = (64, 48);
+        let rows = [
+            [0.0, 0.0, 0.69, 0.92, 0.0, 1.0],
+            [0.0, -0.0184, 0.6624, 0.874, 0.0, -0.8],
+            [0.22, 0.0, 0.11, 0.31, -18.0, -0.2],
+            [-0.22, 0.0, 0.16, 0.41, 18.0, -0.2],
+            [0.0, 0.35, 0.21, 0.25, 0.0, 0.1],
+        ];
+        let ellipses = Shape::ellipses_from_rows(&rows);
+        let mut shapes = ellipses.clone();
+        shapes.push(Shape::rectangle(0.0, 0.3, 0.5, 0.2, 20.0, -0.7));
+        assert_eq!(
+            Phantom::new_with_mode(nx, ny, &shapes, EdgeMode::Binary)
+                .unwrap()
+                .into_vec(),
+            Phantom::new(nx, ny, &shapes).into_vec()
+        );
+        assert_eq!(
+            Phantom::new_with_mode(nx, ny, &shapes, EdgeMode::Supersample(3))
+                .unwrap()
+                .into_vec(),
+            Phantom::new_supersampled(nx, ny, &shapes, 3)
+                .unwrap()
+                .into_vec()
+        );
+
+        // The area of every ellipse is distributed exactly over the pixels
+        let area = Phantom::new_with_mode(nx, ny, &ellipses, EdgeMode::AnalyticArea).unwrap();
+        let expected: f64 = rows
+            .iter()
+            .map(|row| std::f64::consts::PI * row[2] * row[3] * 24.0 * 24.0 * row[5])
+            .sum();
+        assert_abs_diff_eq!(area.data.iter().sum::<f64>(), expected, epsilon = 1e-9);
+        // Inside of the shapes and in the background, the phantom is the same as `Phantom::new`
+        let binary = Phantom::new(nx, ny, &ellipses);
+        assert_eq!(area.get_pixel(32, 24), binary.get_pixel(32, 24));
+        assert_eq!(area.get_pixel(32, 6), binary.get_pixel(32, 6));
+        assert_eq!(area.get_pixel(0, 0), Some(0.0));
+        assert!(area
+            .data
+            .iter()
+            .zip(binary.data.iter())
+            .any(|(a, b)| a != b));
+
+        // The edges of other shapes are approximated
+        let square = [Shape::rectangle(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        let area = Phantom::new_with_mode(nx, ny, &square, EdgeMode::AnalyticArea).unwrap();
+        assert_eq!(area.get_pixel(26, 24), Some(0.5));
+        assert_eq!(area.get_pixel(26, 18), Some(0.25));
+        assert_eq!(area.get_pixel(32, 24), Some(1.0));
+        assert_eq!(area.get_pixel(25, 24), Some(0.0));
+
+        assert!(
+            Phantom::new_with_mode(0, 4, &shapes, EdgeMode::AnalyticArea)
+                .unwrap()
+                .into_vec()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_phantom_typed() {
+        let (nx, ny) = (64, 80);
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.7, 0.4, 30.0, 0.3),
+            Shape::rectangle(0.0, 0.3, 0.5, 0.2, 20.0, -0.7),
+            Shape::lune(-0.2, 0.1, 0.4, -0.1, 0.2, 0.3, 0.9),
+        ];
+        for overlap in [
+            Overlap::Add,
+            Overlap::Replace,
+            Overlap::Max,
+            Overlap::MaxAbs,
+            Overlap::AlphaOver { alpha: 0.3 },
+            Overlap::Multiply,
+        ] {
+            let options = RenderOptions::new().overlap(overlap).floor(-0.2);
+            let single = Phantom::<f32>::new_typed_with_options(nx, ny, &shapes, &options);
+            let double = Phantom::new_with_options(nx, ny, &shapes, &options);
+            assert_eq!(single.dimensions(), double.dimensions());
+            for x in 0..nx {
+                for y in 0..ny {
+                    let (a, b) = (
+                        single.get_pixel(x, y).unwrap(),
+                        double.get_pixel(x, y).unwrap(),
+                    );
+                    assert_abs_diff_eq!(f64::from(a), b, epsilon = 1e-6);
+                }
+            }
+        }
+        // Without accumulation, the values are the rounded values of the `f64` phantom
+        let options = RenderOptions::new().overlap(Overlap::Replace);
+        let single = Phantom::<f32>::new_typed_with_options(nx, ny, &shapes, &options);
+        let double = Phantom::new_with_options(nx, ny, &shapes, &options);
+        assert_eq!(
+            single.with_spacing(1.0, 2.0).into_vec(),
+            double.data.iter().map(|&x| x as f32).collect::<Vec<_>>()
+        );
+        // The `f64` phantom is unchanged
+        assert_eq!(
+            Phantom::<f64>::new_typed(nx, ny, &shapes).data,
+            Phantom::new(nx, ny, &shapes).data
+        );
+    }
+
+    #[test]
+    fn test_phantom_rows() {
+        let (nx, ny) = (48, 32);
+        let phantom = shepplogan(nx, ny);
+        assert_eq!(phantom.rows().len(), ny as usize);
+        assert!(phantom.rows().all(|row| row.len() == nx as usize));
+        assert_eq!(
+            phantom.rows().flatten().copied().collect::<Vec<_>>(),
+            phantom.data
+        );
+        for x in 0..nx {
+            for y in 0..ny {
+                let row = phantom.rows().nth((ny - 1 - y) as usize).unwrap();
+                assert_eq!(
+                    row[x as usize].to_ne_bytes(),
+                    phantom.data[phantom.index(x, y)].to_ne_bytes()
+                );
+            }
+        }
+
+        let empty = Phantom::from_raw(0, 3, Vec::new());
+        assert_eq!(empty.rows().collect::<Vec<_>>(), vec![&[] as &[f64]; 3]);
+    }
+
+    #[test]
+    fn test_phantom_for_each_row_u8() {
+        let (nx, ny) = (48, 32);
+        let mut phantom = shepplogan(nx, ny).scale(200.0);
+        phantom.data[0] = -3.0;
+        phantom.data[1] = 1000.0;
+        phantom.data[2] = f64::NAN;
+
+        let mut rows = Vec::new();
+        let mut concatenated = Vec::new();
+        phantom.for_each_row_u8(|row, pixels| {
+            assert_eq!(pixels.len(), nx as usize);
+            rows.push(row);
+            concatenated.extend_from_slice(pixels);
+        });
+        assert_eq!(rows, (0..ny).collect::<Vec<_>>());
+        assert_eq!(concatenated[..3], [0, 255, 0]);
+        assert_eq!(concatenated, phantom.clone().into_vec_u8());
+        assert_eq!(concatenated, phantom.into_vec_u8_lossy_report().0);
+    }
+
+    #[test]
+    fn test_phantom_into_vec_u8_dithered() {
+        // Smooth ramp from 10 to 20 along x, which only covers 11 levels of `u8`
+        let (nx, ny) = (256, 8);
+        let value = |col: usize| 10.0 + 10.0 * col as f64 / f64::from(nx);
+        let data = (0..ny)
+            .flat_map(|_| (0..nx as usize).map(value))
+            .collect::<Vec<_>>();
+        let dithered = Phantom::from_data(nx, ny, data)
+            .unwrap()
+            .into_vec_u8_dithered(0.0, 255.0);
+        assert_eq!(dithered.len(), (nx * ny) as usize);
+        // The mean of blocks of 4x4 pixels approximates the mean of the original values, whereas
+        // rounding would result in errors of up to 0.5
+        for block_row in 0..(ny / 4) as usize {
+            for block_col in 0..(nx / 4) as usize {
+                let mut sum = 0.0;
+                let mut expected = 0.0;
+                for row in 4 * block_row..4 * block_row + 4 {
+                    for col in 4 * block_col..4 * block_col + 4 {
+                        sum += f64::from(dithered[row * nx as usize + col]);
+                        expected += value(col);
+                    }
+                }
+                assert_abs_diff_eq!(sum / 16.0, expected / 16.0, epsilon = 0.1);
+            }
+        }
+
+        // Exact levels are not changed, values outside of the range are clamped
+        let phantom = Phantom::from_data(3, 2, vec![0.0, 0.6, 1.0, -1.0, 2.0, f64::NAN]).unwrap();
+        assert_eq!(
+            phantom.into_vec_u8_dithered(0.0, 1.0),
+            [0, 153, 255, 0, 255, 0]
+        );
+        let phantom = Phantom::from_data(2, 1, vec![3.0, 7.0]).unwrap();
+        assert_eq!(phantom.into_vec_u8_dithered(0.0, 255.0), [3, 7]);
+
+        // Degenerate ranges map every pixel to zero
+        let phantom = Phantom::from_data(3, 1, vec![-1.0, 0.5, 2.0]).unwrap();
+        for (lo, hi) in [(0.5, 0.5), (1.0, 0.0), (f64::NAN, 1.0), (0.0, f64::NAN)] {
+            assert_eq!(phantom.clone().into_vec_u8_dithered(lo, hi), [0, 0, 0]);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_phantom_parallel_bit_identical() {
+        use super::{phantom_parallel, phantom_serial};
+
+        // Intensities of different magnitudes, such that the order of accumulation matters
+        let mut shapes = crate::shepplogan::Preset::Modified.shapes().to_vec();
+        shapes.extend((0..20).map(|i| {
+            let t = f64::from(i) / 20.0;
+            Shape::ellipse(t - 0.5, 0.3 - t, 0.4, 0.2, 30.0 * t, 1e-16 * (1.0 + t))
+        }));
+        shapes.push(Shape::rectangle(0.1, 0.0, 0.8, 0.5, 10.0, 0.3));
+        shapes.push(Shape::lune(0.0, 0.0, 0.6, 0.2, 0.0, 0.5, -0.7));
+        for options in [RenderOptions::new(), RenderOptions::new().floor(0.05)] {
+            for (nx, ny) in [(0, 0), (1, 1), (97, 64), (128, 255)] {
+                let canvas = options.canvas(nx, ny);
+                let shapes = shapes
+                    .iter()
+                    .map(|shape| shape.on_canvas_with(&canvas))
+                    .collect::<Vec<_>>();
+                let serial: Vec<f64> = phantom_serial(&shapes, nx, ny, &options);
+                let parallel: Vec<f64> = phantom_parallel(&shapes, nx, ny, &options);
+                assert_eq!(serial.len(), parallel.len());
+                assert!(serial
+                    .iter()
+                    .zip(parallel.iter())
+                    .all(|(a, b)| a.to_ne_bytes() == b.to_ne_bytes()));
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_phantom_supersampled_parallel_bit_identical() {
+        use super::{phantom_supersampled_parallel, phantom_supersampled_serial};
+
+        // Intensities of different magnitudes, such that the order of accumulation matters
+        let mut shapes = crate::shepplogan::Preset::Modified.shapes().to_vec();
+        shapes.extend((0..20).map(|i| {
+            let t = f64::from(i) / 20.0;
+            Shape::ellipse(t - 0.5, 0.3 - t, 0.4, 0.2, 30.0 * t, 1e-16 * (1.0 + t))
+        }));
+        shapes.push(Shape::rectangle(0.1, 0.0, 0.8, 0.5, 10.0, 0.3));
+        shapes.push(Shape::lune(0.0, 0.0, 0.6, 0.2, 0.0, 0.5, -0.7));
+        for options in [RenderOptions::new(), RenderOptions::new().floor(0.05)] {
+            for (nx, ny) in [(0, 0), (1, 1), (97, 64), (128, 255)] {
+                for samples in [2, 4] {
+                    let canvas = options.canvas(nx, ny).supersample(samples).unwrap();
+                    let shapes = shapes
+                        .iter()
+                        .map(|shape| shape.on_canvas_with(&canvas))
+                        .collect::<Vec<_>>();
+                    let serial = phantom_supersampled_serial(&shapes, nx, ny, &options, samples);
+                    let parallel =
+                        phantom_supersampled_parallel(&shapes, nx, ny, &options, samples);
+                    assert_eq!(serial.len(), parallel.len());
+                    assert!(serial
+                        .iter()
+                        .zip(parallel.iter())
+                        .all(|(a, b)| a.to_ne_bytes() == b.to_ne_bytes()));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn test_into_vec_f16() {
+        let phantom = shepplogan(64, 48).scale(1000.0);
+        let data = phantom.clone().into_vec();
+        let data_f16 = phantom.into_vec_f16();
+        assert_eq!(data_f16.len(), 64 * 48);
+        for (x, x_f16) in data.iter().zip(data_f16.iter()) {
+            assert_abs_diff_eq!(
+                x_f16.to_f64(),
+                *x,
+                epsilon = x.abs() * f64::from(half::f16::EPSILON)
+            );
+        }
+        let large = Phantom::from_data(1, 1, vec![1e6]).unwrap().into_vec_f16();
+        assert!(large[0].is_infinite());
+    }
+
+    #[test]
+    fn test_invert() {
+        let mut phantom = shepplogan(64, 48).scale(3.0);
+        let (min, max) = phantom.extrema();
+        let (x_max, y_max, _) = phantom.argmax().unwrap();
+        let (x_min, y_min, _) = phantom.argmin().unwrap();
+
+        let mut inverted = phantom.clone().invert();
+        assert_eq!(inverted.extrema(), (min, max));
+        assert_eq!(inverted.extrema(), inverted.compute_extrema());
+        assert_eq!(inverted.data[inverted.index(x_max, y_max)], min);
+        assert_eq!(inverted.data[inverted.index(x_min, y_min)], max);
+
+        let twice = inverted.invert();
+        for (a, b) in twice.data.iter().zip(phantom.data.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-12);
+        }
+
+        let mut inverted = phantom.clone().invert_range(0.0, 10.0);
+        assert_eq!(inverted.extrema(), (10.0 - max, 10.0 - min));
+        assert_eq!(inverted.extrema(), inverted.compute_extrema());
+
+        assert!(Phantom::new(0, 0, &[]).invert().data.is_empty());
+    }
+
+    #[test]
+    fn test_new_physical() {
+        let shapes = crate::shepplogan::Preset::Original.shapes();
+        for (fov, spacing) in [
+            ((250.0, 250.0), (0.5, 0.5)),
+            ((300.0, 200.0), (0.7, 1.1)),
+            ((100.0, 10.0), (3.0, 0.3)),
+        ] {
+            let phantom = Phantom::new_physical(fov, spacing, &shapes).unwrap();
+            let nx = (fov.0 / spacing.0).round() as u32;
+            let ny = (fov.1 / spacing.1).round() as u32;
+            assert_eq!((phantom.nx, phantom.ny), (nx, ny));
+            assert_eq!(phantom.spacing(), Some(spacing));
+            assert_eq!(phantom.data, Phantom::new(nx, ny, &shapes).data);
+        }
+        assert_eq!(
+            Phantom::new_physical((300.0, 200.0), (0.7, 1.1), &shapes)
+                .unwrap()
+                .nx,
+            429
+        );
+
+        for spacing in [
+            (0.0, 1.0),
+            (1.0, -0.5),
+            (f64::NAN, 1.0),
+            (1.0, f64::INFINITY),
+        ] {
+            assert!(matches!(
+                Phantom::new_physical((100.0, 100.0), spacing, &shapes),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        for fov in [(-1.0, 100.0), (100.0, f64::NAN), (f64::INFINITY, 100.0)] {
+            assert!(matches!(
+                Phantom::new_physical(fov, (1.0, 1.0), &shapes),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        // Too many pixels along one axis or in total
+        assert!(matches!(
+            Phantom::new_physical((1e10, 1.0), (1e-3, 1.0), &shapes),
+            Err(Error::DimensionOverflow)
+        ));
+        assert!(matches!(
+            Phantom::new_physical((1e5, 1e5), (1.0, 1.0), &shapes),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_overlap() {
+        let shapes = [
+            Shape::rectangle(-0.25, 0.0, 1.0, 1.0, 0.0, 0.8),
+            Shape::rectangle(0.25, 0.0, 1.0, 1.0, 0.0, 0.2),
+        ];
+        // Pixels covered by the first shape only, both shapes and the second shape only
+        let (nx, ny) = (64, 64);
+        let pixels = [(20, 32), (32, 32), (44, 32)];
+        let render = |overlap| {
+            let options = RenderOptions::new().overlap(overlap);
+            let phantom = Phantom::new_with_options(nx, ny, &shapes, &options);
+            pixels.map(|(x, y)| phantom.data[phantom.index(x, y)])
+        };
+        assert_eq!(render(Overlap::Add), [0.8, 0.8 + 0.2, 0.2]);
+        assert_eq!(render(Overlap::Replace), [0.8, 0.2, 0.2]);
+        assert_eq!(render(Overlap::Max), [0.8, 0.8, 0.2]);
+        assert_eq!(render(Overlap::MaxAbs), [0.8, 0.8, 0.2]);
+
+        // Negative intensities as in the inner ellipses of the Shepp-Logan phantom
+        let negative = [
+            Shape::rectangle(-0.25, 0.0, 1.0, 1.0, 0.0, 0.2),
+            Shape::rectangle(0.25, 0.0, 1.0, 1.0, 0.0, -0.8),
+        ];
+        let render_negative = |overlap| {
+            let options = RenderOptions::new().overlap(overlap);
+            let phantom = Phantom::new_with_options(nx, ny, &negative, &options);
+            pixels.map(|(x, y)| phantom.data[phantom.index(x, y)])
+        };
+        assert_eq!(render_negative(Overlap::Max), [0.2, 0.2, 0.0]);
+        assert_eq!(render_negative(Overlap::MaxAbs), [0.2, -0.8, -0.8]);
+        // Equal magnitudes keep the value of the earlier shape
+        let tie = [
+            Shape::rectangle(-0.25, 0.0, 1.0, 1.0, 0.0, -0.5),
+            Shape::rectangle(0.25, 0.0, 1.0, 1.0, 0.0, 0.5),
+        ];
+        let options = RenderOptions::new().overlap(Overlap::MaxAbs);
+        let phantom = Phantom::new_with_options(nx, ny, &tie, &options);
+        assert_eq!(
+            pixels.map(|(x, y)| phantom.data[phantom.index(x, y)]),
+            [-0.5, -0.5, 0.5]
+        );
+
+        // Standard `over` compositing onto a background of zero
+        let alpha = 0.25;
+        let first = alpha * 0.8;
+        let over = alpha * 0.2 + (1.0 - alpha) * first;
+        assert_eq!(
+            render(Overlap::AlphaOver { alpha }),
+            [first, over, alpha * 0.2]
+        );
+        assert_eq!(
+            render(Overlap::AlphaOver { alpha: 1.0 }),
+            render(Overlap::Replace)
+        );
+
+        // Transmission through both shapes, the background is fully transmissive
+        let halves = [
+            Shape::rectangle(-0.25, 0.0, 1.0, 1.0, 0.0, 0.5),
+            Shape::rectangle(0.25, 0.0, 1.0, 1.0, 0.0, 0.5),
+        ];
+        let options = RenderOptions::new().overlap(Overlap::Multiply);
+        let phantom = Phantom::new_with_options(nx, ny, &halves, &options);
+        assert_eq!(
+            pixels.map(|(x, y)| phantom.data[phantom.index(x, y)]),
+            [0.5, 0.25, 0.5]
+        );
+        assert_eq!(phantom.data[phantom.index(0, 0)], 1.0);
+
+        assert_eq!(
+            Phantom::new_with_options(nx, ny, &shapes, &RenderOptions::new()).data,
+            Phantom::new(nx, ny, &shapes).data
+        );
+    }
+
+    #[test]
+    fn test_empty_bounding_boxes() {
+        // Shapes outside of the canvas or with non-finite parameters have inverted bounding boxes
+        let shapes = [
+            Shape::ellipse(5.0, 5.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(f64::NAN, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::rectangle(-5.0, 0.0, 0.5, 0.5, 30.0, 1.0),
+            Shape::rectangle(0.0, 0.0, f64::NAN, 0.5, 30.0, 1.0),
+            Shape::rectangle(0.0, 3.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::lune(0.0, -4.0, 0.5, 0.0, -4.0, 0.2, 1.0),
+            Shape::group(vec![Shape::ellipse(2.0, 0.0, 0.5, 0.5, 0.0, 0.0)], 1.0),
+        ];
+        for (nx, ny) in [(16, 16), (16, 0), (0, 16)] {
+            let on_canvas = shapes
+                .iter()
+                .map(|shape| shape.on_canvas(nx, ny))
+                .collect::<Vec<_>>();
+            for shape in on_canvas.iter() {
+                let bbox = shape.bounding_box();
+                assert!(bbox.x_low > bbox.x_high || bbox.y_low > bbox.y_high);
+            }
+            let mut hits = 0;
+            rasterize(&on_canvas, nx, ny, |_, _, _| hits += 1);
+            assert_eq!(hits, 0);
+            let phantom = Phantom::new(nx, ny, &shapes);
+            assert!(phantom.data.iter().all(|&x| x == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_rasterize_row_order() {
+        let (nx, ny) = (97, 64);
+        let mut shapes = Preset::Modified.shapes().to_vec();
+        shapes.push(Shape::rectangle(0.3, -0.6, 0.5, 0.2, 25.0, -0.7));
+        shapes.push(Shape::rectangle(-0.5, 0.5, 0.4, 0.3, 0.0, 0.4));
+        shapes.push(Shape::lune(-0.2, 0.6, 0.3, 0.0, 0.7, 0.25, 0.9));
+        let on_canvas = shapes
+            .iter()
+            .map(|shape| shape.on_canvas(nx, ny))
+            .collect::<Vec<_>>();
+
+        // Reference: Visit the pixels of each shape column by column
+        let mut expected_hits = Vec::new();
+        for (shape_idx, shape) in on_canvas.iter().enumerate() {
+            let bbox = shape.bounding_box();
+            let mut hits = Vec::new();
+            for x in bbox.x_low..=bbox.x_high {
+                for y in bbox.y_low..=bbox.y_high {
+                    if shape.inside_pixel(x, y) {
+                        hits.push(((ny - y - 1) * nx + x) as usize);
+                    }
+                }
+            }
+            hits.sort_unstable();
+            expected_hits.push((shape_idx, hits));
+        }
+
+        // The same pixels are visited in increasing order of their index
+        let mut hits = vec![Vec::new(); shapes.len()];
+        rasterize(&on_canvas, nx, ny, |idx, shape_idx, _| {
+            hits[shape_idx].push(idx)
+        });
+        for (shape_idx, expected) in expected_hits.iter() {
+            assert!(!expected.is_empty());
+            assert_eq!(&hits[*shape_idx], expected);
+        }
+
+        // Therefore the rendered phantoms are identical for all overlap modes
+        for overlap in [
+            Overlap::Add,
+            Overlap::Replace,
+            Overlap::Max,
+            Overlap::MaxAbs,
+            Overlap::Multiply,
+        ] {
+            let options = RenderOptions::new().overlap(overlap).floor(-0.5);
+            let mut expected = vec![overlap.initial(); (nx * ny) as usize];
+            for (shape_idx, pixels) in expected_hits.iter() {
+                for &idx in pixels {
+                    super::accumulate(&mut expected[idx], &on_canvas[*shape_idx], &options);
+                }
+            }
+            let phantom = Phantom::new_with_options(nx, ny, &shapes, &options);
+            assert_eq!(
+                phantom.data.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+                expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_90() {
+        let phantom = shepplogan(64, 48).with_spacing(1.0, 2.0);
+        let rotated = phantom.rotate_90();
+        assert_eq!((rotated.nx, rotated.ny), (48, 64));
+        assert_eq!(rotated.spacing(), Some((2.0, 1.0)));
+        for x in 0..64 {
+            for y in 0..48 {
+                assert_eq!(
+                    rotated.data[rotated.index(47 - y, x)].to_ne_bytes(),
+                    phantom.data[phantom.index(x, y)].to_ne_bytes()
+                );
+            }
+        }
+        // The right pixel of a row ends up at the top of a column
+        let phantom = Phantom::from_data(2, 1, vec![1.0, 2.0]).unwrap();
+        assert_eq!(phantom.rotate_90().data, [2.0, 1.0]);
+
+        let rotated = phantom.rotate_90().rotate_90().rotate_90().rotate_90();
+        assert_eq!(rotated.data, phantom.data);
+    }
+
+    #[test]
+    fn test_new_non_empty() {
+        // `new` is permissive
+        assert!(Phantom::new(8, 8, &[]).data.iter().all(|&x| x == 0.0));
+        assert!(matches!(
+            Phantom::new_non_empty(8, 8, &[]),
+            Err(Error::NoShapes)
+        ));
+
+        let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        assert_eq!(
+            Phantom::new_non_empty(8, 8, &shapes).unwrap().data,
+            Phantom::new(8, 8, &shapes).data
+        );
+    }
+
+    #[test]
+    fn test_render_incremental() {
+        let shapes = Preset::Original.shapes();
+        let (nx, ny) = (96, 80);
+        let steps = Phantom::render_incremental(nx, ny, &shapes);
+        assert_eq!(steps.len(), shapes.len());
+        assert_eq!(
+            steps.last().unwrap().data,
+            Phantom::new(nx, ny, &shapes).data
+        );
+        assert!(steps[0].data.iter().any(|&x| x != 0.0));
+
+        let empty = Phantom::new(nx, ny, &[]);
+        for (i, shape) in shapes.iter().enumerate() {
+            let previous = if i == 0 { &empty } else { &steps[i - 1] };
+            let bbox = shape.on_canvas(nx, ny).bounding_box();
+            for x in 0..nx {
+                for y in 0..ny {
+                    let idx = previous.index(x, y);
+                    if !bbox.contains(x, y) {
+                        assert_eq!(
+                            steps[i].data[idx].to_ne_bytes(),
+                            previous.data[idx].to_ne_bytes()
+                        );
+                    }
+                }
+            }
+        }
+        assert!(Phantom::render_incremental(nx, ny, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_shape_order() {
+        // Overlapping shapes of different sizes spread over the canvas
+        let shapes = (0..40)
+            .map(|i| {
+                let t = f64::from(i);
+                let (x, y) = ((t * 0.37).sin() * 0.7, (t * 0.91).cos() * 0.7);
+                let r = 0.05 + 0.02 * (t % 7.0);
+                Shape::ellipse(x, y, r, 1.5 * r, 10.0 * t, 0.1 + 0.01 * t)
+            })
+            .collect::<Vec<_>>();
+        let (nx, ny) = (96, 64);
+        let render = |options: RenderOptions| Phantom::new_with_options(nx, ny, &shapes, &options);
+
+        let reference = render(RenderOptions::new());
+        for order in [ShapeOrder::BySizeAscending, ShapeOrder::Morton] {
+            let reordered = render(RenderOptions::new().order(order));
+            for (a, b) in reordered.data.iter().zip(reference.data.iter()) {
+                assert_abs_diff_eq!(a, b, epsilon = 1e-12);
+            }
+            let options = RenderOptions::new().overlap(Overlap::Max);
+            assert_eq!(
+                render(options.clone().order(order)).data,
+                render(options).data
+            );
+            // The order of the shapes matters, therefore it is not changed
+            for overlap in [Overlap::Replace, Overlap::MaxAbs] {
+                let options = RenderOptions::new().overlap(overlap);
+                assert_eq!(
+                    render(options.clone().order(order)).data,
+                    render(options).data
+                );
+            }
+            let options = RenderOptions::new().floor(0.15);
+            assert_eq!(
+                render(options.clone().order(order)).data,
+                render(options).data
+            );
+        }
+    }
+
+    #[test]
+    fn test_into_vec_col_major() {
+        let phantom = shepplogan(48, 32);
+        let col_major = phantom.clone().into_vec_col_major();
+        assert_eq!(col_major.len(), phantom.data.len());
+        for x in 0..48 {
+            for y in 0..32 {
+                // Rows are counted from the top
+                let (col, row) = (x as usize, (32 - y - 1) as usize);
+                assert_eq!(
+                    col_major[col * 32 + row].to_ne_bytes(),
+                    phantom.data[phantom.index(x, y)].to_ne_bytes()
+                );
+            }
+        }
+        assert!(Phantom::new(0, 5, &[]).into_vec_col_major().is_empty());
+    }
+}
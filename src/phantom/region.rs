@@ -0,0 +1,470 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{check_dimensions, BorderMode, Phantom};
+use crate::{shape::BoundingBox, Error, Shape};
+
+impl Phantom {
+    /// Returns the region of size `width` times `height` whose lower left pixel is `(x, y)`.
+    ///
+    /// As for the shapes, `(0, 0)` is the lower left pixel of the phantom. Returns
+    /// `Error::OutOfBounds` if the region does not lie entirely inside of the phantom. The pixel
+    /// spacing is preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let center = shepplogan(256, 256).crop(64, 64, 128, 128).unwrap();
+    /// assert!(shepplogan(256, 256).crop(200, 0, 128, 128).is_err());
+    /// ```
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Phantom, Error> {
+        let x_end = x.checked_add(width).ok_or(Error::OutOfBounds)?;
+        let y_end = y.checked_add(height).ok_or(Error::OutOfBounds)?;
+        if x_end > self.nx || y_end > self.ny {
+            return Err(Error::OutOfBounds);
+        }
+        let mut data = Vec::with_capacity(width as usize * height as usize);
+        // Rows are stored from top to bottom
+        for row in (y..y_end).rev() {
+            let start = self.index(x, row);
+            data.extend_from_slice(&self.data[start..start + width as usize]);
+        }
+        let mut cropped = Phantom::from_raw(width, height, data);
+        cropped.spacing = self.spacing;
+        Ok(cropped)
+    }
+
+    /// Pads the phantom with `left`, `right`, `bottom` and `top` pixels of the given `value`.
+    ///
+    /// Pixel `(x, y)` of the original phantom ends up at `(x + left, y + bottom)`. Returns
+    /// `Error::DimensionOverflow` if the number of pixels of the padded phantom does not fit into
+    /// a `u32`. The pixel spacing is preserved.
+    pub fn pad(
+        &self,
+        left: u32,
+        right: u32,
+        bottom: u32,
+        top: u32,
+        value: f64,
+    ) -> Result<Phantom, Error> {
+        let nx = self
+            .nx
+            .checked_add(left)
+            .and_then(|n| n.checked_add(right))
+            .ok_or(Error::DimensionOverflow)?;
+        let ny = self
+            .ny
+            .checked_add(bottom)
+            .and_then(|n| n.checked_add(top))
+            .ok_or(Error::DimensionOverflow)?;
+        let mut padded = Phantom::from_raw(nx, ny, vec![value; check_dimensions(nx, ny)?]);
+        for y in 0..self.ny {
+            let src = self.index(0, y);
+            let dst = padded.index(left, y + bottom);
+            padded.data[dst..dst + self.nx as usize]
+                .copy_from_slice(&self.data[src..src + self.nx as usize]);
+        }
+        padded.spacing = self.spacing;
+        Ok(padded)
+    }
+
+    /// Extends the phantom by `left`, `right`, `bottom` and `top` pixels according to `border`.
+    ///
+    /// In contrast to `pad`, which fills the margins with a constant value, the margins are filled
+    /// by reflecting, replicating or periodically repeating the phantom (see `BorderMode`). As for
+    /// the shapes, `(0, 0)` is the lower left pixel, therefore `bottom` refers to the last rows of
+    /// `into_vec`. Pixel `(x, y)` of the original phantom ends up at `(x + left, y + bottom)`. The
+    /// margins of an empty phantom are zero for all border modes. Returns
+    /// `Error::DimensionOverflow` if the number of pixels of the extended phantom does not fit
+    /// into a `u32`. The pixel spacing is preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, BorderMode};
+    /// let extended = shepplogan(64, 64)
+    ///     .extend(8, 8, 8, 8, BorderMode::Reflect)
+    ///     .unwrap();
+    /// assert_eq!(extended.into_vec().len(), 80 * 80);
+    /// ```
+    pub fn extend(
+        &self,
+        left: u32,
+        right: u32,
+        bottom: u32,
+        top: u32,
+        border: BorderMode,
+    ) -> Result<Phantom, Error> {
+        let mut extended = self.pad(left, right, bottom, top, 0.0)?;
+        if self.data.is_empty() {
+            return Ok(extended);
+        }
+        let (nx, ny) = (i64::from(self.nx), i64::from(self.ny));
+        for y in 0..extended.ny {
+            let Some(src_y) = border.index(i64::from(y) - i64::from(bottom), ny) else {
+                continue;
+            };
+            for x in 0..extended.nx {
+                let Some(src_x) = border.index(i64::from(x) - i64::from(left), nx) else {
+                    continue;
+                };
+                let idx = extended.index(x, y);
+                extended.data[idx] = self.data[self.index(src_x as u32, src_y as u32)];
+            }
+        }
+        Ok(extended)
+    }
+
+    /// Pads the smaller dimension of the phantom with `fill` to make it square.
+    ///
+    /// The padding is split evenly between both sides, which keeps the content centered. If the
+    /// difference of the dimensions is odd, the additional pixel is added on the right or the top.
+    /// Returns `Error::DimensionOverflow` if the number of pixels of the square phantom does not
+    /// fit into a `u32`. The pixel spacing is preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let square = shepplogan(256, 320).pad_to_square(0.0).unwrap();
+    /// assert_eq!(square.into_vec().len(), 320 * 320);
+    /// ```
+    pub fn pad_to_square(&self, fill: f64) -> Result<Phantom, Error> {
+        let n = self.nx.max(self.ny);
+        let (dx, dy) = (n - self.nx, n - self.ny);
+        self.pad(dx / 2, dx - dx / 2, dy / 2, dy - dy / 2, fill)
+    }
+
+    /// Splits the phantom into square patches of `patch` times `patch` pixels with the given
+    /// `stride`.
+    ///
+    /// The patches are anchored at the upper left corner of the phantom and returned row by row
+    /// starting at the top, which is the same order as the pixels in `into_vec`. Each patch is
+    /// upright, i.e. it is a `crop` of the phantom. Patches overlap if `stride < patch`. If the
+    /// patches do not fit exactly, the phantom is padded with zeros on the right and at the bottom
+    /// such that the last patches of each row and column cover the remaining pixels. A phantom
+    /// with `nx` pixels along `x` therefore results in `ceil((nx - patch) / stride) + 1` patches
+    /// along `x` (one patch if `nx <= patch`). Empty phantoms result in no patches. The pixel
+    /// spacing is preserved.
+    ///
+    /// Returns `Error::InvalidParameter` if `patch` or `stride` are zero and
+    /// `Error::DimensionOverflow` if the number of pixels of the padded phantom does not fit into
+    /// a `u32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let patches = shepplogan(256, 256).patches(64, 32).unwrap();
+    /// assert_eq!(patches.len(), 7 * 7);
+    /// ```
+    pub fn patches(&self, patch: u32, stride: u32) -> Result<Vec<Phantom>, Error> {
+        if patch == 0 || stride == 0 {
+            return Err(Error::InvalidParameter(
+                "patch size and stride must be positive",
+            ));
+        }
+        if self.data.is_empty() {
+            return Ok(Vec::new());
+        }
+        let count = |n: u32| (n.saturating_sub(patch)).div_ceil(stride) + 1;
+        let (cx, cy) = (count(self.nx), count(self.ny));
+        let extent = |count: u32| {
+            (count - 1)
+                .checked_mul(stride)
+                .and_then(|n| n.checked_add(patch))
+                .ok_or(Error::DimensionOverflow)
+        };
+        let (width, height) = (extent(cx)?, extent(cy)?);
+        let padded = self.pad(0, width - self.nx, height - self.ny, 0, 0.0)?;
+        let mut patches = Vec::with_capacity(cx as usize * cy as usize);
+        for row in 0..cy {
+            for col in 0..cx {
+                let y = height - row * stride - patch;
+                patches.push(
+                    padded
+                        .crop(col * stride, y, patch, patch)
+                        .expect("patch is inside of the padded phantom"),
+                );
+            }
+        }
+        Ok(patches)
+    }
+
+    /// Iterates over the pixels of the phantom which are covered by `shape`.
+    ///
+    /// Yields `(x, y, value)`, where `(0, 0)` is the lower left pixel of the phantom. The pixels
+    /// are yielded row by row starting at the top, which is the same order as in `into_vec`. Only
+    /// the pixels inside of the bounding box of the shape are tested, which makes this efficient
+    /// for processing a region of interest of a large phantom. The shape is placed onto the phantom
+    /// in the same way as in `Phantom::new`, therefore this is consistent with `Shape::contains`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, Shape};
+    /// let phantom = shepplogan(128, 128);
+    /// let roi = Shape::ellipse(0.0, 0.0, 0.1, 0.1, 0.0, 0.0);
+    /// let (sum, count) = phantom
+    ///     .pixels_in_shape(&roi)
+    ///     .fold((0.0, 0), |(sum, count), (_, _, v)| (sum + v, count + 1));
+    /// let mean = sum / f64::from(count);
+    /// ```
+    pub fn pixels_in_shape<'a>(
+        &'a self,
+        shape: &Shape,
+    ) -> impl Iterator<Item = (u32, u32, f64)> + 'a {
+        let shape = shape.on_canvas(self.nx, self.ny);
+        // Bounding boxes cannot represent an empty canvas
+        let bbox = if self.data.is_empty() {
+            BoundingBox::empty()
+        } else {
+            shape.bounding_box()
+        };
+        (bbox.y_low..=bbox.y_high)
+            .rev()
+            .flat_map(move |y| (bbox.x_low..=bbox.x_high).map(move |x| (x, y)))
+            .filter(move |&(x, y)| shape.inside_pixel(x, y))
+            .map(move |(x, y)| (x, y, self.data[self.index(x, y)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BorderMode, Error, Phantom, Shape};
+
+    /// Phantom whose pixel `(x, y)` has the value `10 * x + y`
+    fn ramp(nx: u32, ny: u32) -> Phantom {
+        let mut phantom = Phantom::from_raw(nx, ny, vec![0.0; (nx * ny) as usize]);
+        for x in 0..nx {
+            for y in 0..ny {
+                let idx = phantom.index(x, y);
+                phantom.data[idx] = f64::from(10 * x + y);
+            }
+        }
+        phantom
+    }
+
+    #[test]
+    fn test_crop() {
+        let phantom = ramp(6, 5).with_spacing(0.5, 2.0);
+        let cropped = phantom.crop(1, 2, 3, 2).unwrap();
+        assert_eq!((cropped.nx, cropped.ny), (3, 2));
+        assert_eq!(cropped.spacing(), Some((0.5, 2.0)));
+        for x in 0..3 {
+            for y in 0..2 {
+                assert_eq!(
+                    cropped.data[cropped.index(x, y)].to_ne_bytes(),
+                    f64::from(10 * (x + 1) + y + 2).to_ne_bytes()
+                );
+            }
+        }
+
+        assert_eq!(phantom.crop(0, 0, 6, 5).unwrap().data, phantom.data);
+        assert!(phantom.crop(6, 5, 0, 0).unwrap().data.is_empty());
+        assert!(matches!(phantom.crop(4, 0, 3, 1), Err(Error::OutOfBounds)));
+        assert!(matches!(phantom.crop(0, 1, 1, 5), Err(Error::OutOfBounds)));
+        assert!(matches!(
+            phantom.crop(1, 0, u32::MAX, 1),
+            Err(Error::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn test_pad() {
+        let phantom = ramp(3, 2);
+        let padded = phantom.pad(1, 2, 3, 4, -1.0).unwrap();
+        assert_eq!((padded.nx, padded.ny), (6, 9));
+        for x in 0..6 {
+            for y in 0..9 {
+                let expected = if (1..4).contains(&x) && (3..5).contains(&y) {
+                    f64::from(10 * (x - 1) + y - 3)
+                } else {
+                    -1.0
+                };
+                assert_eq!(
+                    padded.data[padded.index(x, y)].to_ne_bytes(),
+                    expected.to_ne_bytes()
+                );
+            }
+        }
+        assert_eq!(padded.crop(1, 3, 3, 2).unwrap().data, phantom.data);
+
+        assert!(matches!(
+            phantom.pad(u32::MAX, 0, 0, 0, 0.0),
+            Err(Error::DimensionOverflow)
+        ));
+        assert!(matches!(
+            phantom.pad(1 << 16, 0, 1 << 16, 0, 0.0),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_extend() {
+        // Values of the pixels in the bottom row (y = 0) and the left column (x = 0)
+        let phantom = ramp(4, 3).with_spacing(0.5, 2.0);
+        let row = |extended: &Phantom, y: u32| {
+            (0..extended.nx)
+                .map(|x| extended.data[extended.index(x, y)])
+                .collect::<Vec<_>>()
+        };
+        let column = |extended: &Phantom, x: u32| {
+            (0..extended.ny)
+                .map(|y| extended.data[extended.index(x, y)])
+                .collect::<Vec<_>>()
+        };
+
+        let extended = phantom.extend(2, 1, 1, 2, BorderMode::Zero).unwrap();
+        assert_eq!((extended.nx, extended.ny), (7, 6));
+        assert_eq!(extended.spacing(), Some((0.5, 2.0)));
+        assert_eq!(extended.crop(2, 1, 4, 3).unwrap().data, phantom.data);
+        assert_eq!(row(&extended, 1), [0.0, 0.0, 0.0, 10.0, 20.0, 30.0, 0.0]);
+        assert_eq!(column(&extended, 2), [0.0, 0.0, 1.0, 2.0, 0.0, 0.0]);
+
+        let extended = phantom.extend(2, 1, 1, 2, BorderMode::Reflect).unwrap();
+        assert_eq!(extended.crop(2, 1, 4, 3).unwrap().data, phantom.data);
+        assert_eq!(row(&extended, 1), [10.0, 0.0, 0.0, 10.0, 20.0, 30.0, 30.0]);
+        assert_eq!(column(&extended, 2), [0.0, 0.0, 1.0, 2.0, 2.0, 1.0]);
+
+        let extended = phantom.extend(2, 1, 1, 2, BorderMode::Replicate).unwrap();
+        assert_eq!(extended.crop(2, 1, 4, 3).unwrap().data, phantom.data);
+        assert_eq!(row(&extended, 1), [0.0, 0.0, 0.0, 10.0, 20.0, 30.0, 30.0]);
+        assert_eq!(column(&extended, 2), [0.0, 0.0, 1.0, 2.0, 2.0, 2.0]);
+        // Corners replicate the corner pixels
+        assert_eq!(row(&extended, 5)[..3], [2.0, 2.0, 2.0]);
+
+        let extended = phantom.extend(2, 1, 1, 2, BorderMode::Wrap).unwrap();
+        assert_eq!(extended.crop(2, 1, 4, 3).unwrap().data, phantom.data);
+        assert_eq!(row(&extended, 1), [20.0, 30.0, 0.0, 10.0, 20.0, 30.0, 0.0]);
+        assert_eq!(column(&extended, 2), [2.0, 0.0, 1.0, 2.0, 0.0, 1.0]);
+
+        // Margins larger than the phantom
+        let extended = ramp(2, 1).extend(5, 0, 0, 0, BorderMode::Reflect).unwrap();
+        assert_eq!(row(&extended, 0), [0.0, 0.0, 10.0, 10.0, 0.0, 0.0, 10.0]);
+
+        let empty = Phantom::from_raw(0, 0, vec![]);
+        let extended = empty.extend(1, 1, 1, 1, BorderMode::Wrap).unwrap();
+        assert_eq!(extended.data, [0.0; 4]);
+        assert!(matches!(
+            phantom.extend(u32::MAX, 0, 0, 0, BorderMode::Wrap),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_pad_to_square() {
+        let phantom = ramp(256, 320);
+        let square = phantom.pad_to_square(-1.0).unwrap();
+        assert_eq!((square.nx, square.ny), (320, 320));
+        assert_eq!(square.crop(32, 0, 256, 320).unwrap().data, phantom.data);
+        for x in (0..32).chain(288..320) {
+            for y in 0..320 {
+                assert_eq!(square.data[square.index(x, y)], -1.0);
+            }
+        }
+
+        // The additional pixel of an odd difference ends up at the top
+        let square = ramp(5, 2).pad_to_square(-1.0).unwrap();
+        assert_eq!((square.nx, square.ny), (5, 5));
+        assert_eq!(square.crop(0, 1, 5, 2).unwrap().data, ramp(5, 2).data);
+        assert_eq!(square.data[square.index(0, 0)], -1.0);
+        assert_eq!(square.data[square.index(0, 4)], -1.0);
+        assert_eq!(square.data[square.index(0, 3)], -1.0);
+
+        assert_eq!(ramp(4, 4).pad_to_square(0.0).unwrap().data, ramp(4, 4).data);
+        assert!(matches!(
+            Phantom::from_raw(1 << 16, 1, vec![0.0; 1 << 16]).pad_to_square(0.0),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_patches() {
+        // 2 x 3 patches of 3 x 3 pixels without overlap, which require one column and two rows
+        // of padding
+        let phantom = ramp(5, 7).with_spacing(0.5, 2.0);
+        let patches = phantom.patches(3, 3).unwrap();
+        assert_eq!(patches.len(), 2 * 3);
+        let padded = phantom.pad(0, 1, 2, 0, 0.0).unwrap();
+        for (i, patch) in patches.iter().enumerate() {
+            let (col, row) = (i as u32 % 2, i as u32 / 2);
+            assert_eq!((patch.nx, patch.ny), (3, 3));
+            assert_eq!(patch.spacing(), Some((0.5, 2.0)));
+            assert_eq!(
+                patch.data,
+                padded.crop(3 * col, 6 - 3 * row, 3, 3).unwrap().data
+            );
+        }
+        // The first patch is the upper left corner of the phantom
+        assert_eq!(patches[0].data, phantom.crop(0, 4, 3, 3).unwrap().data);
+        // The patches cover every pixel exactly once
+        let sum = patches.iter().flat_map(|p| p.data.iter()).sum::<f64>();
+        assert_eq!(sum, phantom.data.iter().sum::<f64>());
+        assert_eq!(patches[5].data[8], 0.0);
+
+        // Overlapping patches
+        let patches = ramp(8, 6).patches(4, 2).unwrap();
+        assert_eq!(patches.len(), 3 * 2);
+        assert_eq!(patches[1].data, ramp(8, 6).crop(2, 2, 4, 4).unwrap().data);
+        assert_eq!(patches[1].data[..2], patches[0].data[2..4]);
+
+        // Patches which are larger than the phantom
+        let patches = ramp(2, 3).patches(4, 1).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].crop(0, 1, 2, 3).unwrap().data, ramp(2, 3).data);
+        assert!(Phantom::from_raw(0, 0, vec![])
+            .patches(2, 2)
+            .unwrap()
+            .is_empty());
+
+        // Invalid parameters
+        let phantom = ramp(10, 10);
+        for (patch, stride) in [(0, 1), (3, 0), (0, 0)] {
+            assert!(matches!(
+                phantom.patches(patch, stride),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        assert!(matches!(
+            Phantom::from_raw(0, 0, vec![]).patches(0, 1),
+            Err(Error::InvalidParameter(_))
+        ));
+        // The padded phantom would be too large
+        assert!(matches!(
+            phantom.patches(5, u32::MAX),
+            Err(Error::DimensionOverflow)
+        ));
+        assert!(matches!(
+            phantom.patches(u32::MAX, 1),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_pixels_in_shape() {
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.5, 0.3, 20.0, 0.0),
+            Shape::rectangle(0.0, 0.0, 0.3, 0.4, 0.0, 0.0),
+            Shape::rectangle(-0.3, 0.2, 0.6, 0.4, 45.0, 0.0),
+            Shape::lune(0.0, 0.0, 0.6, 0.2, 0.0, 0.5, 0.0),
+            Shape::ellipse(3.0, 3.0, 0.5, 0.5, 0.0, 0.0),
+        ];
+        for (nx, ny) in [(20, 30), (31, 17), (0, 0)] {
+            let phantom = ramp(nx, ny);
+            for shape in shapes.iter() {
+                let pixels = phantom.pixels_in_shape(shape).collect::<Vec<_>>();
+                let expected = (0..ny)
+                    .rev()
+                    .flat_map(|y| (0..nx).map(move |x| (x, y)))
+                    .filter(|&(x, y)| shape.contains(nx, ny, x, y))
+                    .map(|(x, y)| (x, y, f64::from(10 * x + y)))
+                    .collect::<Vec<_>>();
+                assert_eq!(pixels, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::Phantom;
+
+impl Phantom {
+    /// Returns a downsampled preview of the phantom whose larger dimension is `max_dim`.
+    ///
+    /// The aspect ratio is preserved as far as possible, the smaller dimension is rounded to the
+    /// nearest number of pixels (but at least one pixel). Each pixel of the thumbnail is the mean
+    /// of the block of pixels of the phantom which it covers. If the phantom already fits into
+    /// `max_dim`, it is returned unchanged. The pixel spacing is adapted to the new dimensions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// let thumbnail = shepplogan(512, 256).thumbnail(64);
+    /// assert_eq!(thumbnail.into_vec().len(), 64 * 32);
+    /// ```
+    pub fn thumbnail(&self, max_dim: u32) -> Phantom {
+        let n_max = self.nx.max(self.ny);
+        if n_max <= max_dim {
+            return self.clone();
+        }
+        if max_dim == 0 {
+            return Phantom::from_raw(0, 0, Vec::new());
+        }
+        let shrink = |n: u32| -> u32 {
+            let scaled = (f64::from(n) * f64::from(max_dim) / f64::from(n_max)).round() as u32;
+            scaled.clamp(1, max_dim)
+        };
+        let (nx, ny) = (shrink(self.nx), shrink(self.ny));
+        // Pixels of the phantom covered by pixel `i` of a thumbnail with `t` pixels
+        let block = |i: u32, n: u32, t: u32| {
+            let bound = |i: u32| (u64::from(i) * u64::from(n) / u64::from(t)) as u32;
+            bound(i)..bound(i + 1)
+        };
+        let mut thumbnail = Phantom::from_raw(nx, ny, vec![0.0; nx as usize * ny as usize]);
+        for x in 0..nx {
+            for y in 0..ny {
+                let (xs, ys) = (block(x, self.nx, nx), block(y, self.ny, ny));
+                let count = f64::from((xs.end - xs.start) * (ys.end - ys.start));
+                let mut sum = 0.0;
+                for xx in xs {
+                    for yy in ys.clone() {
+                        sum += self.data[self.index(xx, yy)];
+                    }
+                }
+                let idx = thumbnail.index(x, y);
+                thumbnail.data[idx] = sum / count;
+            }
+        }
+        thumbnail.spacing = self.spacing.map(|(dx, dy)| {
+            (
+                dx * f64::from(self.nx) / f64::from(nx),
+                dy * f64::from(self.ny) / f64::from(ny),
+            )
+        });
+        thumbnail
+    }
+
+    /// Resamples the phantom onto a canvas of size `out_nx` times `out_ny` with the affine
+    /// transform `matrix`.
+    ///
+    /// The matrix `[a, b, c, d, e, f]` is the inverse mapping: Pixel `(x, y)` of the result is
+    /// sampled from the position `(a * x + b * y + c, d * x + e * y + f)` of the phantom. As for the
+    /// shapes, `(0, 0)` is the lower left pixel, both for the result and the phantom. Therefore
+    /// `[1, 0, tx, 0, 1, ty]` moves the content `tx` pixels to the left and `ty` pixels
+    /// downwards. To apply a forward transform, which maps positions of the phantom onto the
+    /// result, pass its inverse.
+    ///
+    /// The values are interpolated bilinearly from the four closest pixels. Pixels of the result
+    /// which are sampled from outside of the phantom are set to `fill`. Since the transform may
+    /// change the size of the pixels, the pixel spacing is not preserved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::shepplogan;
+    /// // Rotate by 30 degrees about the center
+    /// let (sin, cos) = 30f64.to_radians().sin_cos();
+    /// let c = 127.5;
+    /// let matrix = [cos, sin, c - c * cos - c * sin, -sin, cos, c + c * sin - c * cos];
+    /// let rotated = shepplogan(256, 256).warp_affine(matrix, 256, 256, 0.0);
+    /// ```
+    pub fn warp_affine(&self, matrix: [f64; 6], out_nx: u32, out_ny: u32, fill: f64) -> Phantom {
+        let [a, b, c, d, e, f] = matrix;
+        let (x_max, y_max) = (f64::from(self.nx) - 1.0, f64::from(self.ny) - 1.0);
+        let mut warped = Phantom::from_raw(
+            out_nx,
+            out_ny,
+            vec![fill; out_nx as usize * out_ny as usize],
+        );
+        for y in 0..out_ny {
+            for x in 0..out_nx {
+                let (xf, yf) = (f64::from(x), f64::from(y));
+                let (sx, sy) = (a * xf + b * yf + c, d * xf + e * yf + f);
+                // Also fails for `NaN` and an empty phantom
+                if !((0.0..=x_max).contains(&sx) && (0.0..=y_max).contains(&sy)) {
+                    continue;
+                }
+                let (x0, y0) = (sx.floor(), sy.floor());
+                let (wx, wy) = (sx - x0, sy - y0);
+                let (x0, y0) = (x0 as u32, y0 as u32);
+                let (x1, y1) = ((x0 + 1).min(self.nx - 1), (y0 + 1).min(self.ny - 1));
+                let value = |x: u32, y: u32| self.data[self.index(x, y)];
+                let bottom = value(x0, y0) * (1.0 - wx) + value(x1, y0) * wx;
+                let top = value(x0, y1) * (1.0 - wx) + value(x1, y1) * wx;
+                let idx = warped.index(x, y);
+                warped.data[idx] = bottom * (1.0 - wy) + top * wy;
+            }
+        }
+        warped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shepplogan, Phantom};
+
+    #[test]
+    fn test_thumbnail() {
+        let thumbnail = shepplogan(512, 640).with_spacing(1.0, 0.5).thumbnail(64);
+        assert_eq!((thumbnail.nx, thumbnail.ny), (51, 64));
+        assert_eq!(thumbnail.spacing(), Some((512.0 / 51.0, 5.0)));
+
+        let thumbnail = shepplogan(640, 512).thumbnail(64);
+        assert_eq!((thumbnail.nx, thumbnail.ny), (64, 51));
+
+        // Blocks of 2x2 pixels are averaged
+        let phantom =
+            Phantom::from_data(4, 2, vec![1.0, 3.0, 0.0, 0.0, 5.0, 7.0, 2.0, 6.0]).unwrap();
+        let thumbnail = phantom.thumbnail(2);
+        assert_eq!((thumbnail.nx, thumbnail.ny), (2, 1));
+        assert_eq!(thumbnail.data, vec![4.0, 2.0]);
+
+        // Small phantoms are not changed
+        let phantom = shepplogan(32, 16);
+        assert_eq!(phantom.thumbnail(32).data, phantom.data);
+        assert!(phantom.thumbnail(0).data.is_empty());
+        assert_eq!(shepplogan(100, 1).thumbnail(10).ny, 1);
+    }
+
+    #[test]
+    fn test_warp_affine() {
+        let phantom = shepplogan(64, 48).with_spacing(1.0, 1.0);
+        let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let warped = phantom.warp_affine(identity, 64, 48, -1.0);
+        assert_eq!(warped.data, phantom.data);
+        assert_eq!(warped.spacing(), None);
+
+        // Sampling from 5 pixels to the left and 3 pixels above moves the content to the right
+        // and downwards
+        let warped = phantom.warp_affine([1.0, 0.0, -5.0, 0.0, 1.0, 3.0], 64, 48, -1.0);
+        for x in 0..64 {
+            for y in 0..48 {
+                let expected = if x >= 5 && y < 45 {
+                    phantom.data[phantom.index(x - 5, y + 3)]
+                } else {
+                    -1.0
+                };
+                assert_eq!(
+                    warped.data[warped.index(x, y)].to_ne_bytes(),
+                    expected.to_ne_bytes()
+                );
+            }
+        }
+
+        // Bilinear interpolation halfway between pixels
+        let phantom = Phantom::from_data(2, 2, vec![2.0, 4.0, 0.0, 6.0]).unwrap();
+        let warped = phantom.warp_affine([0.5, 0.0, 0.0, 0.0, 0.5, 0.0], 3, 3, f64::NAN);
+        let value = |x: u32, y: u32| warped.data[warped.index(x, y)];
+        assert_eq!(value(0, 0), 0.0);
+        assert_eq!(value(1, 0), 3.0);
+        assert_eq!(value(0, 1), 1.0);
+        assert_eq!(value(1, 1), 3.0);
+        assert_eq!(value(2, 2), 4.0);
+        assert!(phantom
+            .warp_affine([2.0, 0.0, 0.0, 0.0, 2.0, 0.0], 2, 2, f64::NAN)
+            .data[1]
+            .is_nan());
+
+        // Empty phantoms and canvases
+        let empty = Phantom::from_raw(0, 0, Vec::new());
+        assert_eq!(empty.warp_affine(identity, 2, 1, 7.0).data, [7.0, 7.0]);
+        assert!(phantom.warp_affine(identity, 0, 5, 0.0).data.is_empty());
+    }
+}
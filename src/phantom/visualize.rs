@@ -0,0 +1,207 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::Phantom;
+use crate::{Error, Shape};
+
+impl Phantom {
+    /// Visualizes the difference between the phantom and a `reference` as an RGB image.
+    ///
+    /// Returns an RGB8 buffer with three bytes per pixel and the same layout as `into_vec`. The
+    /// `reference` is shown in grayscale (linearly mapped from its minimum and maximum onto
+    /// `[0, 255]`) and tinted red where the phantom is larger than the reference and blue where it
+    /// is smaller. The strength of the tint is proportional to the magnitude of the difference,
+    /// where the largest absolute difference is shown in pure red or blue. Identical phantoms
+    /// therefore result in a pure grayscale image.
+    ///
+    /// Returns `Error::DimensionMismatch` if the dimensions of the phantoms differ, including
+    /// transposed dimensions. The error only reports the number of pixels of both phantoms, which
+    /// are equal in the transposed case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{shepplogan, shepplogan_modified};
+    /// let overlay = shepplogan_modified(64, 64)
+    ///     .diff_overlay(&shepplogan(64, 64))
+    ///     .unwrap();
+    /// assert_eq!(overlay.len(), 3 * 64 * 64);
+    /// ```
+    pub fn diff_overlay(&self, reference: &Phantom) -> Result<Vec<u8>, Error> {
+        if (self.nx, self.ny) != (reference.nx, reference.ny) {
+            return Err(Error::DimensionMismatch {
+                expected: reference.data.len(),
+                actual: self.data.len(),
+            });
+        }
+        let (min, max) = reference.compute_extrema();
+        let range = max - min;
+        let max_diff = self
+            .data
+            .iter()
+            .zip(reference.data.iter())
+            .map(|(x, r)| (x - r).abs())
+            .fold(0.0, f64::max);
+
+        let mut rgb = Vec::with_capacity(3 * self.data.len());
+        for (&x, &r) in self.data.iter().zip(reference.data.iter()) {
+            let gray = if range > 0.0 {
+                (r - min) / range * 255.0
+            } else {
+                0.0
+            };
+            let diff = x - r;
+            let tint = if max_diff > 0.0 {
+                diff.abs() / max_diff
+            } else {
+                0.0
+            };
+            // Blend the gray value towards pure red or blue
+            let blend = |target: f64| (gray * (1.0 - tint) + target * tint).round() as u8;
+            let (red, blue) = if diff > 0.0 {
+                (255.0, 0.0)
+            } else {
+                (0.0, 255.0)
+            };
+            rgb.extend_from_slice(&[blend(red), blend(0.0), blend(blue)]);
+        }
+        Ok(rgb)
+    }
+
+    /// Draws the outlines of `shapes` on a phantom of size `nx` times `ny` as an RGB image.
+    ///
+    /// Returns an RGB8 buffer with three bytes per pixel and the same layout as `into_vec`. The
+    /// outlines are drawn in `color` with a line `width` in pixels over a black background. The
+    /// lines are anti-aliased: Each pixel is weighted by how far its distance to the closest
+    /// outline exceeds `width / 2`, fading out linearly over one pixel. The intensities of the
+    /// shapes are ignored. This is useful for verifying the placement of shapes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+    /// let outlines = Phantom::draw_outlines(&shapes, 64, 64, [255, 0, 0], 1.5);
+    /// assert_eq!(outlines.len(), 3 * 64 * 64);
+    /// ```
+    pub fn draw_outlines(
+        shapes: &[Shape],
+        nx: u32,
+        ny: u32,
+        color: [u8; 3],
+        width: f64,
+    ) -> Vec<u8> {
+        let shapes = shapes
+            .iter()
+            .map(|shape| shape.on_canvas(nx, ny))
+            .collect::<Vec<_>>();
+        let mut rgb = Vec::with_capacity(3 * nx as usize * ny as usize);
+        // Rows are stored from top to bottom
+        for y in (0..ny).rev() {
+            for x in 0..nx {
+                let (x, y) = (f64::from(x), f64::from(y));
+                let weight = shapes
+                    .iter()
+                    .map(|shape| shape.signed_distance(x, y).abs())
+                    .map(|distance| (width / 2.0 + 0.5 - distance).clamp(0.0, 1.0))
+                    .fold(0.0, f64::max);
+                rgb.extend(color.iter().map(|&c| (f64::from(c) * weight).round() as u8));
+            }
+        }
+        rgb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{shepplogan, Error, Phantom, Shape};
+
+    #[test]
+    fn test_diff_overlay() {
+        let (nx, ny) = (64, 64);
+        let reference = shepplogan(nx, ny);
+
+        let overlay = reference.diff_overlay(&reference).unwrap();
+        assert_eq!(overlay.len(), 3 * (nx * ny) as usize);
+        assert!(overlay
+            .chunks(3)
+            .all(|pixel| pixel[0] == pixel[1] && pixel[1] == pixel[2]));
+        assert!(overlay.contains(&255));
+
+        // Brighter and darker region on a zero background
+        let mut phantom = reference.clone();
+        let (bright, dark) = (phantom.index(0, 0), phantom.index(nx - 1, 0));
+        phantom.data[bright] += 1.0;
+        phantom.data[dark] -= 0.5;
+        let overlay = phantom.diff_overlay(&reference).unwrap();
+        assert_eq!(overlay[3 * bright..3 * bright + 3], [255, 0, 0]);
+        assert_eq!(overlay[3 * dark..3 * dark + 3], [0, 0, 128]);
+
+        assert!(matches!(
+            reference.diff_overlay(&Phantom::new(nx, ny + 1, &[])),
+            Err(Error::DimensionMismatch { .. })
+        ));
+        // Transposed dimensions have the same number of pixels
+        assert!(matches!(
+            Phantom::new(2, 3, &[]).diff_overlay(&Phantom::new(3, 2, &[])),
+            Err(Error::DimensionMismatch {
+                expected: 6,
+                actual: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn test_draw_outlines() {
+        let color = [255, 128, 0];
+        // Circle with radius 16 centered at (32, 32) and a square with edges at 4 and 12
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::rectangle(-0.75, -0.75, 0.25, 0.25, 0.0, 1.0),
+        ];
+        let (nx, ny) = (64, 64);
+        let outlines = Phantom::draw_outlines(&shapes, nx, ny, color, 1.0);
+        assert_eq!(outlines.len(), 3 * 64 * 64);
+        let pixel = |x: u32, y: u32| {
+            let idx = 3 * ((ny - y - 1) * nx + x) as usize;
+            [outlines[idx], outlines[idx + 1], outlines[idx + 2]]
+        };
+
+        // Points on the outlines
+        for (x, y) in [
+            (48, 32),
+            (16, 32),
+            (32, 48),
+            (32, 16),
+            (4, 8),
+            (12, 4),
+            (8, 12),
+        ] {
+            assert_eq!(pixel(x, y), color);
+        }
+        // Interiors, exterior and pixels slightly off the outline are black
+        for (x, y) in [(32, 32), (40, 32), (8, 8), (60, 60), (50, 32), (2, 8)] {
+            assert_eq!(pixel(x, y), [0, 0, 0]);
+        }
+        // Anti-aliasing of the circle, whose outline passes between the pixels (43, 43) and
+        // (44, 44)
+        let partial = pixel(43, 43);
+        assert!(partial[0] > 0 && partial[0] < 255);
+
+        // Rotated rectangles, lunes and groups have outlines as well
+        let shapes = [
+            Shape::rectangle(0.0, 0.0, 1.0, 0.5, 30.0, 1.0),
+            Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 1.0),
+            Shape::group(vec![Shape::ellipse(0.5, 0.5, 0.2, 0.2, 0.0, 0.0)], 1.0),
+        ];
+        for shape in shapes.iter() {
+            let outlines = Phantom::draw_outlines(std::slice::from_ref(shape), nx, ny, color, 2.0);
+            let drawn = outlines.chunks(3).filter(|&c| c == color).count();
+            assert!(drawn > 20);
+            // Far away from all outlines
+            assert_eq!(outlines[..3], [0, 0, 0]);
+        }
+    }
+}
@@ -0,0 +1,434 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{
+    shape::{Canvas, ShapeOnCanvas},
+    Float,
+};
+
+/// Options which control how shapes are rendered onto a phantom
+///
+/// The default options reproduce the behavior of `Phantom::new`.
+///
+/// # Example
+///
+/// ```
+/// use shepplogan::{Phantom, RenderOptions, Shape};
+///
+/// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, -1.0)];
+/// let options = RenderOptions::new().floor(0.0);
+/// let phantom = Phantom::new_with_options(64, 64, &shapes, &options);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RenderOptions {
+    pub(crate) floor: Option<f64>,
+    pub(crate) sample_at_center: bool,
+    pub(crate) boundary: Boundary,
+    pub(crate) scale: Scale,
+    pub(crate) overlap: Overlap,
+    pub(crate) order: ShapeOrder,
+    pub(crate) bbox_rounding: BBoxRounding,
+}
+
+/// Determines the order in which the shapes are rendered
+///
+/// Rendering the shapes in a different order than given may improve the cache efficiency for
+/// large numbers of shapes. The order is only changed if this does not change the result (up to
+/// rounding errors), which is the case for `Overlap::Add`, `Overlap::Max` and `Overlap::Multiply`
+/// without a floor. Otherwise, the shapes are always rendered as given. Note that for
+/// `Overlap::Add` and `Overlap::Multiply`, the result may differ in the last bits, since floating
+/// point arithmetic is not associative.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ShapeOrder {
+    /// The shapes are rendered in the given order (default)
+    #[default]
+    AsGiven,
+    /// The shapes are rendered from the smallest to the largest bounding box
+    BySizeAscending,
+    /// The shapes are rendered in the Morton order (Z-order) of the centers of their bounding
+    /// boxes, such that shapes which are close to each other are rendered one after the other
+    Morton,
+}
+
+/// Determines how the intensity of a shape is combined with the value of a pixel it covers
+///
+/// The shapes are applied in order, starting from a value of `0.0` for every pixel (`1.0` for
+/// `Overlap::Multiply`).
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Overlap {
+    /// The intensity is added to the value (default)
+    #[default]
+    Add,
+    /// The value is replaced by the intensity, therefore later shapes hide earlier shapes
+    Replace,
+    /// The value is replaced by the maximum of the value and the intensity
+    Max,
+    /// The value is replaced by the intensity if the magnitude of the intensity is larger than
+    /// the magnitude of the value
+    ///
+    /// In contrast to `Max`, shapes with negative intensities are kept if they dominate the
+    /// value, e.g. a pixel covered by shapes with intensities `0.2` and `-0.8` ends up at `-0.8`.
+    /// The sign of the intensity is preserved. If both magnitudes are equal, the value is kept.
+    MaxAbs,
+    /// The shape is composited over the value with opacity `alpha` as in `alpha * intensity +
+    /// (1 - alpha) * value`
+    ///
+    /// This renders the shapes as semi-transparent layers: With an `alpha` of `1.0`, this is the
+    /// same as `Replace`, with an `alpha` of `0.0` the shapes are invisible.
+    AlphaOver {
+        /// opacity of the shapes
+        alpha: f64,
+    },
+    /// The value is multiplied by the intensity
+    ///
+    /// In contrast to the other modes, every pixel starts with a value of `1.0`, such that the
+    /// background, which is not covered by any shape, is `1.0` as well. This models the
+    /// transmission through layers of material according to the Beer-Lambert law, where the
+    /// intensities of the shapes are the transmission factors of the layers.
+    Multiply,
+}
+
+impl Overlap {
+    /// Combines the current `value` of a pixel with the `intensity` of a shape covering it.
+    #[inline(always)]
+    pub(crate) fn apply<T: Float>(self, value: T, intensity: T) -> T {
+        match self {
+            Overlap::Add => value + intensity,
+            Overlap::Replace => intensity,
+            Overlap::Max => value.max(intensity),
+            Overlap::MaxAbs => {
+                if intensity.abs() > value.abs() {
+                    intensity
+                } else {
+                    value
+                }
+            }
+            Overlap::AlphaOver { alpha } => {
+                T::from_f64(alpha) * intensity + T::from_f64(1.0 - alpha) * value
+            }
+            Overlap::Multiply => value * intensity,
+        }
+    }
+
+    /// Value of every pixel before any shape is applied
+    pub(crate) fn initial(self) -> f64 {
+        match self {
+            Overlap::Multiply => 1.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Determines how the shapes, which are defined on `[-1, 1]`, are scaled onto the phantom
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Scale {
+    /// `[-1, 1]` is scaled onto the smaller dimension of the phantom, therefore the entire square
+    /// `[-1, 1] x [-1, 1]` is visible (default)
+    #[default]
+    FitMin,
+    /// `[-1, 1]` is scaled onto the larger dimension of the phantom, therefore the square
+    /// `[-1, 1] x [-1, 1]` fills the entire phantom and is cropped along the smaller dimension
+    FitMax,
+    /// A distance of `1.0` in shape coordinates corresponds to the given number of pixels
+    Fixed(f64),
+}
+
+/// Determines how the bounding boxes of the shapes are rounded to pixels
+///
+/// Only the pixels inside of the bounding box of a shape are tested whether they are covered by
+/// the shape. The bounding box is computed from the outline of the shape on the canvas and
+/// rounded to pixels, which applies to ellipses, lunes and rotated rectangles (the bounding box
+/// of an axis aligned rectangle is always exact).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BBoxRounding {
+    /// Lower bounds are rounded down and upper bounds are rounded up (default)
+    ///
+    /// This never misses a pixel covered by a shape, but includes up to one additional row and
+    /// column of pixels on each side which are then rejected by the test whether they are
+    /// inside of the shape.
+    #[default]
+    Conservative,
+    /// Bounds are rounded to the nearest pixel
+    ///
+    /// This tests fewer pixels, which speeds up rendering many small shapes. However, since the
+    /// bounds are subject to rounding errors, a pixel which is sampled exactly on the outline of
+    /// a shape may be missed.
+    Tight,
+}
+
+impl BBoxRounding {
+    /// Rounds the bounds `min` and `max` of a bounding box to pixels.
+    #[inline(always)]
+    pub(crate) fn round(self, min: f64, max: f64) -> (f64, f64) {
+        match self {
+            BBoxRounding::Conservative => (min.floor(), max.ceil()),
+            BBoxRounding::Tight => (min.round(), max.round()),
+        }
+    }
+}
+
+/// Determines how the pixels at the edges of the shapes are rendered, see
+/// `Phantom::new_with_mode`
+///
+/// The pixel `(x, y)` is considered to be the square `[x - 0.5, x + 0.5] x [y - 0.5, y + 0.5]`
+/// on the canvas, i.e. it is centered on the point sampled by `Phantom::new`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EdgeMode {
+    /// Every pixel is either inside or outside of a shape as in `Phantom::new` (default)
+    #[default]
+    Binary,
+    /// Every pixel is the average of `n` times `n` samples as in `Phantom::new_supersampled`
+    Supersample(u32),
+    /// The intensity of every shape is weighted by the fraction of the pixel covered by the shape
+    ///
+    /// The fraction is computed exactly for ellipses and approximated with 16 times 16 samples
+    /// for the other shapes.
+    AnalyticArea,
+}
+
+/// Determines whether points exactly on the boundary of a shape are inside of the shape
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Boundary {
+    /// Points on the boundary are inside of the shape (default)
+    #[default]
+    Inclusive,
+    /// Points on the boundary are outside of the shape
+    Exclusive,
+}
+
+impl Boundary {
+    /// Compares `a` and `b` as `a <= b` for `Inclusive` and as `a < b` for `Exclusive`.
+    #[inline(always)]
+    pub(crate) fn le(self, a: f64, b: f64) -> bool {
+        match self {
+            Boundary::Inclusive => a <= b,
+            Boundary::Exclusive => a < b,
+        }
+    }
+
+    /// Returns the opposite boundary behavior.
+    ///
+    /// This is needed for shapes which are cut out of other shapes: The boundary of the cut out
+    /// shape is included in the remaining shape if and only if it is excluded from the cut out
+    /// shape.
+    pub(crate) fn flip(self) -> Boundary {
+        match self {
+            Boundary::Inclusive => Boundary::Exclusive,
+            Boundary::Exclusive => Boundary::Inclusive,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Creates the default render options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clamps the accumulated value of each pixel to at least `floor`.
+    ///
+    /// The clamping is applied after each shape is added (not only at the end), therefore a pixel
+    /// covered by a shape never drops below `floor`, even temporarily. This changes the result
+    /// whenever the order of the shapes matters: For instance, a pixel covered by a shape with
+    /// intensity `-1.0` followed by a shape with intensity `1.0` ends up at `1.0` with a floor of
+    /// `0.0` instead of `0.0`. Pixels which are not covered by any shape are not affected.
+    pub fn floor(mut self, floor: f64) -> Self {
+        self.floor = Some(floor);
+        self
+    }
+
+    /// Samples the shapes at the centers of the pixels instead of their corners.
+    ///
+    /// By default, pixel `(x, y)` is considered covered by a shape if the point `(x, y)` on the
+    /// canvas is inside the shape. If the pixels are interpreted as unit squares `[x, x + 1] x
+    /// [y, y + 1]`, this is the lower left corner of the pixel, which shifts all shapes by half a
+    /// pixel towards the lower left corner of the phantom. With `sample_at_center` set to `true`,
+    /// the point `(x + 0.5, y + 0.5)` is sampled instead, which removes this bias. This is
+    /// relevant for sub-pixel accurate ground truth. Defaults to `false` for backwards
+    /// compatibility.
+    pub fn sample_at_center(mut self, sample_at_center: bool) -> Self {
+        self.sample_at_center = sample_at_center;
+        self
+    }
+
+    /// Determines whether pixels which are sampled exactly on the boundary of a shape are covered
+    /// by the shape.
+    ///
+    /// Defaults to `Boundary::Inclusive`. With `Boundary::Exclusive`, only pixels strictly inside
+    /// of a shape are covered. Note that this only affects pixels which are sampled exactly on
+    /// the boundary, which mostly happens for shapes whose edges are aligned with the pixel grid.
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Determines how the shapes are scaled onto the phantom.
+    ///
+    /// Defaults to `Scale::FitMin`. This only makes a difference for non-square phantoms or if
+    /// the size of the shapes in pixels needs to be controlled precisely, e.g. for a given
+    /// physical pixel spacing. In all cases, the origin of the shape coordinates is mapped onto
+    /// the center of the phantom.
+    pub fn scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Determines how the intensities of overlapping shapes are combined.
+    ///
+    /// Defaults to `Overlap::Add`, which is required for the Shepp-Logan phantoms, whose inner
+    /// ellipses are defined relative to the ellipses containing them. If a floor is set (see
+    /// `floor`), it is applied after each shape is combined with the value of a pixel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shepplogan::{Overlap, Phantom, RenderOptions, Shape};
+    ///
+    /// let shapes = [
+    ///     Shape::ellipse(0.0, 0.0, 0.8, 0.8, 0.0, 1.0),
+    ///     Shape::ellipse(0.0, 0.0, 0.2, 0.2, 0.0, 0.5),
+    /// ];
+    /// let options = RenderOptions::new().overlap(Overlap::Replace);
+    /// let phantom = Phantom::new_with_options(64, 64, &shapes, &options);
+    /// assert_eq!(phantom.into_vec()[32 * 64 + 32], 0.5);
+    /// ```
+    pub fn overlap(mut self, overlap: Overlap) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Determines the order in which the shapes are rendered.
+    ///
+    /// Defaults to `ShapeOrder::AsGiven`. See `ShapeOrder` for the overlap modes which allow
+    /// reordering the shapes.
+    pub fn order(mut self, order: ShapeOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Determines how the bounding boxes of the shapes are rounded to pixels.
+    ///
+    /// Defaults to `BBoxRounding::Conservative`. See `BBoxRounding` for the tradeoff between
+    /// both modes.
+    pub fn bbox_rounding(mut self, bbox_rounding: BBoxRounding) -> Self {
+        self.bbox_rounding = bbox_rounding;
+        self
+    }
+
+    /// Reorders `shapes` according to the shape order, if this does not change the result.
+    pub(crate) fn reorder(&self, shapes: &mut [ShapeOnCanvas]) {
+        let reorderable = matches!(
+            self.overlap,
+            Overlap::Add | Overlap::Max | Overlap::Multiply
+        ) && self.floor.is_none();
+        if !reorderable {
+            return;
+        }
+        match self.order {
+            ShapeOrder::AsGiven => {}
+            ShapeOrder::BySizeAscending => shapes.sort_by_cached_key(|shape| {
+                let bbox = shape.bounding_box();
+                if bbox.is_empty() {
+                    0
+                } else {
+                    u64::from(bbox.x_high - bbox.x_low + 1)
+                        * u64::from(bbox.y_high - bbox.y_low + 1)
+                }
+            }),
+            ShapeOrder::Morton => shapes.sort_by_cached_key(|shape| {
+                let bbox = shape.bounding_box();
+                let center = |low: u32, high: u32| ((u64::from(low) + u64::from(high)) / 2) as u32;
+                morton(
+                    center(bbox.x_low, bbox.x_high),
+                    center(bbox.y_low, bbox.y_high),
+                )
+            }),
+        }
+    }
+
+    /// Returns the canvas of size `nx` times `ny` onto which shapes are transformed.
+    pub(crate) fn canvas(&self, nx: u32, ny: u32) -> Canvas {
+        let canvas = Canvas::new(nx, ny)
+            .boundary(self.boundary)
+            .bbox_rounding(self.bbox_rounding);
+        let canvas = match self.scale {
+            Scale::FitMin => canvas,
+            Scale::FitMax => canvas.scale(f64::from(nx.max(ny)) / 2.0),
+            Scale::Fixed(scale) => canvas.scale(scale),
+        };
+        if self.sample_at_center {
+            canvas.sample_offset(0.5)
+        } else {
+            canvas
+        }
+    }
+}
+
+/// Interleaves the bits of `x` and `y` to the Morton code of the point `(x, y)`.
+fn morton(x: u32, y: u32) -> u64 {
+    let spread = |v: u32| {
+        let mut v = u64::from(v);
+        v = (v | (v << 16)) & 0x0000_ffff_0000_ffff;
+        v = (v | (v << 8)) & 0x00ff_00ff_00ff_00ff;
+        v = (v | (v << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+        v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+        (v | (v << 1)) & 0x5555_5555_5555_5555
+    };
+    spread(x) | (spread(y) << 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{morton, BBoxRounding, RenderOptions};
+    use crate::{Phantom, Shape};
+
+    #[test]
+    fn test_morton() {
+        assert_eq!(morton(0, 0), 0);
+        assert_eq!(morton(1, 0), 1);
+        assert_eq!(morton(0, 1), 2);
+        assert_eq!(morton(3, 3), 15);
+        assert_eq!(morton(4, 0), 16);
+        assert_eq!(morton(u32::MAX, u32::MAX), u64::MAX);
+        assert_eq!(morton(u32::MAX, 0), 0x5555_5555_5555_5555);
+    }
+
+    #[test]
+    fn test_bbox_rounding() {
+        let (nx, ny) = (128, 96);
+        let shapes = [
+            Shape::ellipse(0.13, -0.07, 0.61, 0.23, 37.0, 1.0),
+            Shape::rectangle(-0.3, 0.2, 0.37, 0.19, 23.0, 0.5),
+            Shape::lune(0.2, 0.3, 0.31, 0.3, 0.35, 0.2, 0.25),
+        ];
+        let conservative = RenderOptions::new();
+        let tight = RenderOptions::new().bbox_rounding(BBoxRounding::Tight);
+        // Number of pixels which are tested whether they are inside of the shapes
+        let visits = |options: &RenderOptions| {
+            let canvas = options.canvas(nx, ny);
+            shapes
+                .iter()
+                .map(|shape| {
+                    let bbox = shape.on_canvas_with(&canvas).bounding_box();
+                    (bbox.x_high - bbox.x_low + 1) * (bbox.y_high - bbox.y_low + 1)
+                })
+                .collect::<Vec<_>>()
+        };
+        for (c, t) in visits(&conservative).into_iter().zip(visits(&tight)) {
+            assert!(t < c);
+        }
+        for shape in &shapes {
+            let shape = std::slice::from_ref(shape);
+            assert_eq!(
+                Phantom::new_with_options(nx, ny, shape, &tight).into_vec(),
+                Phantom::new_with_options(nx, ny, shape, &conservative).into_vec()
+            );
+        }
+        assert_eq!(BBoxRounding::Tight.round(1.4, 3.6), (1.0, 4.0));
+        assert_eq!(BBoxRounding::Conservative.round(1.4, 3.6), (1.0, 4.0));
+        assert_eq!(BBoxRounding::Tight.round(1.6, 3.4), (2.0, 3.0));
+        assert_eq!(BBoxRounding::Conservative.round(1.6, 3.4), (1.0, 4.0));
+    }
+}
@@ -3,6 +3,8 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use crate::BBoxRounding;
+
 /// A bounding box around a shape
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub(crate) struct BoundingBox {
@@ -23,9 +25,109 @@ impl From<(u32, u32, u32, u32)> for BoundingBox {
     }
 }
 
+impl BoundingBox {
+    /// A bounding box which does not contain any pixel
+    pub(crate) fn empty() -> BoundingBox {
+        (1, 0, 1, 0).into()
+    }
+
+    /// Bounding box of the pixels of a canvas of size `nx` times `ny` which touch the area
+    /// `[x_min, x_max] x [y_min, y_max]` (in canvas coordinates)
+    ///
+    /// The bounds are rounded according to `rounding` and clamped to the canvas. The bounding box
+    /// is empty if the area lies entirely outside of the canvas, if the canvas is empty or if any
+    /// bound is `NaN`.
+    pub(crate) fn clamped(
+        x_min: f64,
+        x_max: f64,
+        y_min: f64,
+        y_max: f64,
+        nx: u32,
+        ny: u32,
+        rounding: BBoxRounding,
+    ) -> Self {
+        let clamp = |min: f64, max: f64, n: u32| {
+            let (low, high) = rounding.round(min, max);
+            let (low, high) = (low.max(0.0), high.min(f64::from(n) - 1.0));
+            // `NaN` fails all comparisons
+            if low <= high && !min.is_nan() && !max.is_nan() {
+                Some((low as u32, high as u32))
+            } else {
+                None
+            }
+        };
+        match (clamp(x_min, x_max, nx), clamp(y_min, y_max, ny)) {
+            (Some((x_low, x_high)), Some((y_low, y_high))) => BoundingBox {
+                x_low,
+                x_high,
+                y_low,
+                y_high,
+            },
+            _ => BoundingBox::empty(),
+        }
+    }
+
+    /// Returns `true` if the bounding box does not contain any pixel
+    pub(crate) fn is_empty(&self) -> bool {
+        self.x_low > self.x_high || self.y_low > self.y_high
+    }
+
+    /// Returns `true` if pixel `(x, y)` is inside of the bounding box
+    #[inline(always)]
+    pub(crate) fn contains(&self, x: u32, y: u32) -> bool {
+        self.x_low <= x && x <= self.x_high && self.y_low <= y && y <= self.y_high
+    }
+
+    /// Bounding box extended by one pixel on each side and clamped to a canvas of size `nx`
+    /// times `ny`
+    pub(crate) fn grow(&self, nx: u32, ny: u32) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+        BoundingBox {
+            x_low: self.x_low.saturating_sub(1),
+            x_high: self.x_high.saturating_add(1).min(nx.saturating_sub(1)),
+            y_low: self.y_low.saturating_sub(1),
+            y_high: self.y_high.saturating_add(1).min(ny.saturating_sub(1)),
+        }
+    }
+
+    /// Smallest bounding box which contains both `self` and `other`
+    pub(crate) fn union(&self, other: &BoundingBox) -> BoundingBox {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        BoundingBox {
+            x_low: self.x_low.min(other.x_low),
+            x_high: self.x_high.max(other.x_high),
+            y_low: self.y_low.min(other.y_low),
+            y_high: self.y_high.max(other.y_high),
+        }
+    }
+
+    /// Largest bounding box which is contained in both `self` and `other`
+    pub(crate) fn intersection(&self, other: &BoundingBox) -> BoundingBox {
+        let bbox = BoundingBox {
+            x_low: self.x_low.max(other.x_low),
+            x_high: self.x_high.min(other.x_high),
+            y_low: self.y_low.max(other.y_low),
+            y_high: self.y_high.min(other.y_high),
+        };
+        if bbox.is_empty() {
+            BoundingBox::empty()
+        } else {
+            bbox
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::BoundingBox;
+    use crate::BBoxRounding;
 
     #[quickcheck]
     fn test_bounding_box(x_low: u32, x_high: u32, y_low: u32, y_high: u32) -> bool {
@@ -41,4 +143,59 @@ mod tests {
                 y_high,
             } == bbox
     }
+
+    #[test]
+    fn test_bounding_box_clamped() {
+        let clamped = |x_min, x_max, y_min, y_max| {
+            BoundingBox::clamped(x_min, x_max, y_min, y_max, 8, 4, BBoxRounding::Conservative)
+        };
+        assert_eq!(clamped(1.5, 3.5, 0.2, 1.0), (1, 4, 0, 1).into());
+        assert_eq!(clamped(-3.0, 10.0, -1.0, 2.5), (0, 7, 0, 3).into());
+        // Touching the canvas
+        assert_eq!(clamped(-3.0, -0.5, 3.5, 5.0), (0, 0, 3, 3).into());
+        // Outside of the canvas, inverted or `NaN`
+        assert!(clamped(8.0, 10.0, 0.0, 1.0).is_empty());
+        assert!(clamped(0.0, 1.0, -2.0, -1.5).is_empty());
+        assert!(clamped(3.0, 1.0, 0.0, 1.0).is_empty());
+        assert!(clamped(f64::NAN, 1.0, 0.0, 1.0).is_empty());
+        assert!(clamped(0.0, 1.0, 0.0, f64::NAN).is_empty());
+        // Empty canvas
+        assert!(BoundingBox::clamped(-1.0, 1.0, -1.0, 1.0, 0, 4, BBoxRounding::Tight).is_empty());
+        assert!(BoundingBox::clamped(-1.0, 1.0, -1.0, 1.0, 4, 0, BBoxRounding::Tight).is_empty());
+    }
+
+    #[test]
+    fn test_bounding_box_union() {
+        let a: BoundingBox = (2, 5, 3, 4).into();
+        let b: BoundingBox = (4, 8, 0, 1).into();
+        assert_eq!(a.union(&b), (2, 8, 0, 4).into());
+        assert!(BoundingBox::empty().is_empty());
+        assert!(!a.is_empty());
+        assert_eq!(a.union(&BoundingBox::empty()), a);
+        assert_eq!(BoundingBox::empty().union(&b), b);
+
+        assert!(a.contains(2, 3) && a.contains(5, 4) && a.contains(3, 4));
+        assert!(!a.contains(1, 3) && !a.contains(6, 3) && !a.contains(2, 5));
+        assert!(!BoundingBox::empty().contains(0, 0) && !BoundingBox::empty().contains(1, 1));
+    }
+
+    #[test]
+    fn test_bounding_box_intersection() {
+        let a: BoundingBox = (2, 5, 3, 4).into();
+        let b: BoundingBox = (4, 8, 0, 3).into();
+        assert_eq!(a.intersection(&b), (4, 5, 3, 3).into());
+        assert_eq!(b.intersection(&a), (4, 5, 3, 3).into());
+        assert_eq!(a.intersection(&a), a);
+        assert!(a.intersection(&(6, 8, 3, 4).into()).is_empty());
+        assert!(a.intersection(&(2, 5, 5, 6).into()).is_empty());
+        assert_eq!(a.intersection(&BoundingBox::empty()), BoundingBox::empty());
+    }
+
+    #[test]
+    fn test_bounding_box_grow() {
+        let a: BoundingBox = (2, 5, 0, 3).into();
+        assert_eq!(a.grow(8, 8), (1, 6, 0, 4).into());
+        assert_eq!(a.grow(6, 4), (1, 5, 0, 3).into());
+        assert!(BoundingBox::empty().grow(8, 8).is_empty());
+    }
 }
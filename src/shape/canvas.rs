@@ -0,0 +1,160 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{BBoxRounding, Boundary, Error};
+
+/// Mapping of the shape coordinates onto the pixel grid of a phantom
+///
+/// A coordinate `x` of a shape is mapped onto `x * scale + offset_x` on the canvas (and likewise
+/// for `y`). Pixel `(i, j)` of the phantom is sampled at the canvas coordinates `(i, j)`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Canvas {
+    /// number of pixels in x direction
+    pub(crate) nx: u32,
+    /// number of pixels in y direction
+    pub(crate) ny: u32,
+    /// scaling of the shape coordinates
+    pub(crate) scale: f64,
+    /// position of the origin of the shape coordinates in x direction
+    pub(crate) offset_x: f64,
+    /// position of the origin of the shape coordinates in y direction
+    pub(crate) offset_y: f64,
+    /// whether points on the boundary of a shape are inside of the shape
+    pub(crate) boundary: Boundary,
+    /// how bounding boxes are rounded to pixels
+    pub(crate) bbox_rounding: BBoxRounding,
+}
+
+impl Canvas {
+    /// Canvas of size `nx` times `ny`.
+    ///
+    /// The square `[-1, 1]` is scaled onto the smaller dimension and centered.
+    pub(crate) fn new(nx: u32, ny: u32) -> Self {
+        let nx_half = f64::from(nx) / 2.0;
+        let ny_half = f64::from(ny) / 2.0;
+        // Everything is going to be scaled by the smaller dimension
+        let n_min = std::cmp::min_by(nx_half, ny_half, |nx, ny| nx.partial_cmp(ny).unwrap());
+        Canvas {
+            nx,
+            ny,
+            scale: n_min,
+            offset_x: nx_half,
+            offset_y: ny_half,
+            boundary: Boundary::Inclusive,
+            bbox_rounding: BBoxRounding::Conservative,
+        }
+    }
+
+    /// Sets the number of pixels corresponding to a distance of `1.0` in shape coordinates.
+    pub(crate) fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets whether points on the boundary of a shape are inside of the shape.
+    pub(crate) fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Sets how bounding boxes are rounded to pixels.
+    pub(crate) fn bbox_rounding(mut self, bbox_rounding: BBoxRounding) -> Self {
+        self.bbox_rounding = bbox_rounding;
+        self
+    }
+
+    /// Samples pixel `(i, j)` at `(i + offset, j + offset)` instead of `(i, j)`.
+    ///
+    /// This is achieved by shifting the shapes by `-offset`.
+    pub(crate) fn sample_offset(mut self, offset: f64) -> Self {
+        self.offset_x -= offset;
+        self.offset_y -= offset;
+        self
+    }
+
+    /// Refines the canvas into `samples` times `samples` subpixels per pixel.
+    ///
+    /// The subpixels of pixel `(i, j)` are sampled on a regular grid which is centered on the
+    /// sampling point of the pixel, i.e. subpixel `(k, l)` samples the point
+    /// `(i + (k + 0.5) / samples - 0.5, j + (l + 0.5) / samples - 0.5)` of the original canvas.
+    /// For a single sample, the canvas is unchanged. Returns `Error::DimensionOverflow` if the
+    /// number of subpixels along `x` or `y` does not fit into a `u32`.
+    pub(crate) fn supersample(mut self, samples: u32) -> Result<Self, Error> {
+        let s = f64::from(samples);
+        let shift = (s - 1.0) / 2.0;
+        self.nx = self
+            .nx
+            .checked_mul(samples)
+            .ok_or(Error::DimensionOverflow)?;
+        self.ny = self
+            .ny
+            .checked_mul(samples)
+            .ok_or(Error::DimensionOverflow)?;
+        self.scale *= s;
+        self.offset_x = self.offset_x * s + shift;
+        self.offset_y = self.offset_y * s + shift;
+        Ok(self)
+    }
+
+    /// Maps the point `(x, y)` given in shape coordinates onto the canvas.
+    #[inline(always)]
+    pub(crate) fn map(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            x * self.scale + self.offset_x,
+            y * self.scale + self.offset_y,
+        )
+    }
+
+    /// Maps the point `(x, y)` on the canvas back to shape coordinates.
+    ///
+    /// This is the inverse of `map`.
+    pub(crate) fn unmap(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            (x - self.offset_x) / self.scale,
+            (y - self.offset_y) / self.scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Canvas;
+    use crate::Error;
+
+    #[test]
+    fn test_canvas() {
+        let canvas = Canvas::new(64, 128);
+        assert_eq!(canvas.scale, 32.0);
+        assert_eq!(canvas.map(0.0, 0.0), (32.0, 64.0));
+        assert_eq!(canvas.map(-1.0, 1.0), (0.0, 96.0));
+
+        let canvas = Canvas::new(100, 50).sample_offset(0.5);
+        assert_eq!(canvas.scale, 25.0);
+        assert_eq!(canvas.map(0.0, 0.0), (49.5, 24.5));
+
+        let canvas = Canvas::new(100, 50).scale(50.0);
+        assert_eq!(canvas.map(1.0, -1.0), (100.0, -25.0));
+        assert_eq!(canvas.unmap(100.0, -25.0), (1.0, -1.0));
+    }
+
+    #[test]
+    fn test_canvas_supersample() {
+        let canvas = Canvas::new(64, 32);
+        assert_eq!(canvas.supersample(1).unwrap(), canvas);
+
+        // The subpixels of pixel (2, 3) sample the points (2 ± 0.25, 3 ± 0.25)
+        let fine = canvas.supersample(2).unwrap();
+        assert_eq!((fine.nx, fine.ny), (128, 64));
+        let (x, y) = canvas.unmap(1.75, 2.75);
+        assert_eq!(fine.map(x, y), (4.0, 6.0));
+        let (x, y) = canvas.unmap(2.25, 3.25);
+        assert_eq!(fine.map(x, y), (5.0, 7.0));
+
+        assert!(matches!(
+            canvas.supersample(u32::MAX / 32),
+            Err(Error::DimensionOverflow)
+        ));
+    }
+}
@@ -3,10 +3,12 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use super::BoundingBox;
+use super::{BoundingBox, Canvas};
+use crate::Boundary;
 
 /// Representation of an Ellipse.
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Ellipse {
     /// x-coordinate of center
     pub(crate) center_x: f64,
@@ -48,8 +50,66 @@ impl Ellipse {
         }
     }
 
+    /// Area of the ellipse in shape coordinates
+    pub(crate) fn area(&self) -> f64 {
+        std::f64::consts::PI * (self.major_axis * self.minor_axis).abs()
+    }
+
+    /// Perimeter of the ellipse in shape coordinates, using Ramanujan's second approximation
+    pub(crate) fn perimeter(&self) -> f64 {
+        let a = self.major_axis.abs();
+        let b = self.minor_axis.abs();
+        if a + b == 0.0 {
+            return 0.0;
+        }
+        let h = ((a - b) / (a + b)).powi(2);
+        std::f64::consts::PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+
+    /// Bounding box `(x_min, x_max, y_min, y_max)` of the ellipse in shape coordinates
+    pub(crate) fn bounds(&self) -> (f64, f64, f64, f64) {
+        let (theta_sin, theta_cos) = self.theta.to_radians().sin_cos();
+        let half_width = (self.major_axis * theta_cos).hypot(self.minor_axis * theta_sin);
+        let half_height = (self.major_axis * theta_sin).hypot(self.minor_axis * theta_cos);
+        (
+            self.center_x - half_width,
+            self.center_x + half_width,
+            self.center_y - half_height,
+            self.center_y + half_height,
+        )
+    }
+
+    /// Returns the parameters `[center_x, center_y, major_axis, minor_axis, theta]`.
+    pub(crate) fn params(&self) -> [f64; 5] {
+        [
+            self.center_x,
+            self.center_y,
+            self.major_axis,
+            self.minor_axis,
+            self.theta,
+        ]
+    }
+
+    /// Returns the bit patterns of the parameters.
+    pub(crate) fn to_bits(&self) -> [u64; 5] {
+        self.params().map(f64::to_bits)
+    }
+
+    /// Returns `true` if all parameters are finite.
+    pub(crate) fn is_finite(&self) -> bool {
+        [
+            self.center_x,
+            self.center_y,
+            self.major_axis,
+            self.minor_axis,
+            self.theta,
+        ]
+        .iter()
+        .all(|x| x.is_finite())
+    }
+
     #[inline(always)]
-    pub(crate) fn on_canvas(&self, nx: u32, ny: u32) -> EllipseOnCanvas {
+    pub(crate) fn on_canvas(&self, canvas: &Canvas) -> EllipseOnCanvas {
         let Self {
             center_x,
             center_y,
@@ -61,18 +121,12 @@ impl Ellipse {
         let theta = theta.to_radians();
         let theta_sin = theta.sin();
         let theta_cos = theta.cos();
-        let nx_f = f64::from(nx);
-        let ny_f = f64::from(ny);
-        let nx_half = nx_f / 2.0;
-        let ny_half = ny_f / 2.0;
-        let n_min = std::cmp::min_by(nx_half, ny_half, |nx, ny| nx.partial_cmp(ny).unwrap());
-
-        let center_x = center_x * n_min + nx_half;
-        let center_y = center_y * n_min + ny_half;
-        let major_axis = major_axis * n_min;
-        let minor_axis = minor_axis * n_min;
-        let major_axis_squared = major_axis.powi(2);
-        let minor_axis_squared = minor_axis.powi(2);
+        let (center_x, center_y) = canvas.map(*center_x, *center_y);
+        let major_axis = major_axis * canvas.scale;
+        let minor_axis = minor_axis * canvas.scale;
+        // Reciprocals, such that checking whether a point is inside requires no divisions
+        let inv_major_axis_squared = major_axis.powi(2).recip();
+        let inv_minor_axis_squared = minor_axis.powi(2).recip();
 
         let theta_pi2_sin = (theta + std::f64::consts::FRAC_PI_2).sin();
         let theta_pi2_cos = (theta + std::f64::consts::FRAC_PI_2).cos();
@@ -83,32 +137,25 @@ impl Ellipse {
         let halfwidth = (ux.powi(2) + vx.powi(2)).sqrt();
         let halfheight = (uy.powi(2) + vy.powi(2)).sqrt();
 
-        let bbox: Vec<u32> = [
-            ((center_x - halfwidth).floor(), nx_f),
-            ((center_x + halfwidth).ceil(), nx_f),
-            ((center_y - halfheight).floor(), ny_f),
-            ((center_y + halfheight).ceil(), ny_f),
-        ]
-        .into_iter()
-        .map(|(b, l)| {
-            if b < 0.0 {
-                0
-            } else if b >= l {
-                (l - 1.0) as u32
-            } else {
-                b as u32
-            }
-        })
-        .collect();
+        let bbox = BoundingBox::clamped(
+            center_x - halfwidth,
+            center_x + halfwidth,
+            center_y - halfheight,
+            center_y + halfheight,
+            canvas.nx,
+            canvas.ny,
+            canvas.bbox_rounding,
+        );
 
         EllipseOnCanvas {
             center_x,
             center_y,
-            major_axis_squared,
-            minor_axis_squared,
+            inv_major_axis_squared,
+            inv_minor_axis_squared,
             theta_sin,
             theta_cos,
-            bbox: (bbox[0], bbox[1], bbox[2], bbox[3]).into(),
+            bbox,
+            boundary: canvas.boundary,
         }
     }
 }
@@ -120,16 +167,18 @@ pub(crate) struct EllipseOnCanvas {
     center_x: f64,
     /// y-coordinate of center
     center_y: f64,
-    /// major axis squared
-    major_axis_squared: f64,
-    /// minor axis squared
-    minor_axis_squared: f64,
+    /// reciprocal of the major axis squared
+    inv_major_axis_squared: f64,
+    /// reciprocal of the minor axis squared
+    inv_minor_axis_squared: f64,
     /// sin(theta)
     theta_sin: f64,
     /// cos(theta)
     theta_cos: f64,
     /// bounding box
     bbox: BoundingBox,
+    /// whether points on the boundary are inside
+    boundary: Boundary,
 }
 
 impl EllipseOnCanvas {
@@ -143,15 +192,138 @@ impl EllipseOnCanvas {
     pub(crate) fn inside(&self, x: f64, y: f64) -> bool {
         let x_diff = x - self.center_x;
         let y_diff = y - self.center_y;
-        (self.theta_cos * x_diff + self.theta_sin * y_diff).powi(2) / self.major_axis_squared
-            + (self.theta_sin * x_diff - self.theta_cos * y_diff).powi(2) / self.minor_axis_squared
-            <= 1.0
+        let u = self.theta_cos * x_diff + self.theta_sin * y_diff;
+        let v = self.theta_sin * x_diff - self.theta_cos * y_diff;
+        self.boundary.le(
+            u * u * self.inv_major_axis_squared + v * v * self.inv_minor_axis_squared,
+            1.0,
+        )
+    }
+
+    /// Approximate signed distance of a point to the outline of the ellipse
+    ///
+    /// The distance is negative inside of the ellipse. It is exact for circles and a first order
+    /// approximation otherwise, which is accurate close to the outline.
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        let x_diff = x - self.center_x;
+        let y_diff = y - self.center_y;
+        let u = self.theta_cos * x_diff + self.theta_sin * y_diff;
+        let v = self.theta_sin * x_diff - self.theta_cos * y_diff;
+        let (inv_a, inv_b) = (self.inv_major_axis_squared, self.inv_minor_axis_squared);
+        let level = (u * u * inv_a + v * v * inv_b).sqrt();
+        let gradient = (u * u * inv_a * inv_a + v * v * inv_b * inv_b).sqrt();
+        if gradient > 0.0 {
+            (level - 1.0) * level / gradient
+        } else {
+            // Center of the ellipse
+            -inv_a.max(inv_b).sqrt().recip()
+        }
+    }
+
+    /// Fraction of the square `[x - 0.5, x + 0.5] x [y - 0.5, y + 0.5]` which is covered by the
+    /// ellipse
+    ///
+    /// The square is transformed into the coordinates in which the ellipse is the unit circle,
+    /// where it becomes a parallelogram. The area of the intersection of the parallelogram and the
+    /// unit disk is then computed exactly (up to rounding errors).
+    pub(crate) fn coverage(&self, x: f64, y: f64) -> f64 {
+        let scale_u = self.inv_major_axis_squared.sqrt();
+        let scale_v = self.inv_minor_axis_squared.sqrt();
+        // An ellipse with an axis of length zero does not have an area
+        if !(scale_u.is_finite() && scale_v.is_finite()) {
+            return 0.0;
+        }
+        let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)].map(|(dx, dy)| {
+            let x_diff = x + dx - self.center_x;
+            let y_diff = y + dy - self.center_y;
+            (
+                (self.theta_cos * x_diff + self.theta_sin * y_diff) * scale_u,
+                (self.theta_sin * x_diff - self.theta_cos * y_diff) * scale_v,
+            )
+        });
+        // The ellipse is convex, therefore the square is inside if all corners are inside
+        if corners.iter().all(|(u, v)| u * u + v * v <= 1.0) {
+            return 1.0;
+        }
+        let edges = (0..4).map(|i| (corners[i], corners[(i + 1) % 4]));
+        // The transformation scales areas by `scale_u * scale_v`
+        let area = if edges
+            .clone()
+            .all(|(a, b)| segment_distance_squared(a, b) >= 1.0)
+        {
+            // The outline of the square does not intersect the disk, therefore the disk is either
+            // entirely inside of the square or outside. The edges wind around the origin in the
+            // first case.
+            let cross = edges.map(|(a, b)| a.0 * b.1 - a.1 * b.0);
+            if cross.clone().all(|c| c > 0.0) || cross.clone().all(|c| c < 0.0) {
+                std::f64::consts::PI
+            } else {
+                0.0
+            }
+        } else {
+            edges
+                .map(|(a, b)| unit_disk_triangle_area(a, b))
+                .sum::<f64>()
+                .abs()
+        };
+        (area / (scale_u * scale_v)).min(1.0)
     }
 }
 
+/// Squared distance of the origin to the line segment from `a` to `b`
+fn segment_distance_squared(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (-(a.0 * dx + a.1 * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (x, y) = (a.0 + t * dx, a.1 + t * dy);
+    x * x + y * y
+}
+
+/// Signed area of the intersection of the unit disk and the triangle `(0, a, b)`
+///
+/// The area is positive if the triangle is oriented counterclockwise. Summing over the edges of a
+/// polygon yields the area of the intersection of the polygon and the unit disk.
+fn unit_disk_triangle_area(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let point = |t: f64| (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1));
+    // The edge `a + t * (b - a)` crosses the circle at the roots of `qa * t² + 2 qb * t + qc`
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let qa = dx * dx + dy * dy;
+    let qb = a.0 * dx + a.1 * dy;
+    let qc = a.0 * a.0 + a.1 * a.1 - 1.0;
+    let discriminant = qb * qb - qa * qc;
+    let mut ts = vec![0.0];
+    if qa > 0.0 && discriminant > 0.0 {
+        let root = discriminant.sqrt();
+        ts.extend(
+            [(-qb - root) / qa, (-qb + root) / qa]
+                .into_iter()
+                .filter(|&t| t > 0.0 && t < 1.0),
+        );
+    }
+    ts.push(1.0);
+
+    // Each piece of the edge is either inside of the circle (triangle) or outside (sector)
+    ts.windows(2)
+        .map(|t| {
+            let (p, q) = (point(t[0]), point(t[1]));
+            let mid = point((t[0] + t[1]) / 2.0);
+            let cross = p.0 * q.1 - p.1 * q.0;
+            if mid.0 * mid.0 + mid.1 * mid.1 <= 1.0 {
+                cross / 2.0
+            } else {
+                cross.atan2(p.0 * q.0 + p.1 * q.1) / 2.0
+            }
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Ellipse;
+    use super::{unit_disk_triangle_area, BoundingBox, Canvas, Ellipse, EllipseOnCanvas};
     use approx::assert_abs_diff_eq;
 
     #[derive(Debug, Copy, Clone)]
@@ -239,7 +411,7 @@ mod tests {
             theta_input,
         );
 
-        let ellipse_on_canvas = ellipse.on_canvas(nx, ny);
+        let ellipse_on_canvas = ellipse.on_canvas(&Canvas::new(nx, ny));
 
         let theta = ellipse.theta.to_radians();
         let theta_sin = theta.sin();
@@ -254,8 +426,8 @@ mod tests {
         let center_y = ellipse.center_y * n_min + ny_half;
         let major_axis = ellipse.major_axis * n_min;
         let minor_axis = ellipse.minor_axis * n_min;
-        let major_axis_squared = major_axis.powi(2);
-        let minor_axis_squared = minor_axis.powi(2);
+        let inv_major_axis_squared = 1.0 / major_axis.powi(2);
+        let inv_minor_axis_squared = 1.0 / minor_axis.powi(2);
 
         let theta_pi2_sin = (theta + std::f64::consts::FRAC_PI_2).sin();
         let theta_pi2_cos = (theta + std::f64::consts::FRAC_PI_2).cos();
@@ -283,21 +455,37 @@ mod tests {
             }
         })
         .collect();
+        // Ellipses entirely outside of the canvas have an empty bounding box
+        let outside = (center_x + halfwidth).ceil() < 0.0
+            || (center_x - halfwidth).floor() >= nx_f
+            || (center_y + halfheight).ceil() < 0.0
+            || (center_y - halfheight).floor() >= ny_f;
+        let bbox: BoundingBox = if outside {
+            BoundingBox::empty()
+        } else {
+            (bbox[0], bbox[1], bbox[2], bbox[3]).into()
+        };
 
         assert_abs_diff_eq!(ellipse_on_canvas.center_x, center_x);
         assert_abs_diff_eq!(ellipse_on_canvas.center_y, center_y);
-        assert_abs_diff_eq!(ellipse_on_canvas.major_axis_squared, major_axis_squared);
-        assert_abs_diff_eq!(ellipse_on_canvas.minor_axis_squared, minor_axis_squared);
-        assert_abs_diff_eq!(ellipse_on_canvas.theta_sin, theta_sin);
-        assert_abs_diff_eq!(ellipse_on_canvas.theta_cos, theta_cos);
-        assert_eq!(
-            ellipse_on_canvas.bbox,
-            (bbox[0], bbox[1], bbox[2], bbox[3]).into()
+        // Within one ULP; the reciprocals are infinite for axes of length zero
+        let ulps = |a: f64, b: f64| (a.to_bits() as i64 - b.to_bits() as i64).abs();
+        assert!(
+            ulps(
+                ellipse_on_canvas.inv_major_axis_squared,
+                inv_major_axis_squared
+            ) <= 1
         );
-        assert_eq!(
-            ellipse_on_canvas.bounding_box(),
-            (bbox[0], bbox[1], bbox[2], bbox[3]).into()
+        assert!(
+            ulps(
+                ellipse_on_canvas.inv_minor_axis_squared,
+                inv_minor_axis_squared
+            ) <= 1
         );
+        assert_abs_diff_eq!(ellipse_on_canvas.theta_sin, theta_sin);
+        assert_abs_diff_eq!(ellipse_on_canvas.theta_cos, theta_cos);
+        assert_eq!(ellipse_on_canvas.bbox, bbox);
+        assert_eq!(ellipse_on_canvas.bounding_box(), bbox);
     }
 
     #[quickcheck]
@@ -317,17 +505,167 @@ mod tests {
         let y = y.0;
         let major_axis = 2.0 * minor_axis.0;
 
+        let canvas = Canvas::new(nx, ny);
         let ellipse = Ellipse::new(center_x.0, center_y.0, major_axis, minor_axis.0, theta.0)
-            .on_canvas(nx, ny);
+            .on_canvas(&canvas);
+
+        // Division based reference
+        let value = quadratic_form(&ellipse, major_axis, minor_axis.0, &canvas, x, y);
+        // Points within rounding errors of the boundary may end up on either side
+        ellipse.inside(x, y) == (value <= 1.0) || (value - 1.0).abs() < 1e-12
+    }
 
+    /// Evaluates the quadratic form defining the ellipse at `(x, y)` by dividing by the squared
+    /// axes instead of multiplying with their reciprocals.
+    fn quadratic_form(
+        ellipse: &EllipseOnCanvas,
+        major_axis: f64,
+        minor_axis: f64,
+        canvas: &Canvas,
+        x: f64,
+        y: f64,
+    ) -> f64 {
         let x_diff = x - ellipse.center_x;
         let y_diff = y - ellipse.center_y;
-        let inside = (ellipse.theta_cos * x_diff + ellipse.theta_sin * y_diff).powi(2)
-            / ellipse.major_axis_squared
+        (ellipse.theta_cos * x_diff + ellipse.theta_sin * y_diff).powi(2)
+            / (major_axis * canvas.scale).powi(2)
             + (ellipse.theta_sin * x_diff - ellipse.theta_cos * y_diff).powi(2)
-                / ellipse.minor_axis_squared
-            <= 1.0;
+                / (minor_axis * canvas.scale).powi(2)
+    }
+
+    #[test]
+    fn test_reciprocals() {
+        let canvas = Canvas::new(128, 96);
+        for &(major_axis, minor_axis, theta) in
+            &[(0.69, 0.92, 0.0), (0.11, 0.31, -18.0), (0.7, 0.2, 33.3)]
+        {
+            let ellipse = Ellipse::new(0.1, -0.2, major_axis, minor_axis, theta);
+            let on_canvas = ellipse.on_canvas(&canvas);
+            for x in 0..128 {
+                for y in 0..96 {
+                    let (x, y) = (f64::from(x), f64::from(y));
+                    let expected =
+                        quadratic_form(&on_canvas, major_axis, minor_axis, &canvas, x, y);
+                    let u = on_canvas.theta_cos * (x - on_canvas.center_x)
+                        + on_canvas.theta_sin * (y - on_canvas.center_y);
+                    let v = on_canvas.theta_sin * (x - on_canvas.center_x)
+                        - on_canvas.theta_cos * (y - on_canvas.center_y);
+                    let value = u * u * on_canvas.inv_major_axis_squared
+                        + v * v * on_canvas.inv_minor_axis_squared;
+                    // A few ULPs of difference
+                    assert_abs_diff_eq!(value, expected, epsilon = 8.0 * f64::EPSILON * expected);
+                    if (expected - 1.0).abs() > 1e-12 {
+                        assert_eq!(on_canvas.inside(x, y), expected <= 1.0);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bounding_box_non_square_tall() {
+        let (nx, ny) = (64, 512);
+
+        // Exceeds the canvas at the top. If the y bounds were clamped with `nx`, both `y_low` and
+        // `y_high` would end up at 63.
+        let bbox = Ellipse::new(0.0, 7.5, 0.9, 0.9, 0.0)
+            .on_canvas(&Canvas::new(nx, ny))
+            .bounding_box();
+        assert_eq!(bbox.y_high, ny - 1);
+        assert!(bbox.y_low > nx && bbox.y_low < ny);
+        assert!(bbox.x_low < bbox.x_high && bbox.x_high < nx);
+
+        // Exceeds the canvas on the right. If the x bounds were clamped with `ny`, `x_high` would
+        // be outside of the canvas.
+        let bbox = Ellipse::new(1.5, 0.0, 0.9, 0.9, 0.0)
+            .on_canvas(&Canvas::new(nx, ny))
+            .bounding_box();
+        assert_eq!(bbox.x_high, nx - 1);
+        assert!(bbox.x_low < nx);
+        assert!(bbox.y_low > nx && bbox.y_high < ny);
+    }
+
+    #[test]
+    fn test_bounding_box_non_square_wide() {
+        let (nx, ny) = (512, 64);
+
+        // Exceeds the canvas on the right. If the x bounds were clamped with `ny`, both `x_low`
+        // and `x_high` would end up at 63.
+        let bbox = Ellipse::new(7.5, 0.0, 0.9, 0.9, 0.0)
+            .on_canvas(&Canvas::new(nx, ny))
+            .bounding_box();
+        assert_eq!(bbox.x_high, nx - 1);
+        assert!(bbox.x_low > ny && bbox.x_low < nx);
+        assert!(bbox.y_low < bbox.y_high && bbox.y_high < ny);
+
+        // Exceeds the canvas at the top. If the y bounds were clamped with `nx`, `y_high` would be
+        // outside of the canvas.
+        let bbox = Ellipse::new(0.0, 1.5, 0.9, 0.9, 0.0)
+            .on_canvas(&Canvas::new(nx, ny))
+            .bounding_box();
+        assert_eq!(bbox.y_high, ny - 1);
+        assert!(bbox.y_low < ny);
+        assert!(bbox.x_low > ny && bbox.x_high < nx);
+    }
+
+    #[test]
+    fn test_unit_disk_triangle_area() {
+        // Triangle inside of the disk
+        assert_abs_diff_eq!(
+            unit_disk_triangle_area((0.5, 0.0), (0.0, 0.5)),
+            0.125,
+            epsilon = 1e-15
+        );
+        // Clockwise triangle
+        assert_abs_diff_eq!(
+            unit_disk_triangle_area((0.0, 0.5), (0.5, 0.0)),
+            -0.125,
+            epsilon = 1e-15
+        );
+        // Quarter of the disk
+        assert_abs_diff_eq!(
+            unit_disk_triangle_area((2.0, 0.0), (0.0, 2.0)),
+            std::f64::consts::FRAC_PI_4,
+            epsilon = 1e-15
+        );
+        // Square around the disk
+        let square = [(-2.0, -2.0), (2.0, -2.0), (2.0, 2.0), (-2.0, 2.0)];
+        let area: f64 = (0..4)
+            .map(|i| unit_disk_triangle_area(square[i], square[(i + 1) % 4]))
+            .sum();
+        assert_abs_diff_eq!(area, std::f64::consts::PI, epsilon = 1e-14);
+    }
+
+    #[test]
+    fn test_ellipse_coverage() {
+        let (nx, ny) = (48, 40);
+        let canvas = Canvas::new(nx, ny);
+        let ellipse = Ellipse::new(0.1, -0.2, 0.5, 0.3, 30.0).on_canvas(&canvas);
+        let mut sum = 0.0;
+        for x in 0..nx {
+            for y in 0..ny {
+                let coverage = ellipse.coverage(f64::from(x), f64::from(y));
+                assert!((0.0..=1.0).contains(&coverage));
+                sum += coverage;
+            }
+        }
+        let area = std::f64::consts::PI * 0.5 * 0.3 * canvas.scale.powi(2);
+        assert_abs_diff_eq!(sum, area, epsilon = 1e-9);
+        assert_eq!(ellipse.coverage(26.0, 16.0), 1.0);
+        assert_eq!(ellipse.coverage(0.0, 0.0), 0.0);
+
+        // The outline of a large circle passes through the center of the pixel
+        let circle = Ellipse::new(0.0, 0.0, 10.0, 10.0, 0.0).on_canvas(&canvas);
+        let (x, y) = canvas.map(10.0, 0.0);
+        assert_abs_diff_eq!(circle.coverage(x, y), 0.5, epsilon = 1e-3);
+
+        let degenerate = Ellipse::new(0.0, 0.0, 0.5, 0.0, 0.0).on_canvas(&canvas);
+        assert_eq!(degenerate.coverage(24.0, 20.0), 0.0);
 
-        ellipse.inside(x, y) == inside
+        // A tiny ellipse inside of a pixel
+        let tiny = Ellipse::new(0.0, 0.0, 0.01, 0.005, 10.0).on_canvas(&canvas);
+        let area = std::f64::consts::PI * 0.01 * 0.005 * canvas.scale.powi(2);
+        assert_abs_diff_eq!(tiny.coverage(24.0, 20.0), area, epsilon = 1e-15);
+        assert_eq!(tiny.coverage(25.0, 20.0), 0.0);
     }
 }
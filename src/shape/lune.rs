@@ -0,0 +1,211 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{
+    ellipse::{Ellipse, EllipseOnCanvas},
+    BoundingBox, Canvas,
+};
+
+/// Representation of a lune (crescent).
+///
+/// A lune is the region inside the circle `outer` which is not inside the circle `inner`.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Lune {
+    /// circle defining the outline of the lune
+    pub(crate) outer: Ellipse,
+    /// circle which is cut out of `outer`
+    pub(crate) inner: Ellipse,
+}
+
+impl Lune {
+    /// Constructs a new lune.
+    ///
+    /// The canvas for defining lunes is square and ranges from -1 to 1 on both axes `x` and `y`.
+    ///
+    /// # Parameters
+    ///
+    /// * `center_x`: x component of center of the outer circle on the canvas
+    /// * `center_y`: y component of center of the outer circle on the canvas
+    /// * `radius`: radius of the outer circle
+    /// * `cut_center_x`: x component of center of the inner circle on the canvas
+    /// * `cut_center_y`: y component of center of the inner circle on the canvas
+    /// * `cut_radius`: radius of the inner circle
+    pub(crate) fn new(
+        center_x: f64,
+        center_y: f64,
+        radius: f64,
+        cut_center_x: f64,
+        cut_center_y: f64,
+        cut_radius: f64,
+    ) -> Self {
+        Lune {
+            outer: Ellipse::new(center_x, center_y, radius, radius, 0.0),
+            inner: Ellipse::new(cut_center_x, cut_center_y, cut_radius, cut_radius, 0.0),
+        }
+    }
+
+    /// Area of the lune in shape coordinates
+    pub(crate) fn area(&self) -> f64 {
+        let r1 = self.outer.major_axis.abs();
+        let r2 = self.inner.major_axis.abs();
+        let d = (self.outer.center_x - self.inner.center_x)
+            .hypot(self.outer.center_y - self.inner.center_y);
+        // Area of the intersection of both circles
+        let intersection = if d >= r1 + r2 {
+            0.0
+        } else if d <= (r1 - r2).abs() {
+            std::f64::consts::PI * r1.min(r2).powi(2)
+        } else {
+            let alpha = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).acos();
+            let beta = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).acos();
+            let kite = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).sqrt();
+            r1 * r1 * alpha + r2 * r2 * beta - 0.5 * kite
+        };
+        self.outer.area() - intersection
+    }
+
+    /// Perimeter of the lune in shape coordinates
+    ///
+    /// The outline consists of the arc of the outer circle outside of the inner circle and the
+    /// arc of the inner circle inside of the outer circle.
+    pub(crate) fn perimeter(&self) -> f64 {
+        use std::f64::consts::PI;
+
+        let r1 = self.outer.major_axis.abs();
+        let r2 = self.inner.major_axis.abs();
+        let d = (self.outer.center_x - self.inner.center_x)
+            .hypot(self.outer.center_y - self.inner.center_y);
+        if d >= r1 + r2 {
+            2.0 * PI * r1
+        } else if d <= r2 - r1 {
+            0.0
+        } else if d <= r1 - r2 {
+            2.0 * PI * (r1 + r2)
+        } else {
+            // Half of the angles of the arcs of both circles inside of the other circle
+            let alpha = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).acos();
+            let beta = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).acos();
+            r1 * (2.0 * PI - 2.0 * alpha) + r2 * 2.0 * beta
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn on_canvas(&self, canvas: &Canvas) -> LuneOnCanvas {
+        LuneOnCanvas {
+            outer: self.outer.on_canvas(canvas),
+            // The boundary of the inner circle belongs to the lune if it does not belong to the
+            // inner circle.
+            inner: self
+                .inner
+                .on_canvas(&canvas.boundary(canvas.boundary.flip())),
+        }
+    }
+}
+
+/// Representation of a lune on a canvas
+#[derive(PartialEq, Clone, Debug)]
+pub(crate) struct LuneOnCanvas {
+    outer: EllipseOnCanvas,
+    inner: EllipseOnCanvas,
+}
+
+impl LuneOnCanvas {
+    /// The bounding box of a lune is the bounding box of the outer circle
+    #[inline(always)]
+    pub(crate) fn bounding_box(&self) -> BoundingBox {
+        self.outer.bounding_box()
+    }
+
+    /// Checks if a point is inside the lune
+    #[inline(always)]
+    pub(crate) fn inside(&self, x: f64, y: f64) -> bool {
+        self.outer.inside(x, y) && !self.inner.inside(x, y)
+    }
+
+    /// Approximate signed distance of a point to the outline of the lune (negative inside)
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        self.outer
+            .signed_distance(x, y)
+            .max(-self.inner.signed_distance(x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Canvas, Lune};
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_lune_area() {
+        // disjoint circles
+        assert_abs_diff_eq!(Lune::new(0.0, 0.0, 0.5, 2.0, 0.0, 0.5).area(), PI * 0.25);
+        // inner circle contains the outer circle
+        assert_abs_diff_eq!(Lune::new(0.0, 0.0, 0.5, 0.1, 0.0, 0.7).area(), 0.0);
+        // outer circle contains the inner circle
+        assert_abs_diff_eq!(
+            Lune::new(0.0, 0.0, 0.5, 0.1, 0.0, 0.2).area(),
+            PI * (0.25 - 0.04),
+            epsilon = 1e-12
+        );
+        // Two unit circles at distance 1 intersect in a lens of area 2 pi / 3 - sqrt(3) / 2
+        assert_abs_diff_eq!(
+            Lune::new(0.0, 0.0, 1.0, 1.0, 0.0, 1.0).area(),
+            PI - (2.0 * PI / 3.0 - 3f64.sqrt() / 2.0),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_lune_inside() {
+        // Outer circle centered at the origin, inner circle shifted to the right such that a
+        // crescent remains on the left.
+        let lune = Lune::new(0.0, 0.0, 0.5, 0.25, 0.0, 0.5).on_canvas(&Canvas::new(128, 128));
+
+        // Crescent on the left side (pixel coordinates, the canvas center is at 64)
+        assert!(lune.inside(64.0 - 28.0, 64.0));
+        assert!(lune.inside(64.0 - 20.0, 64.0 + 10.0));
+
+        // Overlap of both circles
+        assert!(!lune.inside(64.0, 64.0));
+        assert!(!lune.inside(64.0 + 20.0, 64.0));
+
+        // Inside the inner circle only
+        assert!(!lune.inside(64.0 + 40.0, 64.0));
+
+        // Outside of both circles
+        assert!(!lune.inside(2.0, 2.0));
+
+        assert_eq!(
+            lune.bounding_box(),
+            Lune::new(0.0, 0.0, 0.5, 0.25, 0.0, 0.5)
+                .outer
+                .on_canvas(&Canvas::new(128, 128))
+                .bounding_box()
+        );
+    }
+
+    #[test]
+    fn test_lune_perimeter() {
+        // disjoint circles
+        assert_abs_diff_eq!(Lune::new(0.0, 0.0, 0.5, 2.0, 0.0, 0.5).perimeter(), PI);
+        // inner circle contains the outer circle
+        assert_abs_diff_eq!(Lune::new(0.0, 0.0, 0.5, 0.1, 0.0, 0.7).perimeter(), 0.0);
+        // outer circle contains the inner circle
+        assert_abs_diff_eq!(
+            Lune::new(0.0, 0.0, 0.5, 0.1, 0.0, 0.2).perimeter(),
+            2.0 * PI * 0.7,
+            epsilon = 1e-12
+        );
+        // Two unit circles at distance 1: Two thirds of the outer circle remain and one third of
+        // the inner circle bounds the lune
+        assert_abs_diff_eq!(
+            Lune::new(0.0, 0.0, 1.0, 1.0, 0.0, 1.0).perimeter(),
+            2.0 * PI,
+            epsilon = 1e-12
+        );
+    }
+}
@@ -3,30 +3,126 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
+use std::hash::{Hash, Hasher};
+
+use crate::Error;
+
 mod boundingbox;
+mod canvas;
 mod ellipse;
+mod lune;
+mod polygon;
+mod radon;
 mod rectangle;
+mod svg;
 
-use boundingbox::BoundingBox;
+pub(crate) use boundingbox::BoundingBox;
+pub(crate) use canvas::Canvas;
 use ellipse::{Ellipse, EllipseOnCanvas};
-use rectangle::{Rectangle, RectangleOnCanvas};
+use lune::{Lune, LuneOnCanvas};
+use polygon::{Polygon, PolygonOnCanvas};
+pub use radon::sinogram;
+use rectangle::{AxisAlignedRectangleOnCanvas, Rectangle, RectangleOnCanvas};
+pub use svg::phantom_to_svg;
 
 /// Representation of a shape.
 ///
 /// A shape is defined on a 2D area where both x- and y-direction are in [-1.0, 1.0].
 /// The shape will later be scaled onto the actual canvas given by the desired dimensions of the
 /// phantom.
-#[derive(Clone, PartialEq, Debug)]
+///
+/// Shapes are compared and hashed based on the bit patterns of their parameters, which allows
+/// using them in a `HashSet` or as keys of a `HashMap`. Note that therefore, in contrast to the
+/// comparison of `f64`, a shape with a `NaN` parameter is equal to itself and `0.0` is not equal
+/// to `-0.0`.
+///
+/// With the `serde` feature, shapes can be serialized and deserialized. The parameters of a shape
+/// are stored as named fields, e.g. an ellipse as
+/// `{"intensity": 1.0, "kind": {"ellipse": {"center_x": 0.0, "center_y": 0.0, "major_axis": 0.5, "minor_axis": 0.25, "theta": 0.0}}}`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Shape {
     intensity: f64,
     kind: ShapeKind,
 }
 
+/// The type of a shape
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShapeType {
+    /// Ellipse, see `Shape::ellipse`
+    Ellipse,
+    /// Rectangle, see `Shape::rectangle`
+    Rectangle,
+    /// Lune, see `Shape::lune`
+    Lune,
+    /// Convex polygon, see `Shape::polygon` and `Shape::triangle`
+    Polygon,
+    /// Group of shapes, see `Shape::group`
+    Group,
+}
+
 /// Represents the kind of shape
-#[derive(PartialEq, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 enum ShapeKind {
     Ellipse(Ellipse),
     Rectangle(Rectangle),
+    Lune(Lune),
+    Polygon(Polygon),
+    Group(Vec<Shape>),
+}
+
+impl PartialEq for Shape {
+    fn eq(&self, other: &Self) -> bool {
+        self.intensity.to_bits() == other.intensity.to_bits() && self.kind == other.kind
+    }
+}
+
+impl Eq for Shape {}
+
+impl Hash for Shape {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.intensity.to_bits().hash(state);
+        self.kind.hash(state);
+    }
+}
+
+impl PartialEq for ShapeKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ShapeKind::Ellipse(a), ShapeKind::Ellipse(b)) => a.to_bits() == b.to_bits(),
+            (ShapeKind::Rectangle(a), ShapeKind::Rectangle(b)) => a.to_bits() == b.to_bits(),
+            (ShapeKind::Lune(a), ShapeKind::Lune(b)) => {
+                a.outer.to_bits() == b.outer.to_bits() && a.inner.to_bits() == b.inner.to_bits()
+            }
+            (ShapeKind::Polygon(a), ShapeKind::Polygon(b)) => a.to_bits() == b.to_bits(),
+            (ShapeKind::Group(a), ShapeKind::Group(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ShapeKind {}
+
+impl Hash for ShapeKind {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            ShapeKind::Ellipse(shape) => shape.to_bits().hash(state),
+            ShapeKind::Rectangle(shape) => shape.to_bits().hash(state),
+            ShapeKind::Lune(shape) => {
+                shape.outer.to_bits().hash(state);
+                shape.inner.to_bits().hash(state);
+            }
+            ShapeKind::Polygon(shape) => shape.to_bits().hash(state),
+            ShapeKind::Group(shapes) => shapes.hash(state),
+        }
+    }
 }
 
 impl Shape {
@@ -64,6 +160,27 @@ impl Shape {
         }
     }
 
+    /// Create a circle
+    ///
+    /// This is an ellipse with both axes equal to `radius` and no rotation.
+    ///
+    /// # Parameters
+    ///
+    /// * `center_x`: x component of center on the canvas
+    /// * `center_y`: y component of center on the canvas
+    /// * `radius`: radius of the circle
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let circle = Shape::circle(0.1, -0.4, 0.3, 1.0);
+    /// assert_eq!(circle, Shape::ellipse(0.1, -0.4, 0.3, 0.3, 0.0, 1.0));
+    /// ```
+    pub fn circle(center_x: f64, center_y: f64, radius: f64, intensity: f64) -> Shape {
+        Shape::ellipse(center_x, center_y, radius, radius, 0.0, intensity)
+    }
+
     /// Create a rectangle
     ///
     /// The canvas for defining rectangles is square and ranges from -1 to 1 on both axes `x` and `y`.
@@ -96,22 +213,705 @@ impl Shape {
         }
     }
 
+    /// Create a lune (crescent)
+    ///
+    /// A lune is the region which is inside of one circle but outside of another circle.
+    /// The canvas for defining lunes is square and ranges from -1 to 1 on both axes `x` and `y`.
+    ///
+    /// # Parameters
+    ///
+    /// * `center_x`: x component of center of the outer circle on the canvas
+    /// * `center_y`: y component of center of the outer circle on the canvas
+    /// * `radius`: radius of the outer circle
+    /// * `cut_center_x`: x component of center of the circle which is cut out
+    /// * `cut_center_y`: y component of center of the circle which is cut out
+    /// * `cut_radius`: radius of the circle which is cut out
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let lune = Shape::lune(0.0, 0.0, 0.5, 0.2, 0.0, 0.45, 1.0);
+    /// ```
+    pub fn lune(
+        center_x: f64,
+        center_y: f64,
+        radius: f64,
+        cut_center_x: f64,
+        cut_center_y: f64,
+        cut_radius: f64,
+        intensity: f64,
+    ) -> Shape {
+        Shape {
+            intensity,
+            kind: ShapeKind::Lune(Lune::new(
+                center_x,
+                center_y,
+                radius,
+                cut_center_x,
+                cut_center_y,
+                cut_radius,
+            )),
+        }
+    }
+
+    /// Create an annulus (ring)
+    ///
+    /// An annulus is the region which is inside of the outer circle but outside of the concentric
+    /// inner circle. It is represented as a lune whose circles share their center, therefore its
+    /// shape type is `ShapeType::Lune`.
+    /// The canvas for defining annuli is square and ranges from -1 to 1 on both axes `x` and `y`.
+    ///
+    /// # Parameters
+    ///
+    /// * `center_x`: x component of center of the annulus on the canvas
+    /// * `center_y`: y component of center of the annulus on the canvas
+    /// * `inner_radius`: radius of the inner circle
+    /// * `outer_radius`: radius of the outer circle
+    ///
+    /// Returns `Error::InvalidParameter` unless `0 <= inner_radius < outer_radius`. Equal radii
+    /// are rejected because such an annulus would not cover any point. An inner radius of zero
+    /// results in a full disk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let ring = Shape::annulus(0.0, 0.0, 0.4, 0.5, 1.0).unwrap();
+    /// assert!(ring.contains(64, 64, 32 + 14, 32));
+    /// assert!(!ring.contains(64, 64, 32, 32));
+    /// assert!(Shape::annulus(0.0, 0.0, 0.5, 0.5, 1.0).is_err());
+    /// ```
+    pub fn annulus(
+        center_x: f64,
+        center_y: f64,
+        inner_radius: f64,
+        outer_radius: f64,
+        intensity: f64,
+    ) -> Result<Shape, Error> {
+        // Also rejects `NaN`
+        if !(0.0..outer_radius).contains(&inner_radius) {
+            return Err(Error::InvalidParameter(
+                "annulus requires 0 <= inner_radius < outer_radius",
+            ));
+        }
+        Ok(Shape::lune(
+            center_x,
+            center_y,
+            outer_radius,
+            center_x,
+            center_y,
+            inner_radius,
+            intensity,
+        ))
+    }
+
+    /// Create a convex polygon
+    ///
+    /// The canvas for defining polygons is square and ranges from -1 to 1 on both axes `x` and `y`.
+    ///
+    /// # Parameters
+    ///
+    /// * `vertices`: vertices of the polygon in clockwise or counterclockwise order
+    /// * `theta`: Rotation angle of the polygon about the mean of its vertices in degrees
+    ///
+    /// Returns `Error::InvalidParameter` if there are less than three vertices, if the polygon has
+    /// no area or if it is not convex. Non-convex shapes can be assembled from convex polygons
+    /// with `Shape::group`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let hexagon = (0..6)
+    ///     .map(|i| {
+    ///         let angle = f64::from(i) * std::f64::consts::PI / 3.0;
+    ///         (0.5 * angle.cos(), 0.5 * angle.sin())
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// let hexagon = Shape::polygon(&hexagon, 0.0, 1.0).unwrap();
+    /// assert!(Shape::polygon(&[(0.0, 0.0), (1.0, 0.0)], 0.0, 1.0).is_err());
+    /// ```
+    pub fn polygon(vertices: &[(f64, f64)], theta: f64, intensity: f64) -> Result<Shape, Error> {
+        Ok(Shape {
+            intensity,
+            kind: ShapeKind::Polygon(Polygon::new(vertices, theta)?),
+        })
+    }
+
+    /// Create a triangle
+    ///
+    /// The canvas for defining triangles is square and ranges from -1 to 1 on both axes `x` and
+    /// `y`. The vertices may be given in clockwise or counterclockwise order. This is the same
+    /// as `Shape::polygon(&[p0, p1, p2], 0.0, intensity)`, therefore `Error::InvalidParameter`
+    /// is returned if the vertices are collinear (or not finite), since the triangle has no area
+    /// then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let triangle = Shape::triangle((-0.5, -0.5), (0.5, -0.5), (0.0, 0.5), 1.0).unwrap();
+    /// assert!(triangle.contains(64, 64, 32, 32));
+    /// assert!(!triangle.contains(64, 64, 10, 60));
+    /// assert!(Shape::triangle((0.0, 0.0), (0.5, 0.5), (1.0, 1.0), 1.0).is_err());
+    /// ```
+    pub fn triangle(
+        p0: (f64, f64),
+        p1: (f64, f64),
+        p2: (f64, f64),
+        intensity: f64,
+    ) -> Result<Shape, Error> {
+        Shape::polygon(&[p0, p1, p2], 0.0, intensity)
+    }
+
+    /// Create a group of shapes
+    ///
+    /// The group covers the union of its members and adds `intensity` exactly once to every
+    /// covered pixel, even where members overlap. The intensities of the members are ignored.
+    /// This allows building a single object out of several overlapping primitives. A group
+    /// without members does not cover any pixel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let group = Shape::group(
+    ///     vec![
+    ///         Shape::ellipse(-0.2, 0.0, 0.3, 0.3, 0.0, 0.0),
+    ///         Shape::ellipse(0.2, 0.0, 0.3, 0.3, 0.0, 0.0),
+    ///     ],
+    ///     1.0,
+    /// );
+    /// ```
+    pub fn group(shapes: Vec<Shape>, intensity: f64) -> Shape {
+        Shape {
+            intensity,
+            kind: ShapeKind::Group(shapes),
+        }
+    }
+
+    /// Create ellipses from a table of parameters
+    ///
+    /// Each row is `[center_x, center_y, major_axis, minor_axis, theta, intensity]`, i.e. the
+    /// parameters of `Shape::ellipse` in the same order, with `theta` in degrees. This allows
+    /// using published phantom tables directly. Note that tables in the format of MATLAB's
+    /// `phantom` start with the intensity and need to be reordered accordingly.
+    ///
+    /// ```
+    /// # use shepplogan::{Phantom, Shape};
+    /// let shapes = Shape::ellipses_from_rows(&[
+    ///     [0.0, 0.0, 0.69, 0.92, 0.0, 1.0],
+    ///     [0.0, -0.0184, 0.6624, 0.874, 0.0, -0.8],
+    /// ]);
+    /// let phantom = Phantom::new(64, 64, &shapes);
+    /// ```
+    pub fn ellipses_from_rows(rows: &[[f64; 6]]) -> Vec<Shape> {
+        rows.iter()
+            .map(
+                |&[center_x, center_y, major_axis, minor_axis, theta, intensity]| {
+                    Shape::ellipse(center_x, center_y, major_axis, minor_axis, theta, intensity)
+                },
+            )
+            .collect()
+    }
+
+    /// Create the axis-aligned ellipse inscribed into a box of pixels of a phantom with
+    /// dimensions `nx` and `ny`
+    ///
+    /// The box is given as `(x_low, x_high, y_low, y_high)`, where both bounds are inclusive and
+    /// `(0, 0)` is the lower left corner of the phantom. The ellipse touches the pixels at the
+    /// centers of the four edges of the box, therefore rendering it with `Phantom::new` covers
+    /// exactly the rows and columns of the box (up to rounding errors). This allows specifying
+    /// shapes by pixel regions instead of canvas coordinates. Note that an ellipse in a box which
+    /// is only one or two pixels wide may not cover any pixel at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let ellipse = Shape::ellipse_in_box((10, 50, 20, 30), 64, 64, 1.0);
+    /// assert!(ellipse.contains(64, 64, 30, 25));
+    /// assert!(!ellipse.contains(64, 64, 9, 25));
+    /// ```
+    pub fn ellipse_in_box(bbox: (u32, u32, u32, u32), nx: u32, ny: u32, intensity: f64) -> Shape {
+        let (x_low, x_high, y_low, y_high) = bbox;
+        let canvas = Canvas::new(nx, ny);
+        let (x_low, y_low) = canvas.unmap(f64::from(x_low), f64::from(y_low));
+        let (x_high, y_high) = canvas.unmap(f64::from(x_high), f64::from(y_high));
+        Shape::ellipse(
+            (x_low + x_high) / 2.0,
+            (y_low + y_high) / 2.0,
+            (x_high - x_low) / 2.0,
+            (y_high - y_low) / 2.0,
+            0.0,
+            intensity,
+        )
+    }
+
+    /// Returns the intensity of the shape.
+    pub fn intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// Returns the type of the shape.
+    pub fn shape_type(&self) -> ShapeType {
+        match self.kind {
+            ShapeKind::Ellipse(_) => ShapeType::Ellipse,
+            ShapeKind::Rectangle(_) => ShapeType::Rectangle,
+            ShapeKind::Lune(_) => ShapeType::Lune,
+            ShapeKind::Polygon(_) => ShapeType::Polygon,
+            ShapeKind::Group(_) => ShapeType::Group,
+        }
+    }
+
+    /// Returns `true` if the shape is an ellipse.
+    pub fn is_ellipse(&self) -> bool {
+        self.shape_type() == ShapeType::Ellipse
+    }
+
+    /// Returns `true` if the shape is a rectangle.
+    pub fn is_rectangle(&self) -> bool {
+        self.shape_type() == ShapeType::Rectangle
+    }
+
+    /// Estimates the fraction of a phantom of size `nx` times `ny` which is covered by the shape.
+    ///
+    /// For ellipses, rectangles, lunes and polygons, the analytic area of the shape after scaling
+    /// it onto the phantom is divided by the number of pixels `nx * ny`, which is much cheaper than
+    /// rendering the shape. The analytic area does not account for parts of the shape which lie
+    /// outside of the phantom, therefore the result may exceed `1.0` for large shapes. Since the
+    /// members of a group may overlap, the area of a group is determined by counting the pixels it
+    /// covers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let square = Shape::rectangle(0.0, 0.0, 1.0, 1.0, 0.0, 1.0);
+    /// assert_eq!(square.area_fraction(128, 128), 0.25);
+    /// ```
+    pub fn area_fraction(&self, nx: u32, ny: u32) -> f64 {
+        let canvas = Canvas::new(nx, ny);
+        let area = match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.area() * canvas.scale.powi(2),
+            ShapeKind::Rectangle(shape) => shape.area() * canvas.scale.powi(2),
+            ShapeKind::Lune(shape) => shape.area() * canvas.scale.powi(2),
+            ShapeKind::Polygon(shape) => shape.area() * canvas.scale.powi(2),
+            ShapeKind::Group(_) => {
+                let shape = self.on_canvas_with(&canvas);
+                let bbox = shape.bounding_box();
+                let mut count = 0;
+                for x in bbox.x_low..=bbox.x_high {
+                    for y in bbox.y_low..=bbox.y_high {
+                        count += usize::from(shape.inside_pixel(x, y));
+                    }
+                }
+                count as f64
+            }
+        };
+        area / (f64::from(nx) * f64::from(ny))
+    }
+
+    /// Returns the perimeter of the shape in pixels after scaling it onto a phantom of size `nx`
+    /// times `ny`.
+    ///
+    /// The perimeter of rectangles, lunes and polygons is exact. For ellipses, Ramanujan's second
+    /// approximation `π (a + b) (1 + 3h / (10 + sqrt(4 - 3h)))` with `h = (a - b)² / (a + b)²` is
+    /// used, which is exact for circles and has a relative error below `0.05 %` (the largest
+    /// error occurs for degenerate ellipses). The perimeter of a group is the sum of the
+    /// perimeters of its members, which overestimates the perimeter of overlapping members. As
+    /// for `area_fraction`, parts of the shape outside of the phantom are not accounted for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let square = Shape::rectangle(0.0, 0.0, 1.0, 1.0, 0.0, 1.0);
+    /// assert_eq!(square.perimeter(128, 128), 256.0);
+    /// ```
+    pub fn perimeter(&self, nx: u32, ny: u32) -> f64 {
+        let canvas = Canvas::new(nx, ny);
+        match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.perimeter() * canvas.scale,
+            ShapeKind::Rectangle(shape) => shape.perimeter() * canvas.scale,
+            ShapeKind::Lune(shape) => shape.perimeter() * canvas.scale,
+            ShapeKind::Polygon(shape) => shape.perimeter() * canvas.scale,
+            ShapeKind::Group(shapes) => shapes.iter().map(|shape| shape.perimeter(nx, ny)).sum(),
+        }
+    }
+
+    /// Returns `true` if the shape lies entirely inside of the canonical domain `[-1, 1] x [-1, 1]`.
+    ///
+    /// Parts of a shape outside of this domain may be clipped when the shape is rendered, which
+    /// is often caused by a wrong parameter. This checks the bounding box of the shape, therefore
+    /// a lune is checked based on its outer circle and a group is within the domain if all of its
+    /// members are. Shapes touching the boundary of the domain are within the domain.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// assert!(Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0).is_within_canonical_domain());
+    /// assert!(!Shape::ellipse(0.5, 0.0, 0.69, 0.92, 0.0, 2.0).is_within_canonical_domain());
+    /// ```
+    pub fn is_within_canonical_domain(&self) -> bool {
+        let within = |(x_min, x_max, y_min, y_max): (f64, f64, f64, f64)| {
+            -1.0 <= x_min && x_max <= 1.0 && -1.0 <= y_min && y_max <= 1.0
+        };
+        match &self.kind {
+            ShapeKind::Ellipse(shape) => within(shape.bounds()),
+            ShapeKind::Rectangle(shape) => within(shape.bounds()),
+            ShapeKind::Lune(shape) => within(shape.outer.bounds()),
+            ShapeKind::Polygon(shape) => within(shape.bounds()),
+            ShapeKind::Group(shapes) => shapes.iter().all(Shape::is_within_canonical_domain),
+        }
+    }
+
+    /// Returns the shape adjusted to lie within the canonical domain `[-1, 1]` on both axes, or
+    /// `None` if it lies entirely outside of it.
+    ///
+    /// Shapes which are already within the domain (see `is_within_canonical_domain`) are returned
+    /// unchanged. Axis aligned rectangles are cut to their intersection with the domain. All other
+    /// shapes keep their orientation and proportions: They are scaled down and moved such that
+    /// their bounding box fits into the intersection of their original bounding box with the
+    /// domain. The shape is considered to be outside of the domain if this intersection is empty.
+    /// Members of groups are clipped individually, members outside of the domain are dropped.
+    /// Intensities are preserved.
+    ///
+    /// Note that rotated rectangles are shrunk rather than cut, since the cut would not be a
+    /// rectangle anymore.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let clipped = Shape::ellipse(0.0, 0.0, 1.5, 0.5, 0.0, 1.0)
+    ///     .clip_to_canonical()
+    ///     .unwrap();
+    /// assert!(clipped.is_within_canonical_domain());
+    /// assert!(Shape::ellipse(3.0, 0.0, 0.5, 0.5, 0.0, 1.0)
+    ///     .clip_to_canonical()
+    ///     .is_none());
+    /// ```
+    pub fn clip_to_canonical(&self) -> Option<Shape> {
+        if self.is_within_canonical_domain() {
+            return Some(self.clone());
+        }
+        let fit_ellipse = |ellipse: &Ellipse, fit: &Fit| Ellipse {
+            center_x: fit.to.0 + fit.factor * (ellipse.center_x - fit.from.0),
+            center_y: fit.to.1 + fit.factor * (ellipse.center_y - fit.from.1),
+            major_axis: fit.factor * ellipse.major_axis,
+            minor_axis: fit.factor * ellipse.minor_axis,
+            theta: ellipse.theta,
+        };
+        let kind = match &self.kind {
+            ShapeKind::Ellipse(shape) => {
+                ShapeKind::Ellipse(fit_ellipse(shape, &Fit::new(shape.bounds())?))
+            }
+            ShapeKind::Rectangle(shape) => {
+                let fit = Fit::new(shape.bounds())?;
+                let (width, height) = if shape.theta == 0.0 {
+                    // The intersection with the domain is a rectangle as well
+                    (fit.clipped.1 - fit.clipped.0, fit.clipped.3 - fit.clipped.2)
+                } else {
+                    (fit.factor * shape.width, fit.factor * shape.height)
+                };
+                ShapeKind::Rectangle(Rectangle::new(
+                    fit.to.0,
+                    fit.to.1,
+                    width,
+                    height,
+                    shape.theta,
+                ))
+            }
+            ShapeKind::Lune(shape) => {
+                let fit = Fit::new(shape.outer.bounds())?;
+                ShapeKind::Lune(Lune {
+                    outer: fit_ellipse(&shape.outer, &fit),
+                    inner: fit_ellipse(&shape.inner, &fit),
+                })
+            }
+            ShapeKind::Polygon(shape) => {
+                let fit = Fit::new(shape.bounds())?;
+                ShapeKind::Polygon(shape.map(|(x, y)| {
+                    (
+                        fit.to.0 + fit.factor * (x - fit.from.0),
+                        fit.to.1 + fit.factor * (y - fit.from.1),
+                    )
+                }))
+            }
+            ShapeKind::Group(shapes) => {
+                let shapes = shapes
+                    .iter()
+                    .filter_map(Shape::clip_to_canonical)
+                    .collect::<Vec<_>>();
+                if shapes.is_empty() {
+                    return None;
+                }
+                ShapeKind::Group(shapes)
+            }
+        };
+        Some(Shape {
+            intensity: self.intensity,
+            kind,
+        })
+    }
+
+    /// Checks whether the shape equals `other` up to a tolerance.
+    ///
+    /// In contrast to `==`, which compares the bit patterns of the parameters, two shapes are
+    /// approximately equal if they are of the same kind and all of their parameters (including the
+    /// intensities) differ by at most `tol`. Groups are approximately equal if they have the same
+    /// number of members and the members are pairwise approximately equal in order. Parameters
+    /// which are `NaN` are never approximately equal. This is useful for comparing shapes which
+    /// went through parsing or arithmetic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let a = Shape::ellipse(0.1, 0.2, 0.3, 0.4, 10.0, 1.0);
+    /// let b = Shape::ellipse(0.1 + 1e-12, 0.2, 0.3, 0.4, 10.0, 1.0);
+    /// assert_ne!(a, b);
+    /// assert!(a.approx_eq(&b, 1e-9));
+    /// ```
+    pub fn approx_eq(&self, other: &Shape, tol: f64) -> bool {
+        let close =
+            |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() <= tol);
+        let kinds = match (&self.kind, &other.kind) {
+            (ShapeKind::Ellipse(a), ShapeKind::Ellipse(b)) => close(&a.params(), &b.params()),
+            (ShapeKind::Rectangle(a), ShapeKind::Rectangle(b)) => close(&a.params(), &b.params()),
+            (ShapeKind::Lune(a), ShapeKind::Lune(b)) => {
+                close(&a.outer.params(), &b.outer.params())
+                    && close(&a.inner.params(), &b.inner.params())
+            }
+            (ShapeKind::Polygon(a), ShapeKind::Polygon(b)) => {
+                a.vertices.len() == b.vertices.len() && close(&a.params(), &b.params())
+            }
+            (ShapeKind::Group(a), ShapeKind::Group(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.approx_eq(b, tol))
+            }
+            _ => false,
+        };
+        kinds && close(&[self.intensity], &[other.intensity])
+    }
+
+    /// Grows or shrinks the shape uniformly by `factor` about its center.
+    ///
+    /// The axes of ellipses and the width and height of rectangles are multiplied by `factor`,
+    /// while the center and the rotation are kept. Polygons are scaled about the mean of their
+    /// vertices. Lunes are scaled about the center of the outer circle and groups about the center
+    /// of their bounding box, such that the positions of the inner circle and the members are
+    /// scaled as well. In contrast to `Phantom::scale`, which scales the intensities, this changes
+    /// the spatial extent of the shape. The intensity is preserved. `factor` is expected to be
+    /// positive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let circle = Shape::ellipse(0.2, 0.1, 0.25, 0.25, 0.0, 1.0);
+    /// assert_eq!(
+    ///     circle.scale_geometry(2.0),
+    ///     Shape::ellipse(0.2, 0.1, 0.5, 0.5, 0.0, 1.0)
+    /// );
+    /// ```
+    pub fn scale_geometry(self, factor: f64) -> Shape {
+        let (x_min, x_max, y_min, y_max) = self.bounds();
+        let center = match &self.kind {
+            ShapeKind::Ellipse(shape) => (shape.center_x, shape.center_y),
+            ShapeKind::Rectangle(shape) => (shape.center_x, shape.center_y),
+            ShapeKind::Lune(shape) => (shape.outer.center_x, shape.outer.center_y),
+            ShapeKind::Polygon(shape) => shape.center(),
+            ShapeKind::Group(_) => ((x_min + x_max) / 2.0, (y_min + y_max) / 2.0),
+        };
+        self.scale_about(factor, center)
+    }
+
+    /// Scales the shape by `factor` about the point `(x, y)`.
+    fn scale_about(self, factor: f64, (x, y): (f64, f64)) -> Shape {
+        let scale_ellipse = |ellipse: Ellipse| Ellipse {
+            center_x: x + factor * (ellipse.center_x - x),
+            center_y: y + factor * (ellipse.center_y - y),
+            major_axis: factor * ellipse.major_axis,
+            minor_axis: factor * ellipse.minor_axis,
+            theta: ellipse.theta,
+        };
+        let kind = match self.kind {
+            ShapeKind::Ellipse(shape) => ShapeKind::Ellipse(scale_ellipse(shape)),
+            ShapeKind::Rectangle(shape) => ShapeKind::Rectangle(Rectangle::new(
+                x + factor * (shape.center_x - x),
+                y + factor * (shape.center_y - y),
+                factor * shape.width,
+                factor * shape.height,
+                shape.theta,
+            )),
+            ShapeKind::Lune(shape) => ShapeKind::Lune(Lune {
+                outer: scale_ellipse(shape.outer),
+                inner: scale_ellipse(shape.inner),
+            }),
+            ShapeKind::Polygon(shape) => ShapeKind::Polygon(
+                shape.map(|(px, py)| (x + factor * (px - x), y + factor * (py - y))),
+            ),
+            ShapeKind::Group(shapes) => ShapeKind::Group(
+                shapes
+                    .into_iter()
+                    .map(|shape| shape.scale_about(factor, (x, y)))
+                    .collect(),
+            ),
+        };
+        Shape {
+            intensity: self.intensity,
+            kind,
+        }
+    }
+
+    /// Returns the bounds `(x_min, x_max, y_min, y_max)` of the shape in shape coordinates.
+    ///
+    /// The bounds of an empty group are infinite with `x_min > x_max` and `y_min > y_max`.
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.bounds(),
+            ShapeKind::Rectangle(shape) => shape.bounds(),
+            ShapeKind::Lune(shape) => shape.outer.bounds(),
+            ShapeKind::Polygon(shape) => shape.bounds(),
+            ShapeKind::Group(shapes) => shapes.iter().map(Shape::bounds).fold(
+                (
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                ),
+                |a, b| (a.0.min(b.0), a.1.max(b.1), a.2.min(b.2), a.3.max(b.3)),
+            ),
+        }
+    }
+
+    /// Returns `true` if all parameters of the shape (including the intensity) are finite.
+    pub(crate) fn is_finite(&self) -> bool {
+        let params_finite = match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.is_finite(),
+            ShapeKind::Rectangle(shape) => shape.is_finite(),
+            ShapeKind::Lune(shape) => shape.outer.is_finite() && shape.inner.is_finite(),
+            ShapeKind::Polygon(shape) => shape.is_finite(),
+            ShapeKind::Group(shapes) => shapes.iter().all(Shape::is_finite),
+        };
+        params_finite && self.intensity.is_finite()
+    }
+
+    /// Checks whether pixel `(x, y)` of a phantom with dimensions `nx` and `ny` is covered by the
+    /// shape.
+    ///
+    /// The pixel is sampled at the same position which is used when rendering a phantom, therefore
+    /// this is consistent with the output of `Phantom::new`. As for the shapes, `(0, 0)` is the
+    /// lower left corner of the phantom.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let circle = Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0);
+    /// assert!(circle.contains(128, 128, 64, 64));
+    /// assert!(!circle.contains(128, 128, 0, 0));
+    /// ```
+    pub fn contains(&self, nx: u32, ny: u32, x: u32, y: u32) -> bool {
+        self.on_canvas(nx, ny).inside(f64::from(x), f64::from(y))
+    }
+
+    /// Checks whether the shape and `other` cover a common pixel of a phantom with dimensions `nx`
+    /// and `ny`.
+    ///
+    /// In contrast to comparing the bounding boxes, this is exact at the given resolution: All
+    /// pixels in the intersection of both bounding boxes are tested with `contains`. Therefore
+    /// shapes which touch or overlap by less than a pixel may not share a pixel, and pixels which
+    /// are sampled exactly on the outline of both shapes count as shared.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let a = Shape::ellipse(-0.2, 0.0, 0.3, 0.3, 0.0, 1.0);
+    /// let b = Shape::ellipse(0.2, 0.0, 0.3, 0.3, 0.0, 1.0);
+    /// let c = Shape::ellipse(0.8, 0.0, 0.2, 0.2, 0.0, 1.0);
+    /// assert!(a.overlaps(&b, 128, 128));
+    /// assert!(!a.overlaps(&c, 128, 128));
+    /// ```
+    pub fn overlaps(&self, other: &Shape, nx: u32, ny: u32) -> bool {
+        let (a, b) = (self.on_canvas(nx, ny), other.on_canvas(nx, ny));
+        let bbox = a.bounding_box().intersection(&b.bounding_box());
+        if bbox.is_empty() {
+            return false;
+        }
+        (bbox.x_low..=bbox.x_high).any(|x| {
+            (bbox.y_low..=bbox.y_high).any(|y| a.inside_pixel(x, y) && b.inside_pixel(x, y))
+        })
+    }
+
     /// Transforms the shape onto the canvas size given by the dimensions `nx` and `ny` of the final
     /// phantom.
     pub(crate) fn on_canvas(&self, nx: u32, ny: u32) -> ShapeOnCanvas {
+        self.on_canvas_with(&Canvas::new(nx, ny))
+    }
+
+    /// Transforms the shape onto the given `canvas`.
+    pub(crate) fn on_canvas_with(&self, canvas: &Canvas) -> ShapeOnCanvas {
         let Self { intensity, kind } = self;
         ShapeOnCanvas {
             intensity: *intensity,
             kind: match kind {
-                ShapeKind::Ellipse(shape) => ShapeKindOnCanvas::Ellipse(shape.on_canvas(nx, ny)),
+                ShapeKind::Ellipse(shape) => ShapeKindOnCanvas::Ellipse(shape.on_canvas(canvas)),
+                ShapeKind::Rectangle(shape) if shape.theta == 0.0 => {
+                    ShapeKindOnCanvas::AxisAlignedRectangle(shape.on_canvas_axis_aligned(canvas))
+                }
                 ShapeKind::Rectangle(shape) => {
-                    ShapeKindOnCanvas::Rectangle(shape.on_canvas(nx, ny))
+                    ShapeKindOnCanvas::Rectangle(shape.on_canvas(canvas))
                 }
+                ShapeKind::Lune(shape) => ShapeKindOnCanvas::Lune(shape.on_canvas(canvas)),
+                ShapeKind::Polygon(shape) => ShapeKindOnCanvas::Polygon(shape.on_canvas(canvas)),
+                ShapeKind::Group(shapes) => ShapeKindOnCanvas::Group(
+                    shapes
+                        .iter()
+                        .map(|shape| shape.on_canvas_with(canvas))
+                        .collect(),
+                ),
             },
         }
     }
 }
 
+/// Uniform scaling `p -> to + factor * (p - from)`, which maps a bounding box into its
+/// intersection with the canonical domain
+struct Fit {
+    factor: f64,
+    from: (f64, f64),
+    to: (f64, f64),
+    /// intersection `(x_min, x_max, y_min, y_max)` of the bounding box with the domain
+    clipped: (f64, f64, f64, f64),
+}
+
+impl Fit {
+    /// Returns `None` if the bounding box `(x_min, x_max, y_min, y_max)` does not intersect the
+    /// domain.
+    fn new((x_min, x_max, y_min, y_max): (f64, f64, f64, f64)) -> Option<Self> {
+        let (x_low, x_high) = (x_min.max(-1.0), x_max.min(1.0));
+        let (y_low, y_high) = (y_min.max(-1.0), y_max.min(1.0));
+        if !(x_low < x_high && y_low < y_high) {
+            return None;
+        }
+        Some(Fit {
+            factor: ((x_high - x_low) / (x_max - x_min)).min((y_high - y_low) / (y_max - y_min)),
+            from: ((x_min + x_max) / 2.0, (y_min + y_max) / 2.0),
+            to: ((x_low + x_high) / 2.0, (y_low + y_high) / 2.0),
+            clipped: (x_low, x_high, y_low, y_high),
+        })
+    }
+}
+
+/// Number of samples per axis used to approximate the coverage of a pixel by shapes other than
+/// ellipses
+const COVERAGE_SAMPLES: u32 = 16;
+
 /// A shape scaled onto a canvas given by the phantom dimensions
 #[derive(Clone, PartialEq, Debug)]
 pub(crate) struct ShapeOnCanvas {
@@ -124,6 +924,10 @@ pub(crate) struct ShapeOnCanvas {
 pub(crate) enum ShapeKindOnCanvas {
     Ellipse(EllipseOnCanvas),
     Rectangle(RectangleOnCanvas),
+    AxisAlignedRectangle(AxisAlignedRectangleOnCanvas),
+    Lune(LuneOnCanvas),
+    Polygon(PolygonOnCanvas),
+    Group(Vec<ShapeOnCanvas>),
 }
 
 impl ShapeOnCanvas {
@@ -133,6 +937,61 @@ impl ShapeOnCanvas {
         match &self.kind {
             ShapeKindOnCanvas::Ellipse(shape) => shape.inside(x, y),
             ShapeKindOnCanvas::Rectangle(shape) => shape.inside(x, y),
+            ShapeKindOnCanvas::AxisAlignedRectangle(shape) => shape.inside(x, y),
+            ShapeKindOnCanvas::Lune(shape) => shape.inside(x, y),
+            ShapeKindOnCanvas::Polygon(shape) => shape.inside(x, y),
+            ShapeKindOnCanvas::Group(shapes) => shapes.iter().any(|shape| shape.inside(x, y)),
+        }
+    }
+
+    /// Checks if pixel `(x, y)` is inside a shape
+    ///
+    /// This is equivalent to calling `inside` with the pixel coordinates, but allows shapes to
+    /// avoid floating point operations.
+    #[inline(always)]
+    pub(crate) fn inside_pixel(&self, x: u32, y: u32) -> bool {
+        match &self.kind {
+            ShapeKindOnCanvas::AxisAlignedRectangle(shape) => shape.inside_pixel(x, y),
+            ShapeKindOnCanvas::Group(shapes) => shapes.iter().any(|shape| shape.inside_pixel(x, y)),
+            _ => self.inside(f64::from(x), f64::from(y)),
+        }
+    }
+
+    /// Fraction of pixel `(x, y)` which is covered by the shape
+    ///
+    /// The pixel is the square `[x - 0.5, x + 0.5] x [y - 0.5, y + 0.5]` on the canvas. The
+    /// fraction is exact for ellipses and approximated with `COVERAGE_SAMPLES` times
+    /// `COVERAGE_SAMPLES` samples for the other shapes.
+    pub(crate) fn coverage(&self, x: u32, y: u32) -> f64 {
+        let (x, y) = (f64::from(x), f64::from(y));
+        match &self.kind {
+            ShapeKindOnCanvas::Ellipse(shape) => shape.coverage(x, y),
+            _ => {
+                let n = COVERAGE_SAMPLES;
+                let offset = |k: u32| (f64::from(k) + 0.5) / f64::from(n) - 0.5;
+                let count = (0..n)
+                    .flat_map(|k| (0..n).map(move |l| (offset(k), offset(l))))
+                    .filter(|&(dx, dy)| self.inside(x + dx, y + dy))
+                    .count();
+                count as f64 / f64::from(n * n)
+            }
+        }
+    }
+
+    /// Approximate signed distance of a point to the outline of the shape in pixels
+    ///
+    /// The distance is negative inside of the shape and only accurate close to the outline.
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        match &self.kind {
+            ShapeKindOnCanvas::Ellipse(shape) => shape.signed_distance(x, y),
+            ShapeKindOnCanvas::Rectangle(shape) => shape.signed_distance(x, y),
+            ShapeKindOnCanvas::AxisAlignedRectangle(shape) => shape.signed_distance(x, y),
+            ShapeKindOnCanvas::Lune(shape) => shape.signed_distance(x, y),
+            ShapeKindOnCanvas::Polygon(shape) => shape.signed_distance(x, y),
+            ShapeKindOnCanvas::Group(shapes) => shapes
+                .iter()
+                .map(|shape| shape.signed_distance(x, y))
+                .fold(f64::INFINITY, f64::min),
         }
     }
 
@@ -148,13 +1007,23 @@ impl ShapeOnCanvas {
         match &self.kind {
             ShapeKindOnCanvas::Ellipse(shape) => shape.bounding_box(),
             ShapeKindOnCanvas::Rectangle(shape) => shape.bounding_box(),
+            ShapeKindOnCanvas::AxisAlignedRectangle(shape) => shape.bounding_box(),
+            ShapeKindOnCanvas::Lune(shape) => shape.bounding_box(),
+            ShapeKindOnCanvas::Polygon(shape) => shape.bounding_box(),
+            ShapeKindOnCanvas::Group(shapes) => {
+                shapes.iter().fold(BoundingBox::empty(), |bbox, shape| {
+                    bbox.union(&shape.bounding_box())
+                })
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Shape, ShapeKind, ShapeKindOnCanvas};
+    use super::{Canvas, Shape, ShapeKind, ShapeKindOnCanvas, ShapeType};
+    use approx::assert_abs_diff_eq;
+    use quickcheck::TestResult;
 
     #[derive(Debug, Copy, Clone)]
     struct FloatLim(f64);
@@ -220,6 +1089,169 @@ mod tests {
         )
     }
 
+    #[quickcheck]
+    fn test_shape_circle(
+        center_x_input: FloatLim,
+        center_y_input: FloatLim,
+        radius_input: FloatLim,
+        intensity_input: FloatLim,
+    ) -> bool {
+        let center_x_input = center_x_input.0;
+        let center_y_input = center_y_input.0;
+        let radius_input = radius_input.0;
+        let intensity_input = intensity_input.0;
+        let Shape { intensity, kind } = Shape::circle(
+            center_x_input,
+            center_y_input,
+            radius_input,
+            intensity_input,
+        );
+
+        matches!(
+            kind,
+            ShapeKind::Ellipse(kind)
+            if kind.center_x.to_ne_bytes() == center_x_input.to_ne_bytes()
+                && kind.center_y.to_ne_bytes() == center_y_input.to_ne_bytes()
+                && kind.major_axis.to_ne_bytes() == radius_input.to_ne_bytes()
+                && kind.minor_axis.to_ne_bytes() == radius_input.to_ne_bytes()
+                && kind.theta.to_ne_bytes() == 0.0f64.to_ne_bytes()
+                && intensity.to_ne_bytes() == intensity_input.to_ne_bytes()
+        )
+    }
+
+    #[test]
+    fn test_shape_polygon() {
+        let (nx, ny) = (64, 64);
+        let square = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+        let polygon = Shape::polygon(&square, 0.0, 1.5).unwrap();
+        assert_eq!(polygon.shape_type(), ShapeType::Polygon);
+        assert_eq!(polygon.intensity(), 1.5);
+        assert!(polygon.is_finite());
+        assert!(Shape::polygon(&square[..2], 0.0, 1.0).is_err());
+
+        // Renders the same pixels as the equivalent rectangle
+        let rectangle = Shape::rectangle(0.0, 0.0, 1.0, 1.0, 0.0, 1.5);
+        for theta in [0.0, 30.0] {
+            let polygon = Shape::polygon(&square, theta, 1.5).unwrap();
+            let rectangle = Shape::rectangle(0.0, 0.0, 1.0, 1.0, theta, 1.5);
+            for x in 0..nx {
+                for y in 0..ny {
+                    assert_eq!(
+                        polygon.contains(nx, ny, x, y),
+                        rectangle.contains(nx, ny, x, y),
+                        "pixel ({}, {})",
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            polygon.area_fraction(nx, ny),
+            rectangle.area_fraction(nx, ny)
+        );
+        assert_eq!(polygon.perimeter(nx, ny), rectangle.perimeter(nx, ny));
+
+        // The rotation is about the mean of the vertices
+        let rotated = Shape::polygon(&square, 45.0, 1.0).unwrap();
+        assert!(rotated.is_within_canonical_domain());
+        let shifted = square.map(|(x, y)| (x + 0.5, y));
+        let shifted = Shape::polygon(&shifted, 45.0, 1.0).unwrap();
+        assert!(!shifted.is_within_canonical_domain());
+        let clipped = shifted.clip_to_canonical().unwrap();
+        assert!(clipped.is_within_canonical_domain());
+        let (x_min, x_max, y_min, y_max) = clipped.bounds();
+        assert_abs_diff_eq!(x_max, 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(x_max - x_min, y_max - y_min, epsilon = 1e-12);
+
+        let scaled = Shape::polygon(&square, 20.0, 1.0)
+            .unwrap()
+            .scale_geometry(0.5);
+        let expected = square.map(|(x, y)| (x / 2.0, y / 2.0));
+        assert_eq!(scaled, Shape::polygon(&expected, 20.0, 1.0).unwrap());
+        assert!(scaled.approx_eq(&Shape::polygon(&expected, 20.0, 1.0).unwrap(), 0.0));
+        assert!(!scaled.approx_eq(&Shape::polygon(&expected[..3], 20.0, 1.0).unwrap(), 1.0));
+        assert_ne!(scaled, rectangle);
+    }
+
+    #[test]
+    fn test_shape_triangle() {
+        let (nx, ny) = (64, 64);
+        let triangle = Shape::triangle((-0.5, -0.5), (0.5, -0.5), (0.0, 0.5), 1.5).unwrap();
+        assert_eq!(triangle.shape_type(), ShapeType::Polygon);
+        assert_eq!(triangle.intensity(), 1.5);
+        assert_eq!(
+            triangle,
+            Shape::polygon(&[(-0.5, -0.5), (0.5, -0.5), (0.0, 0.5)], 0.0, 1.5).unwrap()
+        );
+        assert_eq!(triangle.bounds(), (-0.5, 0.5, -0.5, 0.5));
+
+        // The order of the vertices does not matter
+        let clockwise = Shape::triangle((0.0, 0.5), (0.5, -0.5), (-0.5, -0.5), 1.5).unwrap();
+        for x in 0..nx {
+            for y in 0..ny {
+                assert_eq!(
+                    triangle.contains(nx, ny, x, y),
+                    clockwise.contains(nx, ny, x, y)
+                );
+            }
+        }
+
+        // The bounding box is clamped to the canvas
+        let large = Shape::triangle((-2.0, -2.0), (2.0, -2.0), (0.0, 2.0), 1.0).unwrap();
+        let bbox = large.on_canvas(nx, ny).bounding_box();
+        assert_eq!((bbox.x_low, bbox.x_high), (0, nx - 1));
+        assert_eq!((bbox.y_low, bbox.y_high), (0, ny - 1));
+
+        // Degenerate triangles are rejected as by `Shape::polygon`
+        assert!(Shape::triangle((0.0, 0.0), (0.5, 0.5), (-0.5, -0.5), 1.0).is_err());
+        assert!(Shape::triangle((0.2, 0.1), (0.2, 0.1), (0.5, 0.3), 1.0).is_err());
+        assert!(Shape::triangle((f64::NAN, 0.0), (0.5, 0.0), (0.0, 0.5), 1.0).is_err());
+    }
+
+    #[quickcheck]
+    // Add a reason why this lint is allowed once the feature `lint_reasons` is stabilized.
+    #[allow(clippy::too_many_arguments)]
+    fn test_shape_triangle_inside(
+        x0: FloatLim64,
+        y0: FloatLim64,
+        x1: FloatLim64,
+        y1: FloatLim64,
+        x2: FloatLim64,
+        y2: FloatLim64,
+        nx: u16,
+        ny: u16,
+    ) -> TestResult {
+        let (nx, ny) = (u32::from(nx), u32::from(ny));
+        let vertices = [(x0.0, y0.0), (x1.0, y1.0), (x2.0, y2.0)];
+        let canvas = Canvas::new(nx, ny);
+        let mapped = vertices.map(|(x, y)| canvas.map(x, y));
+        let (a, b, c) = (mapped[0], mapped[1], mapped[2]);
+        let area = ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)).abs();
+        let longest = [(a, b), (b, c), (c, a)]
+            .iter()
+            .map(|(p, q)| (q.0 - p.0).hypot(q.1 - p.1))
+            .fold(0.0, f64::max);
+        // Discard (nearly) degenerate triangles for which the centroid is on the boundary
+        if area <= 1e-6 * longest * longest {
+            return TestResult::discard();
+        }
+
+        let Ok(triangle) = Shape::triangle(vertices[0], vertices[1], vertices[2], 1.0) else {
+            return TestResult::failed();
+        };
+        let triangle = triangle.on_canvas(nx, ny);
+        let centroid = ((a.0 + b.0 + c.0) / 3.0, (a.1 + b.1 + c.1) / 3.0);
+        let x_max = a.0.max(b.0).max(c.0);
+        let y_min = a.1.min(b.1).min(c.1);
+        TestResult::from_bool(
+            triangle.inside(centroid.0, centroid.1)
+                && !triangle.inside(x_max + longest, centroid.1)
+                && !triangle.inside(centroid.0, y_min - longest)
+                && !triangle.inside(x_max + 1.0, y_min - 1.0),
+        )
+    }
+
     #[quickcheck]
     // Add a reason why this lint is allowed once the feature `lint_reasons` is stabilized.
     #[allow(clippy::too_many_arguments)]
@@ -362,9 +1394,12 @@ mod tests {
 
         matches!(
             rectangle_on_canvas.kind,
-            ShapeKindOnCanvas::Rectangle(_kind)
+            ShapeKindOnCanvas::Rectangle(_) | ShapeKindOnCanvas::AxisAlignedRectangle(_)
             if rectangle_on_canvas.intensity().to_ne_bytes() == intensity_input.to_ne_bytes()
-        )
+        ) && matches!(
+            rectangle_on_canvas.kind,
+            ShapeKindOnCanvas::AxisAlignedRectangle(_) if theta_input == 0.0
+        ) == (theta_input == 0.0)
     }
 
     #[quickcheck]
@@ -398,11 +1433,564 @@ mod tests {
         )
         .on_canvas(128, 128);
 
-        matches!(
-            rectangle_on_canvas.kind,
-            ShapeKindOnCanvas::Rectangle(kind)
-            if rectangle_on_canvas.bounding_box() == kind.bounding_box()
-                && rectangle_on_canvas.inside(x, y) == kind.inside(x, y)
+        match rectangle_on_canvas.kind {
+            ShapeKindOnCanvas::Rectangle(ref kind) => {
+                rectangle_on_canvas.bounding_box() == kind.bounding_box()
+                    && rectangle_on_canvas.inside(x, y) == kind.inside(x, y)
+            }
+            ShapeKindOnCanvas::AxisAlignedRectangle(ref kind) => {
+                rectangle_on_canvas.bounding_box() == kind.bounding_box()
+                    && rectangle_on_canvas.inside(x, y) == kind.inside(x, y)
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_shape_lune_on_canvas() {
+        let lune = Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 3.0);
+
+        assert!(matches!(
+            &lune.kind,
+            ShapeKind::Lune(kind)
+            if kind.outer.center_x.to_ne_bytes() == 0.0f64.to_ne_bytes()
+                && kind.outer.major_axis.to_ne_bytes() == 0.5f64.to_ne_bytes()
+                && kind.outer.minor_axis.to_ne_bytes() == 0.5f64.to_ne_bytes()
+                && kind.inner.center_x.to_ne_bytes() == 0.25f64.to_ne_bytes()
+                && kind.inner.major_axis.to_ne_bytes() == 0.5f64.to_ne_bytes()
+        ));
+
+        let lune_on_canvas = lune.on_canvas(128, 128);
+
+        assert_eq!(
+            lune_on_canvas.intensity().to_ne_bytes(),
+            3.0f64.to_ne_bytes()
+        );
+        // crescent
+        assert!(lune_on_canvas.inside(36.0, 64.0));
+        // overlap of both circles
+        assert!(!lune_on_canvas.inside(64.0, 64.0));
+        assert!(matches!(
+            lune_on_canvas.kind,
+            ShapeKindOnCanvas::Lune(ref kind)
+            if lune_on_canvas.bounding_box() == kind.bounding_box()
+        ));
+    }
+
+    #[test]
+    fn test_shape_annulus() {
+        let (nx, ny) = (128, 128);
+        let annulus = Shape::annulus(0.25, 0.0, 0.25, 0.5, 2.0).unwrap();
+        assert_eq!(annulus.shape_type(), ShapeType::Lune);
+        assert_eq!(annulus, Shape::lune(0.25, 0.0, 0.5, 0.25, 0.0, 0.25, 2.0));
+        assert_abs_diff_eq!(
+            annulus.area_fraction(nx, ny),
+            std::f64::consts::PI * (0.25 - 0.0625) / 4.0,
+            epsilon = 1e-12
+        );
+
+        // The center is at pixel (80, 64), the ring covers distances from 16 to 32 pixels
+        let annulus_on_canvas = annulus.on_canvas(nx, ny);
+        assert!(!annulus_on_canvas.inside(80.0, 64.0));
+        assert!(!annulus_on_canvas.inside(80.0 + 15.0, 64.0));
+        assert!(annulus_on_canvas.inside(80.0 + 24.0, 64.0));
+        assert!(annulus_on_canvas.inside(80.0, 64.0 - 24.0));
+        assert!(!annulus_on_canvas.inside(80.0 - 33.0, 64.0));
+        // The boundary of the inner circle belongs to the annulus
+        assert!(annulus_on_canvas.inside(80.0 + 16.0, 64.0));
+        assert!(annulus_on_canvas.inside(80.0 + 32.0, 64.0));
+        assert_eq!(
+            annulus_on_canvas.bounding_box(),
+            Shape::circle(0.25, 0.0, 0.5, 2.0)
+                .on_canvas(nx, ny)
+                .bounding_box()
+        );
+
+        // An inner radius of zero results in a disk
+        let disk = Shape::annulus(0.0, 0.0, 0.0, 0.5, 1.0).unwrap();
+        let circle = Shape::circle(0.0, 0.0, 0.5, 1.0);
+        for x in 0..nx {
+            for y in 0..ny {
+                assert_eq!(disk.contains(nx, ny, x, y), circle.contains(nx, ny, x, y));
+            }
+        }
+
+        assert!(Shape::annulus(0.0, 0.0, 0.5, 0.5, 1.0).is_err());
+        assert!(Shape::annulus(0.0, 0.0, 0.6, 0.5, 1.0).is_err());
+        assert!(Shape::annulus(0.0, 0.0, -0.1, 0.5, 1.0).is_err());
+        assert!(Shape::annulus(0.0, 0.0, f64::NAN, 0.5, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_shape_contains() {
+        let (nx, ny) = (48, 64);
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.7, 0.3, 30.0, 1.0),
+            Shape::rectangle(-0.3, 0.4, 0.5, 0.2, -15.0, 1.0),
+            Shape::lune(0.0, 0.0, 0.6, 0.3, 0.1, 0.5, 1.0),
+        ];
+
+        for shape in shapes.iter() {
+            let shape_on_canvas = shape.on_canvas(nx, ny);
+            let mut num_inside = 0;
+            for x in 0..nx {
+                for y in 0..ny {
+                    let inside = shape_on_canvas.inside(f64::from(x), f64::from(y));
+                    assert_eq!(shape.contains(nx, ny, x, y), inside);
+                    num_inside += usize::from(inside);
+                }
+            }
+            assert!(num_inside > 0);
+        }
+    }
+
+    #[test]
+    fn test_shape_type() {
+        let ellipse = Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 1.0);
+        assert_eq!(ellipse.shape_type(), ShapeType::Ellipse);
+        assert!(ellipse.is_ellipse());
+        assert!(!ellipse.is_rectangle());
+
+        let rectangle = Shape::rectangle(0.1, -0.4, 0.6, 0.2, 20.0, 1.0);
+        assert_eq!(rectangle.shape_type(), ShapeType::Rectangle);
+        assert!(!rectangle.is_ellipse());
+        assert!(rectangle.is_rectangle());
+
+        let lune = Shape::lune(0.0, 0.0, 0.5, 0.2, 0.0, 0.45, 1.0);
+        assert_eq!(lune.shape_type(), ShapeType::Lune);
+        assert!(!lune.is_ellipse());
+        assert!(!lune.is_rectangle());
+
+        let group = Shape::group(vec![ellipse, rectangle], 1.0);
+        assert_eq!(group.shape_type(), ShapeType::Group);
+        assert!(!group.is_ellipse());
+        assert!(!group.is_rectangle());
+    }
+
+    #[test]
+    fn test_shape_group_on_canvas() {
+        let (nx, ny) = (64, 64);
+        let left = Shape::ellipse(-0.2, 0.0, 0.3, 0.3, 0.0, 5.0);
+        let right = Shape::rectangle(0.2, 0.1, 0.4, 0.3, 10.0, 7.0);
+        let group = Shape::group(vec![left.clone(), right.clone()], 2.0).on_canvas(nx, ny);
+        let left = left.on_canvas(nx, ny);
+        let right = right.on_canvas(nx, ny);
+
+        assert_eq!(group.intensity().to_ne_bytes(), 2.0f64.to_ne_bytes());
+        assert_eq!(
+            group.bounding_box(),
+            left.bounding_box().union(&right.bounding_box())
+        );
+        for x in 0..nx {
+            for y in 0..ny {
+                let expected = left.inside_pixel(x, y) || right.inside_pixel(x, y);
+                assert_eq!(group.inside_pixel(x, y), expected);
+                assert_eq!(group.inside(f64::from(x), f64::from(y)), expected);
+            }
+        }
+
+        let empty = Shape::group(vec![], 1.0).on_canvas(nx, ny);
+        assert!(empty.bounding_box().is_empty());
+        assert!(!empty.inside(32.0, 32.0));
+    }
+
+    #[test]
+    fn test_shape_area_fraction() {
+        let (nx, ny) = (256, 192);
+        let count = |shape: &Shape| {
+            let mut count = 0;
+            for x in 0..nx {
+                for y in 0..ny {
+                    count += usize::from(shape.contains(nx, ny, x, y));
+                }
+            }
+            count as f64 / f64::from(nx * ny)
+        };
+
+        let circle = Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0);
+        let expected = std::f64::consts::PI * 48.0 * 48.0 / f64::from(nx * ny);
+        assert_abs_diff_eq!(circle.area_fraction(nx, ny), expected, epsilon = 1e-12);
+        assert_abs_diff_eq!(circle.area_fraction(nx, ny), count(&circle), epsilon = 1e-3);
+
+        let shapes = [
+            Shape::ellipse(0.2, -0.1, 0.6, 0.3, 30.0, 1.0),
+            Shape::rectangle(0.1, 0.2, 0.5, 0.8, 25.0, 1.0),
+            Shape::lune(0.0, 0.0, 0.6, 0.3, 0.1, 0.5, 1.0),
+        ];
+        for shape in shapes.iter() {
+            let fraction = shape.area_fraction(nx, ny);
+            assert!((fraction - count(shape)).abs() / fraction < 0.02);
+        }
+
+        let group = Shape::group(vec![circle.clone(), circle], 1.0);
+        assert_eq!(group.area_fraction(nx, ny), count(&group));
+    }
+
+    #[test]
+    fn test_shape_hash_eq() {
+        use std::collections::HashSet;
+
+        let ellipse = Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 1.0);
+        let shapes = [
+            ellipse.clone(),
+            ellipse.clone(),
+            Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 2.0),
+            Shape::rectangle(0.1, -0.4, 0.6, 0.2, 20.0, 1.0),
+            Shape::rectangle(0.1, -0.4, 0.6, 0.2, 20.0, 1.0),
+            Shape::lune(0.0, 0.0, 0.5, 0.2, 0.0, 0.45, 1.0),
+            Shape::group(vec![ellipse.clone()], 1.0),
+            Shape::group(vec![ellipse.clone()], 1.0),
+            Shape::group(vec![ellipse.clone(), ellipse], 1.0),
+            // bitwise comparison
+            Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(-0.0, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(f64::NAN, 0.0, 0.5, 0.5, 0.0, 1.0),
+            Shape::ellipse(f64::NAN, 0.0, 0.5, 0.5, 0.0, 1.0),
+        ];
+        let set = shapes.iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 9);
+        assert_eq!(shapes[11], shapes[11]);
+        assert_ne!(shapes[9], shapes[10]);
+        // Ellipses and rectangles with the same parameters differ
+        assert_ne!(shapes[0], shapes[3]);
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let shapes = [
+            Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 1.0),
+            Shape::rectangle(0.1, -0.4, 0.6, 0.2, 20.0, 1.0),
+            Shape::lune(0.0, 0.0, 0.5, 0.2, 0.0, 0.45, 1.0),
+            Shape::group(vec![Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 0.0)], 1.0),
+        ];
+        let perturbed = [
+            Shape::ellipse(0.1, -0.4, 0.6, 0.2 + 1e-12, 20.0, 1.0),
+            Shape::rectangle(0.1, -0.4, 0.6, 0.2, 20.0, 1.0 - 1e-12),
+            Shape::lune(0.0, 0.0, 0.5, 0.2, 1e-12, 0.45, 1.0),
+            Shape::group(vec![Shape::ellipse(0.1, -0.4, 0.6, 0.2, 20.0, 1e-12)], 1.0),
+        ];
+        for (i, (shape, other)) in shapes.iter().zip(perturbed.iter()).enumerate() {
+            assert_ne!(shape, other);
+            assert!(shape.approx_eq(other, 1e-9));
+            assert!(other.approx_eq(shape, 1e-9));
+            assert!(!shape.approx_eq(other, 1e-13));
+            // Different kinds are never approximately equal
+            for (j, other) in perturbed.iter().enumerate() {
+                assert_eq!(shape.approx_eq(other, 1.0), i == j);
+            }
+        }
+
+        let group = Shape::group(shapes[..2].to_vec(), 1.0);
+        assert!(!group.approx_eq(&Shape::group(shapes[..1].to_vec(), 1.0), 1.0));
+        assert!(!group.approx_eq(
+            &Shape::group(vec![shapes[1].clone(), shapes[0].clone()], 1.0),
+            1.0
+        ));
+        let nan = Shape::ellipse(f64::NAN, 0.0, 0.5, 0.5, 0.0, 1.0);
+        assert_eq!(nan, nan);
+        assert!(!nan.approx_eq(&nan, 1.0));
+    }
+
+    #[test]
+    fn test_ellipses_from_rows() {
+        let rows = [
+            [0.0, 0.35, 0.21, 0.25, 0.0, 0.1],
+            [0.22, 0.0, 0.11, 0.31, -18.0, -0.2],
+        ];
+        assert_eq!(
+            Shape::ellipses_from_rows(&rows),
+            [
+                Shape::ellipse(0.0, 0.35, 0.21, 0.25, 0.0, 0.1),
+                Shape::ellipse(0.22, 0.0, 0.11, 0.31, -18.0, -0.2),
+            ]
+        );
+        assert!(Shape::ellipses_from_rows(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_scale_geometry() {
+        let (nx, ny) = (256, 256);
+        let shapes = [
+            Shape::ellipse(0.1, -0.2, 0.3, 0.15, 30.0, 1.0),
+            Shape::rectangle(-0.1, 0.2, 0.3, 0.2, 20.0, 1.0),
+            Shape::lune(0.0, 0.1, 0.3, 0.15, 0.1, 0.2, 1.0),
+            Shape::group(
+                vec![
+                    Shape::ellipse(-0.3, 0.0, 0.1, 0.1, 0.0, 0.0),
+                    Shape::rectangle(0.2, 0.1, 0.2, 0.1, 10.0, 0.0),
+                ],
+                1.0,
+            ),
+        ];
+        for shape in shapes {
+            let scaled = shape.clone().scale_geometry(2.0);
+            assert_eq!(scaled.intensity(), shape.intensity());
+            // The bounding box keeps its center and doubles its size
+            let (a, b) = (shape.bounds(), scaled.bounds());
+            assert_abs_diff_eq!((a.0 + a.1) / 2.0, (b.0 + b.1) / 2.0, epsilon = 1e-12);
+            assert_abs_diff_eq!((a.2 + a.3) / 2.0, (b.2 + b.3) / 2.0, epsilon = 1e-12);
+            assert_abs_diff_eq!(2.0 * (a.1 - a.0), b.1 - b.0, epsilon = 1e-12);
+            assert_abs_diff_eq!(2.0 * (a.3 - a.2), b.3 - b.2, epsilon = 1e-12);
+
+            // The rendered shape covers about four times as many pixels
+            let (a, b) = (
+                shape.on_canvas(nx, ny).bounding_box(),
+                scaled.on_canvas(nx, ny).bounding_box(),
+            );
+            let width = |bbox: &super::BoundingBox| f64::from(bbox.x_high - bbox.x_low);
+            assert_abs_diff_eq!(width(&b) / width(&a), 2.0, epsilon = 0.05);
+            let count = |shape: &Shape| {
+                crate::Phantom::new(nx, ny, std::slice::from_ref(shape))
+                    .into_vec()
+                    .iter()
+                    .filter(|&&x| x != 0.0)
+                    .count() as f64
+            };
+            assert_abs_diff_eq!(count(&scaled) / count(&shape), 4.0, epsilon = 0.1);
+        }
+        // Scaling by one does not change the shape
+        let rectangle = Shape::rectangle(0.1, 0.2, 0.3, 0.4, 5.0, 2.0);
+        assert_eq!(rectangle.clone().scale_geometry(1.0), rectangle);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let (nx, ny) = (128, 128);
+        // Circles touching at x = 0.5 / 64, which is between two pixels
+        let shift = 0.5 / 64.0;
+        let left = Shape::ellipse(shift - 0.25, 0.0, 0.25, 0.25, 0.0, 1.0);
+        let right = Shape::ellipse(shift + 0.25, 0.0, 0.25, 0.25, 0.0, 1.0);
+        assert!(!left.overlaps(&right, nx, ny));
+        assert!(!right.overlaps(&left, nx, ny));
+        // The bounding boxes overlap nevertheless
+        let (a, b) = (left.on_canvas(nx, ny), right.on_canvas(nx, ny));
+        assert!(!a.bounding_box().intersection(&b.bounding_box()).is_empty());
+
+        let intersecting = Shape::ellipse(0.1, 0.05, 0.25, 0.25, 0.0, 1.0);
+        assert!(left.overlaps(&intersecting, nx, ny));
+        assert!(intersecting.overlaps(&right, nx, ny));
+
+        // Shapes of different kinds, where one contains the other
+        let square = Shape::rectangle(0.0, 0.0, 1.0, 1.0, 20.0, 1.0);
+        let lune = Shape::lune(0.1, 0.1, 0.1, 0.2, 0.1, 0.05, 1.0);
+        assert!(square.overlaps(&lune, nx, ny));
+        let group = Shape::group(vec![left.clone(), right.clone()], 1.0);
+        assert!(group.overlaps(&intersecting, nx, ny));
+        assert!(!group.overlaps(&Shape::ellipse(0.0, 0.8, 0.1, 0.1, 0.0, 1.0), nx, ny));
+
+        // Shapes outside of the phantom do not cover any pixel
+        let outside = Shape::ellipse(3.0, 0.0, 0.5, 0.5, 0.0, 1.0);
+        assert!(!outside.overlaps(&outside, nx, ny));
+        assert!(!left.overlaps(&left, 0, 0));
+        assert!(left.overlaps(&left, nx, ny));
+    }
+
+    #[test]
+    fn test_ellipse_in_box() {
+        for &(bbox, nx, ny) in &[
+            ((10, 50, 20, 30), 64, 64),
+            ((0, 63, 0, 63), 64, 64),
+            ((3, 5, 90, 127), 48, 128),
+            ((100, 180, 7, 19), 200, 25),
+        ] {
+            let shape = Shape::ellipse_in_box(bbox, nx, ny, 1.0);
+            let phantom = crate::Phantom::new(nx, ny, std::slice::from_ref(&shape));
+            let covered = (0..nx)
+                .flat_map(|x| (0..ny).map(move |y| (x, y)))
+                .filter(|&(x, y)| phantom.data[((ny - y - 1) * nx + x) as usize] == 1.0)
+                .collect::<Vec<_>>();
+            let x_low = covered.iter().map(|p| p.0).min().unwrap();
+            let x_high = covered.iter().map(|p| p.0).max().unwrap();
+            let y_low = covered.iter().map(|p| p.1).min().unwrap();
+            let y_high = covered.iter().map(|p| p.1).max().unwrap();
+            let (ex_low, ex_high, ey_low, ey_high) = bbox;
+            assert!(x_low.abs_diff(ex_low) <= 1 && x_high.abs_diff(ex_high) <= 1);
+            assert!(y_low.abs_diff(ey_low) <= 1 && y_high.abs_diff(ey_high) <= 1);
+            // The rendered ellipse never exceeds the requested box
+            assert!(x_low >= ex_low && x_high <= ex_high);
+            assert!(y_low >= ey_low && y_high <= ey_high);
+        }
+    }
+
+    #[test]
+    fn test_is_within_canonical_domain() {
+        let background = Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0);
+        assert!(background.is_within_canonical_domain());
+        // Rotating by 90 degrees swaps the extents
+        assert!(Shape::ellipse(0.0, 0.0, 0.92, 0.69, 90.0, 2.0).is_within_canonical_domain());
+        assert!(!Shape::ellipse(0.0, 0.0, 1.1, 0.5, 0.0, 1.0).is_within_canonical_domain());
+        assert!(!Shape::ellipse(0.0, 0.0, 0.5, 1.1, 0.0, 1.0).is_within_canonical_domain());
+        assert!(Shape::ellipse(0.0, 0.0, 0.95, 0.2, 45.0, 1.0).is_within_canonical_domain());
+        assert!(!Shape::ellipse(0.0, -0.5, 0.2, 0.6, 0.0, 1.0).is_within_canonical_domain());
+
+        // The full square touches the boundary, its rotated version exceeds it
+        assert!(Shape::rectangle(0.0, 0.0, 2.0, 2.0, 0.0, 1.0).is_within_canonical_domain());
+        assert!(!Shape::rectangle(0.0, 0.0, 2.0, 2.0, 10.0, 1.0).is_within_canonical_domain());
+        assert!(Shape::rectangle(0.0, 0.0, -1.0, 0.5, 45.0, 1.0).is_within_canonical_domain());
+
+        assert!(Shape::lune(0.0, 0.0, 0.5, 0.8, 0.0, 0.5, 1.0).is_within_canonical_domain());
+        assert!(!Shape::lune(0.6, 0.0, 0.5, 0.0, 0.0, 0.5, 1.0).is_within_canonical_domain());
+
+        assert!(Shape::group(vec![], 1.0).is_within_canonical_domain());
+        assert!(!Shape::group(
+            vec![background, Shape::ellipse(0.9, 0.0, 0.2, 0.2, 0.0, 0.0)],
+            1.0
         )
+        .is_within_canonical_domain());
+    }
+
+    #[test]
+    fn test_clip_to_canonical() {
+        // Oversized ellipse centered at the origin is shrunk onto the domain
+        let clipped = Shape::ellipse(0.0, 0.0, 1.5, 0.5, 0.0, 2.0)
+            .clip_to_canonical()
+            .unwrap();
+        assert!(clipped.is_within_canonical_domain());
+        assert_eq!(clipped, Shape::ellipse(0.0, 0.0, 1.0, 1.0 / 3.0, 0.0, 2.0));
+
+        // Partially outside: moved into the part of the bounding box inside of the domain
+        let clipped = Shape::ellipse(0.8, 0.0, 0.4, 0.2, 30.0, 1.0)
+            .clip_to_canonical()
+            .unwrap();
+        assert!(clipped.is_within_canonical_domain());
+        let ShapeKind::Ellipse(ellipse) = &clipped.kind else {
+            panic!("not an ellipse");
+        };
+        assert_eq!(ellipse.theta, 30.0);
+        assert_abs_diff_eq!(ellipse.bounds().1, 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            ellipse.major_axis / ellipse.minor_axis,
+            2.0,
+            epsilon = 1e-12
+        );
+
+        // Shapes within the domain are unchanged, shapes outside of it are removed
+        let shape = Shape::rectangle(0.1, 0.2, 0.5, 0.5, 10.0, 1.0);
+        assert_eq!(shape.clip_to_canonical(), Some(shape));
+        assert!(Shape::ellipse(2.0, 2.0, 0.5, 0.5, 0.0, 1.0)
+            .clip_to_canonical()
+            .is_none());
+        assert!(Shape::rectangle(0.0, 1.5, 0.5, 1.0, 0.0, 1.0)
+            .clip_to_canonical()
+            .is_none());
+
+        // Axis aligned rectangles are cut, rotated ones are shrunk
+        assert_eq!(
+            Shape::rectangle(0.5, 0.0, 2.0, 0.5, 0.0, 1.0).clip_to_canonical(),
+            Some(Shape::rectangle(0.25, 0.0, 1.5, 0.5, 0.0, 1.0))
+        );
+        let clipped = Shape::rectangle(0.0, 0.0, 2.0, 2.0, 45.0, 1.0)
+            .clip_to_canonical()
+            .unwrap();
+        assert!(clipped.is_within_canonical_domain());
+        let ShapeKind::Rectangle(rectangle) = &clipped.kind else {
+            panic!("not a rectangle");
+        };
+        assert_abs_diff_eq!(rectangle.width, 2f64.sqrt(), epsilon = 1e-12);
+        assert_abs_diff_eq!(rectangle.height, 2f64.sqrt(), epsilon = 1e-12);
+
+        // Both circles of a lune are transformed together
+        let clipped = Shape::lune(0.0, 0.0, 2.0, 1.0, 0.0, 2.0, 1.0)
+            .clip_to_canonical()
+            .unwrap();
+        assert_eq!(clipped, Shape::lune(0.0, 0.0, 1.0, 0.5, 0.0, 1.0, 1.0));
+
+        // Members of groups outside of the domain are dropped
+        let inside = Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 0.0);
+        let outside = Shape::ellipse(3.0, 0.0, 0.5, 0.5, 0.0, 0.0);
+        assert_eq!(
+            Shape::group(vec![inside.clone(), outside.clone()], 1.0).clip_to_canonical(),
+            Some(Shape::group(vec![inside], 1.0))
+        );
+        assert!(Shape::group(vec![outside], 1.0)
+            .clip_to_canonical()
+            .is_none());
+    }
+
+    #[test]
+    fn test_perimeter() {
+        use std::f64::consts::PI;
+
+        // Circle with a radius of 32 pixels
+        let circle = Shape::ellipse(0.2, 0.1, 0.5, 0.5, 30.0, 1.0);
+        assert_abs_diff_eq!(circle.perimeter(64, 128), 2.0 * PI * 16.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(circle.perimeter(128, 128), 2.0 * PI * 32.0, epsilon = 1e-12);
+
+        let rectangle = Shape::rectangle(0.0, 0.0, 0.5, -0.25, 45.0, 1.0);
+        assert_abs_diff_eq!(
+            rectangle.perimeter(128, 128),
+            2.0 * (32.0 + 16.0),
+            epsilon = 1e-12
+        );
+
+        // Ellipse with semi-axes 3 and 1, whose perimeter is 13.3648932...
+        let ellipse = Shape::ellipse(0.0, 0.0, 0.3, 0.1, 0.0, 1.0);
+        assert_abs_diff_eq!(
+            ellipse.perimeter(20, 20),
+            13.364893220555258,
+            epsilon = 1e-5
+        );
+        // Degenerate ellipse, whose perimeter is 4a
+        let line = Shape::ellipse(0.0, 0.0, 0.5, 0.0, 0.0, 1.0);
+        assert_abs_diff_eq!(line.perimeter(2, 2), 2.0, epsilon = 2.0 * 5e-4);
+
+        let group = Shape::group(vec![circle.clone(), rectangle.clone()], 1.0);
+        assert_abs_diff_eq!(
+            group.perimeter(128, 128),
+            circle.perimeter(128, 128) + rectangle.perimeter(128, 128)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let shape = Shape::ellipse(0.1, -0.2, 0.5, 0.25, 30.0, 1.5);
+        let json = serde_json::to_value(&shape).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "intensity": 1.5,
+                "kind": {
+                    "ellipse": {
+                        "center_x": 0.1,
+                        "center_y": -0.2,
+                        "major_axis": 0.5,
+                        "minor_axis": 0.25,
+                        "theta": 30.0,
+                    }
+                }
+            })
+        );
+
+        let mut shapes = crate::shepplogan::Preset::Modified.shapes().to_vec();
+        shapes.push(Shape::rectangle(0.3, 0.1, 0.2, 0.4, 15.0, 0.5));
+        shapes.push(Shape::lune(0.0, 0.0, 0.5, 0.1, 0.0, 0.4, 1.0));
+        shapes.push(Shape::polygon(&[(0.0, 0.0), (0.5, 0.1), (0.2, 0.4)], 10.0, 0.3).unwrap());
+        shapes.push(Shape::group(shapes[..2].to_vec(), 0.5));
+        let json = serde_json::to_string(&shapes).unwrap();
+        let reloaded: Vec<Shape> = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded, shapes);
+        let phantom: Vec<f64> = crate::Phantom::new(64, 64, &reloaded).into_vec();
+        assert_eq!(phantom, crate::Phantom::new(64, 64, &shapes).into_vec());
+
+        // Invalid polygons are rejected as by `Shape::polygon`
+        let polygon = |vertices: serde_json::Value| {
+            serde_json::from_value::<Shape>(serde_json::json!({
+                "intensity": 1.0,
+                "kind": { "polygon": { "vertices": vertices, "theta": 0.0 } }
+            }))
+        };
+        assert!(polygon(serde_json::json!([[0.0, 0.0], [0.5, 0.1], [0.2, 0.4]])).is_ok());
+        assert!(polygon(serde_json::json!([])).is_err());
+        assert!(polygon(serde_json::json!([[0.0, 0.0], [0.5, 0.1]])).is_err());
+        assert!(polygon(serde_json::json!([[0.0, 0.0], [0.5, 0.0], [1.0, 0.0]])).is_err());
+        // Non-convex arrow
+        let arrow = serde_json::json!([[0.0, 0.0], [0.5, 0.5], [0.0, 0.2], [-0.5, 0.5]]);
+        assert!(polygon(arrow).is_err());
+        let triangle = Shape::polygon(&[(0.0, 0.0), (0.5, 0.1), (0.2, 0.4)], 0.0, 1.0).unwrap();
+        let mut group = serde_json::to_value(Shape::group(vec![triangle], 1.0)).unwrap();
+        assert!(serde_json::from_value::<Shape>(group.clone()).is_ok());
+        group["kind"]["group"][0]["kind"]["polygon"]["vertices"] = serde_json::json!([]);
+        assert!(serde_json::from_value::<Shape>(group).is_err());
     }
 }
@@ -0,0 +1,376 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{BoundingBox, Canvas};
+use crate::{Boundary, Error};
+
+/// Representation of a convex polygon
+///
+/// Deserialized polygons are validated by `Polygon::new`.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "PolygonDef"))]
+pub(crate) struct Polygon {
+    /// vertices before the rotation
+    pub(crate) vertices: Vec<(f64, f64)>,
+    /// rotation about the mean of the vertices in degrees
+    pub(crate) theta: f64,
+}
+
+/// Unvalidated parameters of a deserialized polygon
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PolygonDef {
+    vertices: Vec<(f64, f64)>,
+    theta: f64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PolygonDef> for Polygon {
+    type Error = Error;
+
+    fn try_from(def: PolygonDef) -> Result<Self, Error> {
+        Polygon::new(&def.vertices, def.theta)
+    }
+}
+
+impl Polygon {
+    /// Constructs a new convex polygon.
+    ///
+    /// The canvas for defining polygons is square and ranges from -1 to 1 on both axes `x` and `y`.
+    ///
+    /// # Parameters
+    ///
+    /// * `vertices`: vertices of the polygon in clockwise or counterclockwise order
+    /// * `theta`: Rotation angle of the polygon about the mean of its vertices in degrees
+    ///
+    /// Returns `Error::InvalidParameter` if there are less than three vertices, if the polygon
+    /// has no area or if it is not convex (which includes polygons whose outline intersects
+    /// itself).
+    pub(crate) fn new(vertices: &[(f64, f64)], theta: f64) -> Result<Self, Error> {
+        if vertices.len() < 3 {
+            return Err(Error::InvalidParameter(
+                "polygon requires at least three vertices",
+            ));
+        }
+        let polygon = Polygon {
+            vertices: vertices.to_vec(),
+            theta,
+        };
+        let area = polygon.signed_area();
+        if area == 0.0 || area.is_nan() {
+            return Err(Error::InvalidParameter("polygon must have a positive area"));
+        }
+        if !polygon.is_convex() {
+            return Err(Error::InvalidParameter("polygon must be convex"));
+        }
+        Ok(polygon)
+    }
+
+    /// Returns `true` if the polygon turns in the same direction at every vertex and its outline
+    /// winds around its interior exactly once.
+    fn is_convex(&self) -> bool {
+        let mut turns = (0.0, 0.0);
+        let mut total_angle = 0.0;
+        for (a, b) in self.edges().zip(self.edges().cycle().skip(1)) {
+            let cross = a.0 * b.1 - a.1 * b.0;
+            if cross > 0.0 {
+                turns.0 += 1.0;
+            } else if cross < 0.0 {
+                turns.1 += 1.0;
+            }
+            total_angle += cross.atan2(a.0 * b.0 + a.1 * b.1);
+        }
+        // A simple polygon turns by exactly one full rotation
+        (turns.0 == 0.0 || turns.1 == 0.0)
+            && (total_angle.abs() - std::f64::consts::TAU).abs() < 1e-6
+    }
+
+    /// Edge vectors from each vertex to the next one
+    fn edges(&self) -> impl Iterator<Item = (f64, f64)> + Clone + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| {
+            let (a, b) = (self.vertices[i], self.vertices[(i + 1) % n]);
+            (b.0 - a.0, b.1 - a.1)
+        })
+    }
+
+    /// Signed area of the polygon, which is positive for counterclockwise vertices
+    pub(crate) fn signed_area(&self) -> f64 {
+        let n = self.vertices.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (self.vertices[i], self.vertices[(i + 1) % n]);
+                a.0 * b.1 - a.1 * b.0
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+
+    /// Area of the polygon in shape coordinates
+    pub(crate) fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    /// Perimeter of the polygon in shape coordinates
+    pub(crate) fn perimeter(&self) -> f64 {
+        self.edges().map(|(x, y)| x.hypot(y)).sum()
+    }
+
+    /// Center of rotation, which is the mean of the vertices
+    pub(crate) fn center(&self) -> (f64, f64) {
+        let n = self.vertices.len() as f64;
+        let (x, y) = self
+            .vertices
+            .iter()
+            .fold((0.0, 0.0), |(x, y), v| (x + v.0, y + v.1));
+        (x / n, y / n)
+    }
+
+    /// Vertices of the polygon after the rotation in shape coordinates
+    pub(crate) fn rotated_vertices(&self) -> Vec<(f64, f64)> {
+        let (theta_sin, theta_cos) = self.theta.to_radians().sin_cos();
+        let (cx, cy) = self.center();
+        self.vertices
+            .iter()
+            .map(|&(x, y)| {
+                let (x, y) = (x - cx, y - cy);
+                (
+                    x * theta_cos - y * theta_sin + cx,
+                    x * theta_sin + y * theta_cos + cy,
+                )
+            })
+            .collect()
+    }
+
+    /// Applies `f` to all vertices, keeping the rotation.
+    ///
+    /// `f` needs to be a uniform scaling followed by a translation, which maps the center of
+    /// rotation onto the center of rotation of the result. Therefore the rotated polygon is
+    /// transformed by `f` as well.
+    pub(crate) fn map(&self, f: impl Fn((f64, f64)) -> (f64, f64)) -> Polygon {
+        Polygon {
+            vertices: self.vertices.iter().copied().map(f).collect(),
+            theta: self.theta,
+        }
+    }
+
+    /// Bounding box `(x_min, x_max, y_min, y_max)` of the polygon in shape coordinates
+    pub(crate) fn bounds(&self) -> (f64, f64, f64, f64) {
+        self.rotated_vertices().iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x_min, x_max, y_min, y_max), &(x, y)| {
+                (x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y))
+            },
+        )
+    }
+
+    /// Returns the parameters `[x_0, y_0, x_1, y_1, ..., theta]`.
+    pub(crate) fn params(&self) -> Vec<f64> {
+        self.vertices
+            .iter()
+            .flat_map(|&(x, y)| [x, y])
+            .chain(std::iter::once(self.theta))
+            .collect()
+    }
+
+    /// Returns the bit patterns of the parameters.
+    pub(crate) fn to_bits(&self) -> Vec<u64> {
+        self.params().into_iter().map(f64::to_bits).collect()
+    }
+
+    /// Returns `true` if all parameters are finite.
+    pub(crate) fn is_finite(&self) -> bool {
+        self.params().iter().all(|x| x.is_finite())
+    }
+
+    pub(crate) fn on_canvas(&self, canvas: &Canvas) -> PolygonOnCanvas {
+        let mut vertices = self
+            .rotated_vertices()
+            .into_iter()
+            .map(|(x, y)| canvas.map(x, y))
+            .collect::<Vec<_>>();
+        // The inside test requires counterclockwise vertices
+        if self.signed_area() < 0.0 {
+            vertices.reverse();
+        }
+        let n = vertices.len();
+        let edges = (0..n)
+            .map(|i| {
+                let (a, b) = (vertices[i], vertices[(i + 1) % n]);
+                (b.0 - a.0, b.1 - a.1)
+            })
+            .collect();
+
+        let (x_min, x_max, y_min, y_max) = vertices.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(x_min, x_max, y_min, y_max), &(x, y)| {
+                (x_min.min(x), x_max.max(x), y_min.min(y), y_max.max(y))
+            },
+        );
+        let bbox = BoundingBox::clamped(
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            canvas.nx,
+            canvas.ny,
+            canvas.bbox_rounding,
+        );
+
+        PolygonOnCanvas {
+            vertices,
+            edges,
+            bbox,
+            boundary: canvas.boundary,
+        }
+    }
+}
+
+/// Representation of a convex polygon on a canvas
+#[derive(PartialEq, Clone, Debug)]
+pub(crate) struct PolygonOnCanvas {
+    /// vertices in counterclockwise order
+    vertices: Vec<(f64, f64)>,
+    /// edge vectors from each vertex to the next one
+    edges: Vec<(f64, f64)>,
+    /// bounding box
+    bbox: BoundingBox,
+    /// whether points on the boundary are inside
+    boundary: Boundary,
+}
+
+impl PolygonOnCanvas {
+    #[inline(always)]
+    pub(crate) fn bounding_box(&self) -> BoundingBox {
+        self.bbox
+    }
+
+    /// Checks if a point is inside the polygon
+    ///
+    /// Since the vertices are counterclockwise, the point is inside if it lies to the left of
+    /// every edge.
+    #[inline(always)]
+    pub(crate) fn inside(&self, x: f64, y: f64) -> bool {
+        self.vertices.iter().zip(self.edges.iter()).all(|(a, e)| {
+            let am = (x - a.0, y - a.1);
+            self.boundary.le(0.0, e.0 * am.1 - e.1 * am.0)
+        })
+    }
+
+    /// Signed distance of a point to the outline of the polygon (negative inside)
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        if self.inside(x, y) {
+            // Distance to the closest supporting line of an edge
+            self.vertices
+                .iter()
+                .zip(self.edges.iter())
+                .map(|(a, e)| -(e.0 * (y - a.1) - e.1 * (x - a.0)) / e.0.hypot(e.1))
+                .fold(f64::NEG_INFINITY, f64::max)
+        } else {
+            // Distance to the closest edge
+            self.vertices
+                .iter()
+                .zip(self.edges.iter())
+                .map(|(a, e)| {
+                    let am = (x - a.0, y - a.1);
+                    let t = ((am.0 * e.0 + am.1 * e.1) / (e.0 * e.0 + e.1 * e.1)).clamp(0.0, 1.0);
+                    (am.0 - t * e.0).hypot(am.1 - t * e.1)
+                })
+                .fold(f64::INFINITY, f64::min)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Canvas, Polygon};
+    use crate::Error;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_polygon_new() {
+        let square = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let polygon = Polygon::new(&square, 30.0).unwrap();
+        assert_eq!(polygon.vertices, square);
+        assert_eq!(polygon.theta.to_ne_bytes(), 30.0f64.to_ne_bytes());
+        // Clockwise vertices
+        let mut clockwise = square;
+        clockwise.reverse();
+        assert!(Polygon::new(&clockwise, 0.0).is_ok());
+        // Collinear vertices are allowed
+        assert!(Polygon::new(&[(0.0, 0.0), (0.5, 0.0), (1.0, 0.0), (0.0, 1.0)], 0.0).is_ok());
+
+        let invalid = |vertices: &[(f64, f64)]| {
+            matches!(Polygon::new(vertices, 0.0), Err(Error::InvalidParameter(_)))
+        };
+        assert!(invalid(&[]));
+        assert!(invalid(&[(0.0, 0.0), (1.0, 0.0)]));
+        assert!(invalid(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0)]));
+        assert!(invalid(&[(0.0, 0.0), (1.0, 0.0), (f64::NAN, 1.0)]));
+        // Non-convex
+        assert!(invalid(&[(0.0, 0.0), (1.0, 0.0), (0.2, 0.2), (0.0, 1.0)]));
+        // Pentagram, which turns in the same direction at every vertex
+        let pentagram = (0..5)
+            .map(|i| {
+                let angle = f64::from(2 * i) * std::f64::consts::TAU / 5.0;
+                (angle.cos(), angle.sin())
+            })
+            .collect::<Vec<_>>();
+        assert!(invalid(&pentagram));
+    }
+
+    #[test]
+    fn test_polygon_geometry() {
+        let triangle = Polygon::new(&[(0.0, 0.0), (0.0, 0.3), (0.4, 0.0)], 0.0).unwrap();
+        assert_abs_diff_eq!(triangle.area(), 0.06, epsilon = 1e-15);
+        assert_abs_diff_eq!(triangle.perimeter(), 1.2, epsilon = 1e-15);
+        assert_abs_diff_eq!(triangle.center().0, 0.4 / 3.0, epsilon = 1e-15);
+        assert_abs_diff_eq!(triangle.center().1, 0.1, epsilon = 1e-15);
+        assert_eq!(triangle.bounds(), (0.0, 0.4, 0.0, 0.3));
+
+        // A square rotated by 45 degrees about its center
+        let square =
+            Polygon::new(&[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)], 45.0).unwrap();
+        let (x_min, x_max, y_min, y_max) = square.bounds();
+        let half_diagonal = std::f64::consts::FRAC_1_SQRT_2;
+        assert_abs_diff_eq!(x_min, -half_diagonal, epsilon = 1e-15);
+        assert_abs_diff_eq!(x_max, half_diagonal, epsilon = 1e-15);
+        assert_abs_diff_eq!(y_min, -half_diagonal, epsilon = 1e-15);
+        assert_abs_diff_eq!(y_max, half_diagonal, epsilon = 1e-15);
+    }
+
+    #[test]
+    fn test_polygon_on_canvas() {
+        let canvas = Canvas::new(64, 64);
+        let vertices = [(-0.5, -0.5), (0.5, -0.5), (0.0, 0.5)];
+        let mut clockwise = vertices;
+        clockwise.reverse();
+        for vertices in [vertices, clockwise] {
+            let triangle = Polygon::new(&vertices, 0.0).unwrap().on_canvas(&canvas);
+            assert_eq!(triangle.bounding_box(), (16, 48, 16, 48).into());
+            assert!(triangle.inside(32.0, 32.0));
+            // Vertices and edges are inside for the inclusive boundary
+            assert!(triangle.inside(16.0, 16.0));
+            assert!(triangle.inside(32.0, 16.0));
+            assert!(!triangle.inside(32.0, 15.9));
+            assert!(!triangle.inside(20.0, 40.0));
+
+            assert_abs_diff_eq!(triangle.signed_distance(32.0, 10.0), 6.0, epsilon = 1e-12);
+            assert_abs_diff_eq!(triangle.signed_distance(32.0, 20.0), -4.0, epsilon = 1e-12);
+            assert_abs_diff_eq!(triangle.signed_distance(10.0, 16.0), 6.0, epsilon = 1e-12);
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{
+    ellipse::Ellipse, lune::Lune, polygon::Polygon, rectangle::Rectangle, Shape, ShapeKind,
+};
+
+/// A line `{p + t * d}` in shape coordinates
+///
+/// The ray at angle `theta` with signed offset `s` consists of all points `(x, y)` with
+/// `x * cos(theta) + y * sin(theta) = s`. It passes through `p = s * (cos(theta), sin(theta))` in
+/// the direction `d = (-sin(theta), cos(theta))`.
+#[derive(Clone, Copy, Debug)]
+struct Ray {
+    p: (f64, f64),
+    d: (f64, f64),
+}
+
+impl Ray {
+    fn new(theta: f64, s: f64) -> Self {
+        let (sin, cos) = theta.sin_cos();
+        Ray {
+            p: (s * cos, s * sin),
+            d: (-sin, cos),
+        }
+    }
+
+    /// Expresses the ray in the frame of a shape centered at `center` and rotated by `theta`
+    /// degrees.
+    fn to_frame(self, center: (f64, f64), theta: f64) -> Ray {
+        let (sin, cos) = theta.to_radians().sin_cos();
+        let rotate = |(x, y): (f64, f64)| (cos * x + sin * y, -sin * x + cos * y);
+        Ray {
+            p: rotate((self.p.0 - center.0, self.p.1 - center.1)),
+            d: rotate(self.d),
+        }
+    }
+}
+
+/// Interval `[t_min, t_max]` of a ray inside of a shape
+type Interval = (f64, f64);
+
+impl Ellipse {
+    /// Interval of `ray` inside of the ellipse
+    fn chord(&self, ray: Ray) -> Option<Interval> {
+        let Ray { p, d } = ray.to_frame((self.center_x, self.center_y), self.theta);
+        let (a2, b2) = (self.major_axis.powi(2), self.minor_axis.powi(2));
+        // (p + t d)_x^2 / a^2 + (p + t d)_y^2 / b^2 = 1
+        let qa = d.0 * d.0 / a2 + d.1 * d.1 / b2;
+        let qb = p.0 * d.0 / a2 + p.1 * d.1 / b2;
+        let qc = p.0 * p.0 / a2 + p.1 * p.1 / b2 - 1.0;
+        let discriminant = qb * qb - qa * qc;
+        if discriminant > 0.0 && qa.is_finite() {
+            let root = discriminant.sqrt();
+            Some(((-qb - root) / qa, (-qb + root) / qa))
+        } else {
+            None
+        }
+    }
+}
+
+impl Rectangle {
+    /// Interval of `ray` inside of the rectangle
+    fn chord(&self, ray: Ray) -> Option<Interval> {
+        let Ray { p, d } = ray.to_frame((self.center_x, self.center_y), self.theta);
+        // Intersection of the slabs |x| <= width / 2 and |y| <= height / 2
+        let slab = |p: f64, d: f64, half: f64| {
+            if d == 0.0 {
+                if p.abs() <= half {
+                    Some((f64::NEG_INFINITY, f64::INFINITY))
+                } else {
+                    None
+                }
+            } else {
+                let (t0, t1) = ((-half - p) / d, (half - p) / d);
+                Some((t0.min(t1), t0.max(t1)))
+            }
+        };
+        let (x0, x1) = slab(p.0, d.0, self.width.abs() / 2.0)?;
+        let (y0, y1) = slab(p.1, d.1, self.height.abs() / 2.0)?;
+        let (t0, t1) = (x0.max(y0), x1.min(y1));
+        (t0 < t1).then_some((t0, t1))
+    }
+}
+
+impl Polygon {
+    /// Interval of `ray` inside of the convex polygon
+    fn chord(&self, ray: Ray) -> Option<Interval> {
+        let Ray { p, d } = ray;
+        let vertices = self.rotated_vertices();
+        // Outward normals point to the right of the edges of a counterclockwise polygon
+        let orientation = if self.signed_area() < 0.0 { -1.0 } else { 1.0 };
+        let (mut t0, mut t1) = (f64::NEG_INFINITY, f64::INFINITY);
+        for (i, a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % vertices.len()];
+            let normal = (orientation * (b.1 - a.1), -orientation * (b.0 - a.0));
+            // The ray is inside of the half-plane if `normal * (p + t d - a) <= 0`
+            let nd = normal.0 * d.0 + normal.1 * d.1;
+            let na = normal.0 * (a.0 - p.0) + normal.1 * (a.1 - p.1);
+            if nd > 0.0 {
+                t1 = t1.min(na / nd);
+            } else if nd < 0.0 {
+                t0 = t0.max(na / nd);
+            } else if na < 0.0 {
+                return None;
+            }
+        }
+        (t0 < t1).then_some((t0, t1))
+    }
+}
+
+impl Lune {
+    /// Intervals of `ray` inside of the lune
+    fn chords(&self, ray: Ray) -> Vec<Interval> {
+        let Some((t0, t1)) = self.outer.chord(ray) else {
+            return Vec::new();
+        };
+        match self.inner.chord(ray) {
+            Some((c0, c1)) => [(t0, t1.min(c0)), (t0.max(c1), t1)]
+                .into_iter()
+                .filter(|(a, b)| a < b)
+                .collect(),
+            None => vec![(t0, t1)],
+        }
+    }
+}
+
+impl Shape {
+    /// Returns the disjoint intervals of `ray` inside of the shape.
+    fn chords(&self, ray: Ray) -> Vec<Interval> {
+        match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.chord(ray).into_iter().collect(),
+            ShapeKind::Rectangle(shape) => shape.chord(ray).into_iter().collect(),
+            ShapeKind::Lune(shape) => shape.chords(ray),
+            ShapeKind::Polygon(shape) => shape.chord(ray).into_iter().collect(),
+            ShapeKind::Group(shapes) => {
+                // Union of the intervals of the members
+                let mut intervals = shapes
+                    .iter()
+                    .flat_map(|shape| shape.chords(ray))
+                    .collect::<Vec<_>>();
+                intervals.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut union: Vec<Interval> = Vec::with_capacity(intervals.len());
+                for (t0, t1) in intervals {
+                    match union.last_mut() {
+                        Some(last) if t0 <= last.1 => last.1 = last.1.max(t1),
+                        _ => union.push((t0, t1)),
+                    }
+                }
+                union
+            }
+        }
+    }
+
+    /// Returns the line integral of the shape along the ray at angle `theta` (in radians) with
+    /// signed offset `s`, i.e. the intensity times the length of the ray inside of the shape.
+    ///
+    /// The ray consists of all points `(x, y)` with `x * cos(theta) + y * sin(theta) = s` in
+    /// shape coordinates, i.e. on the same canvas `[-1, 1] x [-1, 1]` on which the shapes are
+    /// defined. Note that in contrast to the rotation angles of the shapes, `theta` is given in
+    /// radians. The length is computed analytically for every kind of shape. For a group, the
+    /// length of the ray inside of the union of its members is used. Summing the line integrals
+    /// of all shapes yields the Radon transform of a phantom, see `sinogram`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// use std::f64::consts::FRAC_PI_2;
+    ///
+    /// let ellipse = Shape::ellipse(0.0, 0.0, 0.6, 0.2, 0.0, 2.0);
+    /// // Vertical ray through the center, which crosses the minor axis
+    /// assert!((ellipse.line_integral(0.0, 0.0) - 0.8).abs() < 1e-12);
+    /// // Horizontal ray through the center, which crosses the major axis
+    /// assert!((ellipse.line_integral(FRAC_PI_2, 0.0) - 2.4).abs() < 1e-12);
+    /// // The ray misses the ellipse
+    /// assert_eq!(ellipse.line_integral(0.0, 0.7), 0.0);
+    /// ```
+    pub fn line_integral(&self, theta: f64, s: f64) -> f64 {
+        let length = self
+            .chords(Ray::new(theta, s))
+            .iter()
+            .fold(0.0, |length, (t0, t1)| length + (t1 - t0));
+        self.intensity * length
+    }
+}
+
+/// Computes the analytic Radon transform of `shapes` for `num_angles` angles and
+/// `num_detectors` detector positions.
+///
+/// The angles `theta_i = i * π / num_angles` cover `[0, π)` and the detector positions
+/// `s_j = -1 + (2 j + 1) / num_detectors` are the centers of `num_detectors` equally sized
+/// detector elements covering `[-1, 1]`. The result contains the line integrals of the sum of
+/// the shapes for all angles, where the line integrals of angle `i` at the detector positions
+/// are stored at `i * num_detectors..(i + 1) * num_detectors`. All lengths are measured in shape
+/// coordinates.
+///
+/// The line integrals are computed analytically for every kind of shape, therefore the sinogram
+/// is free of discretization errors. The ray at angle `theta` with offset `s` consists of all
+/// points `(x, y)` with `x * cos(theta) + y * sin(theta) = s`, see `Shape::line_integral`.
+///
+/// # Example
+///
+/// ```
+/// use shepplogan::{sinogram, Shape};
+///
+/// let shapes = [Shape::circle(0.0, 0.0, 0.5, 2.0)];
+/// let projections = sinogram(&shapes, 180, 2);
+/// assert_eq!(projections.len(), 180 * 2);
+/// // Both detectors are at a distance of 0.5 from the center and just touch the circle
+/// assert!(projections.iter().all(|&v| v.abs() < 1e-6));
+///
+/// // A single detector at the center measures the diameter times the intensity
+/// let projections = sinogram(&shapes, 4, 1);
+/// assert!(projections.iter().all(|&v| (v - 2.0).abs() < 1e-12));
+/// ```
+pub fn sinogram(shapes: &[Shape], num_angles: u32, num_detectors: u32) -> Vec<f64> {
+    let mut sinogram = Vec::with_capacity(num_angles as usize * num_detectors as usize);
+    for i in 0..num_angles {
+        let theta = f64::from(i) * std::f64::consts::PI / f64::from(num_angles);
+        for j in 0..num_detectors {
+            let s = -1.0 + f64::from(2 * j + 1) / f64::from(num_detectors);
+            sinogram.push(
+                shapes
+                    .iter()
+                    .fold(0.0, |sum, shape| sum + shape.line_integral(theta, s)),
+            );
+        }
+    }
+    sinogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sinogram;
+    use crate::Shape;
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn test_line_integral() {
+        // Chords of a circle with radius 0.5
+        let circle = Shape::ellipse(0.1, -0.2, 0.5, 0.5, 30.0, 2.0);
+        let center_offset = |theta: f64| 0.1 * theta.cos() - 0.2 * theta.sin();
+        for theta in [0.0, 0.3, FRAC_PI_2, 2.0] {
+            let s = center_offset(theta);
+            assert_abs_diff_eq!(circle.line_integral(theta, s), 2.0, epsilon = 1e-12);
+            assert_abs_diff_eq!(
+                circle.line_integral(theta, s + 0.3),
+                2.0 * 2.0 * 0.4,
+                epsilon = 1e-12
+            );
+            assert_eq!(circle.line_integral(theta, s + 0.6), 0.0);
+        }
+
+        // Vertical rays through an ellipse are as long as the vertical axis
+        let ellipse = Shape::ellipse(0.0, 0.0, 0.6, 0.2, 0.0, 1.0);
+        assert_abs_diff_eq!(ellipse.line_integral(0.0, 0.0), 0.4, epsilon = 1e-12);
+        assert_abs_diff_eq!(ellipse.line_integral(FRAC_PI_2, 0.0), 1.2, epsilon = 1e-12);
+
+        // Square with side 1 rotated by 45 degrees
+        let square = Shape::rectangle(0.0, 0.0, 1.0, 1.0, 45.0, 1.0);
+        assert_abs_diff_eq!(square.line_integral(0.0, 0.0), 2f64.sqrt(), epsilon = 1e-12);
+        assert_abs_diff_eq!(square.line_integral(FRAC_PI_4, 0.2), 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(square.line_integral(FRAC_PI_4, 0.5), 1.0, epsilon = 1e-12);
+        assert_eq!(square.line_integral(FRAC_PI_4, 0.6), 0.0);
+
+        // The same square as a polygon
+        let square = Shape::polygon(
+            &[(-0.5, -0.5), (-0.5, 0.5), (0.5, 0.5), (0.5, -0.5)],
+            45.0,
+            1.0,
+        )
+        .unwrap();
+        assert_abs_diff_eq!(square.line_integral(0.0, 0.0), 2f64.sqrt(), epsilon = 1e-12);
+        assert_abs_diff_eq!(square.line_integral(FRAC_PI_4, 0.2), 1.0, epsilon = 1e-12);
+        assert_eq!(square.line_integral(FRAC_PI_4, 0.6), 0.0);
+        // Horizontal rays through a triangle
+        let triangle = Shape::polygon(&[(0.0, 0.0), (0.5, 0.0), (0.0, 0.5)], 0.0, 2.0).unwrap();
+        assert_abs_diff_eq!(
+            triangle.line_integral(FRAC_PI_2, 0.1),
+            2.0 * 0.4,
+            epsilon = 1e-12
+        );
+        assert_eq!(triangle.line_integral(FRAC_PI_2, -0.1), 0.0);
+
+        // Horizontal ray through the center of a lune, whose inner circle is shifted to the right
+        let lune = Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 1.0);
+        assert_abs_diff_eq!(lune.line_integral(FRAC_PI_2, 0.0), 0.25, epsilon = 1e-12);
+        // Inner circle inside of the outer circle: two intervals
+        let lune = Shape::lune(0.0, 0.0, 0.5, 0.0, 0.0, 0.25, 1.0);
+        assert_abs_diff_eq!(lune.line_integral(1.0, 0.0), 0.5, epsilon = 1e-12);
+
+        // Overlapping members of a group are only counted once
+        let group = Shape::group(
+            vec![
+                Shape::rectangle(-0.25, 0.0, 0.5, 0.5, 0.0, 0.0),
+                Shape::rectangle(0.0, 0.0, 0.5, 0.5, 0.0, 0.0),
+                Shape::rectangle(0.6, 0.0, 0.2, 0.2, 0.0, 0.0),
+            ],
+            3.0,
+        );
+        assert_abs_diff_eq!(
+            group.line_integral(FRAC_PI_2, 0.0),
+            3.0 * 0.95,
+            epsilon = 1e-12
+        );
+        assert_eq!(Shape::group(vec![], 1.0).line_integral(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_sinogram() {
+        // The projections of a centered circle do not depend on the angle
+        let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+        let sinogram = sinogram(&shapes, 6, 8);
+        assert_eq!(sinogram.len(), 6 * 8);
+        for projection in sinogram.chunks(8) {
+            for (j, &value) in projection.iter().enumerate() {
+                let s = -1.0 + (2.0 * j as f64 + 1.0) / 8.0;
+                let expected = 2.0 * (0.25 - s * s).max(0.0).sqrt();
+                assert_abs_diff_eq!(value, expected, epsilon = 1e-12);
+            }
+        }
+        // The integral over all detector positions is the area times the intensity
+        let shapes = [Shape::rectangle(0.1, 0.0, 0.4, 0.6, 20.0, 2.0)];
+        for projection in super::sinogram(&shapes, 4, 2000).chunks(2000) {
+            let integral = projection.iter().sum::<f64>() * 2.0 / 2000.0;
+            assert_abs_diff_eq!(integral, 2.0 * 0.24, epsilon = 1e-3);
+        }
+        assert!(super::sinogram(&shapes, 0, 5).is_empty());
+    }
+}
@@ -3,10 +3,12 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use super::BoundingBox;
+use super::{BoundingBox, Canvas};
+use crate::Boundary;
 
 /// Representation of a Rectangle
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Rectangle {
     /// x-coordinate of center
     pub(crate) center_x: f64,
@@ -42,8 +44,124 @@ impl Rectangle {
         }
     }
 
+    /// Area of the rectangle in shape coordinates
+    pub(crate) fn area(&self) -> f64 {
+        (self.width * self.height).abs()
+    }
+
+    /// Perimeter of the rectangle in shape coordinates
+    pub(crate) fn perimeter(&self) -> f64 {
+        2.0 * (self.width.abs() + self.height.abs())
+    }
+
+    /// Bounding box `(x_min, x_max, y_min, y_max)` of the rectangle in shape coordinates
+    pub(crate) fn bounds(&self) -> (f64, f64, f64, f64) {
+        let (theta_sin, theta_cos) = self.theta.to_radians().sin_cos();
+        let (width_half, height_half) = (self.width / 2.0, self.height / 2.0);
+        let half_width = (width_half * theta_cos).abs() + (height_half * theta_sin).abs();
+        let half_height = (width_half * theta_sin).abs() + (height_half * theta_cos).abs();
+        (
+            self.center_x - half_width,
+            self.center_x + half_width,
+            self.center_y - half_height,
+            self.center_y + half_height,
+        )
+    }
+
+    /// Returns the parameters `[center_x, center_y, width, height, theta]`.
+    pub(crate) fn params(&self) -> [f64; 5] {
+        [
+            self.center_x,
+            self.center_y,
+            self.width,
+            self.height,
+            self.theta,
+        ]
+    }
+
+    /// Returns the bit patterns of the parameters.
+    pub(crate) fn to_bits(&self) -> [u64; 5] {
+        self.params().map(f64::to_bits)
+    }
+
+    /// Returns `true` if all parameters are finite.
+    pub(crate) fn is_finite(&self) -> bool {
+        [
+            self.center_x,
+            self.center_y,
+            self.width,
+            self.height,
+            self.theta,
+        ]
+        .iter()
+        .all(|x| x.is_finite())
+    }
+
+    /// Transforms a rectangle with `theta == 0.0` onto the canvas.
+    ///
+    /// In contrast to `on_canvas`, the resulting rectangle stores integer pixel bounds which allow
+    /// for a cheaper check whether a pixel is inside. For a rectangle with `theta == 0.0` the
+    /// result is the same as for `on_canvas`.
+    #[inline(always)]
+    pub(crate) fn on_canvas_axis_aligned(&self, canvas: &Canvas) -> AxisAlignedRectangleOnCanvas {
+        let Self {
+            center_x,
+            center_y,
+            width,
+            height,
+            theta: _,
+        } = self;
+        let Canvas { nx, ny, .. } = *canvas;
+
+        let width_half = width / 2.0;
+        let height_half = height / 2.0;
+
+        // Edges of the rectangle (the width and height may be negative)
+        let (a_x, a_y) = canvas.map(center_x - width_half, center_y - height_half);
+        let (c_x, c_y) = canvas.map(center_x + width_half, center_y + height_half);
+        let edges = |a: f64, b: f64| if a <= b { (a, b) } else { (b, a) };
+        let (x_min, x_max) = edges(a_x, c_x);
+        let (y_min, y_max) = edges(a_y, c_y);
+
+        // Integer pixels which are inside of the rectangle. `NaN` leads to an empty rectangle.
+        let pixels = |min: f64, max: f64| match canvas.boundary {
+            Boundary::Inclusive if min <= max => (min.ceil() as i64, max.floor() as i64),
+            Boundary::Exclusive if min <= max => (min.floor() as i64 + 1, max.ceil() as i64 - 1),
+            _ => (1, 0),
+        };
+        let (x_low, x_high) = pixels(x_min, x_max);
+        let (y_low, y_high) = pixels(y_min, y_max);
+
+        // Clamp to the canvas. An empty range is represented by `low > high`.
+        let clamp = |low: i64, high: i64, n: u32| {
+            let n = i64::from(n);
+            let low = low.max(0);
+            let high = high.min(n - 1);
+            if low <= high {
+                (low as u32, high as u32)
+            } else {
+                (1, 0)
+            }
+        };
+        let (bbox_x_low, bbox_x_high) = clamp(x_low, x_high, nx);
+        let (bbox_y_low, bbox_y_high) = clamp(y_low, y_high, ny);
+
+        AxisAlignedRectangleOnCanvas {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            x_low,
+            x_high,
+            y_low,
+            y_high,
+            bbox: (bbox_x_low, bbox_x_high, bbox_y_low, bbox_y_high).into(),
+            boundary: canvas.boundary,
+        }
+    }
+
     #[inline(always)]
-    pub(crate) fn on_canvas(&self, nx: u32, ny: u32) -> RectangleOnCanvas {
+    pub(crate) fn on_canvas(&self, canvas: &Canvas) -> RectangleOnCanvas {
         let Self {
             center_x,
             center_y,
@@ -55,12 +173,6 @@ impl Rectangle {
         let theta = theta.to_radians();
         let theta_sin = theta.sin();
         let theta_cos = theta.cos();
-        let nx_f = f64::from(nx);
-        let ny_f = f64::from(ny);
-        let nx_half = nx_f / 2.0;
-        let ny_half = ny_f / 2.0;
-        // Everything is going to be scaled by the smaller dimension
-        let n_min = std::cmp::min_by(nx_half, ny_half, |nx, ny| nx.partial_cmp(ny).unwrap());
 
         let width_half = width / 2.0;
         let height_half = height / 2.0;
@@ -89,33 +201,34 @@ impl Rectangle {
         let (d_xr, d_yr) = rotate_and_shift(d_x, d_y);
 
         // Now scale and shift them onto the new canvas
-        let scale_shift = |x: f64, y: f64| (x * n_min + nx_half, y * n_min + ny_half);
-
-        let (a_xr, a_yr) = scale_shift(a_xr, a_yr);
-        let (b_xr, b_yr) = scale_shift(b_xr, b_yr);
-        let (c_xr, c_yr) = scale_shift(c_xr, c_yr);
-        let (d_xr, d_yr) = scale_shift(d_xr, d_yr);
-
-        // compute the minimum and maximum coordinates for the bounding box.
-        let min_max = |arr: &[f64], max: f64| {
-            (
-                arr.iter()
-                    .cloned()
-                    .map(f64::floor)
-                    .map(|x| if x < 0.0 { 0.0 } else { x })
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap() as u32,
-                arr.iter()
-                    .cloned()
-                    .map(f64::ceil)
-                    .map(|x| if x > max { max } else { x })
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap() as u32,
-            )
+        let (a_xr, a_yr) = canvas.map(a_xr, a_yr);
+        let (b_xr, b_yr) = canvas.map(b_xr, b_yr);
+        let (c_xr, c_yr) = canvas.map(c_xr, c_yr);
+        let (d_xr, d_yr) = canvas.map(d_xr, d_yr);
+
+        // compute the minimum and maximum coordinates for the bounding box. In contrast to
+        // `f64::min`, `NaN` is propagated, which leads to an empty bounding box.
+        let min_max = |arr: [f64; 4]| {
+            arr.into_iter()
+                .skip(1)
+                .fold((arr[0], arr[0]), |(min, max), x| {
+                    (
+                        if x < min || x.is_nan() { x } else { min },
+                        if x > max || x.is_nan() { x } else { max },
+                    )
+                })
         };
-
-        let (x_min, x_max) = min_max(&[a_xr, b_xr, c_xr, d_xr], nx_f);
-        let (y_min, y_max) = min_max(&[a_yr, b_yr, c_yr, d_yr], ny_f);
+        let (x_min, x_max) = min_max([a_xr, b_xr, c_xr, d_xr]);
+        let (y_min, y_max) = min_max([a_yr, b_yr, c_yr, d_yr]);
+        let bbox = BoundingBox::clamped(
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            canvas.nx,
+            canvas.ny,
+            canvas.bbox_rounding,
+        );
 
         // Helper variables to make computing whether a point is inside or not easier later on
         let ab = (b_xr - a_xr, b_yr - a_yr);
@@ -132,7 +245,8 @@ impl Rectangle {
             bc,
             abab,
             bcbc,
-            bbox: (x_min, x_max, y_min, y_max).into(),
+            bbox,
+            boundary: canvas.boundary,
         }
     }
 }
@@ -148,6 +262,8 @@ pub(crate) struct RectangleOnCanvas {
     bcbc: f64,
     /// bounding box
     bbox: BoundingBox,
+    /// whether points on the boundary are inside
+    boundary: Boundary,
 }
 
 impl RectangleOnCanvas {
@@ -163,14 +279,88 @@ impl RectangleOnCanvas {
         let bm = (x - self.b.0, y - self.b.1);
         let abam = self.ab.0 * am.0 + self.ab.1 * am.1;
         let bcbm = self.bc.0 * bm.0 + self.bc.1 * bm.1;
-        0.0 <= abam && abam <= self.abab && 0.0 <= bcbm && bcbm <= self.bcbc
+        let le = |a, b| self.boundary.le(a, b);
+        le(0.0, abam) && le(abam, self.abab) && le(0.0, bcbm) && le(bcbm, self.bcbc)
+    }
+
+    /// Signed distance of a point to the outline of the rectangle (negative inside)
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        let am = (x - self.a.0, y - self.a.1);
+        let bm = (x - self.b.0, y - self.b.1);
+        let ab = self.abab.sqrt();
+        let bc = self.bcbc.sqrt();
+        // Coordinates of the point along the edges `ab` and `bc`
+        let s = (self.ab.0 * am.0 + self.ab.1 * am.1) / ab;
+        let t = (self.bc.0 * bm.0 + self.bc.1 * bm.1) / bc;
+        box_distance(s, 0.0, ab, t, 0.0, bc)
+    }
+}
+
+/// Representation of an axis aligned rectangle on a canvas
+#[derive(PartialEq, Clone, Debug)]
+pub(crate) struct AxisAlignedRectangleOnCanvas {
+    x_min: f64,
+    x_max: f64,
+    y_min: f64,
+    y_max: f64,
+    /// smallest pixel in x direction which is inside
+    x_low: i64,
+    /// largest pixel in x direction which is inside
+    x_high: i64,
+    /// smallest pixel in y direction which is inside
+    y_low: i64,
+    /// largest pixel in y direction which is inside
+    y_high: i64,
+    /// bounding box
+    bbox: BoundingBox,
+    /// whether points on the boundary are inside
+    boundary: Boundary,
+}
+
+impl AxisAlignedRectangleOnCanvas {
+    #[inline(always)]
+    pub(crate) fn bounding_box(&self) -> BoundingBox {
+        self.bbox
+    }
+
+    /// Checks if a point is inside the rectangle
+    #[inline(always)]
+    pub(crate) fn inside(&self, x: f64, y: f64) -> bool {
+        let le = |a, b| self.boundary.le(a, b);
+        le(self.x_min, x) && le(x, self.x_max) && le(self.y_min, y) && le(y, self.y_max)
+    }
+
+    /// Signed distance of a point to the outline of the rectangle (negative inside)
+    pub(crate) fn signed_distance(&self, x: f64, y: f64) -> f64 {
+        box_distance(x, self.x_min, self.x_max, y, self.y_min, self.y_max)
+    }
+
+    /// Checks if a pixel is inside the rectangle without any floating point operations
+    #[inline(always)]
+    pub(crate) fn inside_pixel(&self, x: u32, y: u32) -> bool {
+        let x = i64::from(x);
+        let y = i64::from(y);
+        self.x_low <= x && x <= self.x_high && self.y_low <= y && y <= self.y_high
+    }
+}
+
+/// Signed distance of the point `(x, y)` to the outline of the axis aligned box
+/// `[x_min, x_max] x [y_min, y_max]` (negative inside)
+fn box_distance(x: f64, x_min: f64, x_max: f64, y: f64, y_min: f64, y_max: f64) -> f64 {
+    let dx = (x_min - x).max(x - x_max);
+    let dy = (y_min - y).max(y - y_max);
+    if dx > 0.0 || dy > 0.0 {
+        dx.max(0.0).hypot(dy.max(0.0))
+    } else {
+        dx.max(dy)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Rectangle;
+    use super::{BoundingBox, Canvas, Rectangle};
     use approx::assert_abs_diff_eq;
+    use quickcheck::TestResult;
 
     #[derive(Debug, Copy, Clone)]
     struct FloatLim(f64);
@@ -248,7 +438,7 @@ mod tests {
 
         let rectangle = Rectangle::new(center_x, center_y, width, height, theta);
 
-        let rectangle_on_canvas = rectangle.on_canvas(nx, ny);
+        let rectangle_on_canvas = rectangle.on_canvas(&Canvas::new(nx, ny));
         let rectangle_on_canvas2 = rectangle_on_canvas.clone();
 
         let theta = rectangle.theta.to_radians();
@@ -305,7 +495,7 @@ mod tests {
                 arr.iter()
                     .cloned()
                     .map(f64::ceil)
-                    .map(|x| if x > max { max } else { x })
+                    .map(|x| if x > max - 1.0 { max - 1.0 } else { x })
                     .max_by(|a, b| a.partial_cmp(b).unwrap())
                     .unwrap() as u32,
             )
@@ -332,14 +522,20 @@ mod tests {
         assert_abs_diff_eq!(rectangle_on_canvas.bc.1, bc.1);
         assert_abs_diff_eq!(rectangle_on_canvas.abab, abab);
         assert_abs_diff_eq!(rectangle_on_canvas.bcbc, bcbc);
-        assert_eq!(
-            rectangle_on_canvas.bbox,
-            (x_min, x_max, y_min, y_max).into()
-        );
-        assert_eq!(
-            rectangle_on_canvas.bounding_box(),
+        // Rectangles entirely outside of the canvas have an empty bounding box
+        let corners_x = [a_xr, b_xr, c_xr, d_xr];
+        let corners_y = [a_yr, b_yr, c_yr, d_yr];
+        let outside = corners_x.iter().all(|x| x.ceil() < 0.0)
+            || corners_x.iter().all(|x| x.floor() >= nx_f)
+            || corners_y.iter().all(|y| y.ceil() < 0.0)
+            || corners_y.iter().all(|y| y.floor() >= ny_f);
+        let bbox: BoundingBox = if outside {
+            BoundingBox::empty()
+        } else {
             (x_min, x_max, y_min, y_max).into()
-        );
+        };
+        assert_eq!(rectangle_on_canvas.bbox, bbox);
+        assert_eq!(rectangle_on_canvas.bounding_box(), bbox);
     }
 
     #[quickcheck]
@@ -360,8 +556,8 @@ mod tests {
         let width = width.0;
         let height = 2.0 * width;
 
-        let rectangle =
-            Rectangle::new(center_x.0, center_y.0, width, height, theta.0).on_canvas(nx, ny);
+        let rectangle = Rectangle::new(center_x.0, center_y.0, width, height, theta.0)
+            .on_canvas(&Canvas::new(nx, ny));
 
         let am = (x - rectangle.a.0, y - rectangle.a.1);
         let bm = (x - rectangle.b.0, y - rectangle.b.1);
@@ -371,4 +567,62 @@ mod tests {
 
         rectangle.inside(x, y) == inside
     }
+
+    #[quickcheck]
+    fn test_axis_aligned(
+        center_x: FloatLim,
+        center_y: FloatLim,
+        width: FloatLim,
+        height: FloatLim,
+        nx: u8,
+        ny: u8,
+    ) -> TestResult {
+        let nx = u32::from(nx);
+        let ny = u32::from(ny);
+        // Keep the rectangle close to the canvas
+        let rectangle = Rectangle::new(
+            center_x.0 % 1.5,
+            center_y.0 % 1.5,
+            width.0 % 3.0,
+            height.0 % 3.0,
+            0.0,
+        );
+        // The general path does not handle rectangles with zero width or height
+        if rectangle.width == 0.0 || rectangle.height == 0.0 {
+            return TestResult::discard();
+        }
+
+        let general = rectangle.on_canvas(&Canvas::new(nx, ny));
+        let axis_aligned = rectangle.on_canvas_axis_aligned(&Canvas::new(nx, ny));
+
+        let bbox = axis_aligned.bounding_box();
+        for x in 0..nx {
+            for y in 0..ny {
+                let inside = general.inside(f64::from(x), f64::from(y));
+                assert_eq!(axis_aligned.inside_pixel(x, y), inside);
+                assert_eq!(axis_aligned.inside(f64::from(x), f64::from(y)), inside);
+                if inside {
+                    assert!(bbox.x_low <= x && x <= bbox.x_high);
+                    assert!(bbox.y_low <= y && y <= bbox.y_high);
+                }
+            }
+        }
+        TestResult::passed()
+    }
+
+    #[test]
+    fn test_axis_aligned_empty() {
+        // Entirely outside of the canvas
+        let bbox = Rectangle::new(-3.0, 0.0, 0.5, 0.5, 0.0)
+            .on_canvas_axis_aligned(&Canvas::new(64, 64))
+            .bounding_box();
+        assert!(bbox.x_low > bbox.x_high);
+
+        // Thinner than a pixel and between two pixels
+        let rectangle =
+            Rectangle::new(0.01, 0.0, 0.001, 0.5, 0.0).on_canvas_axis_aligned(&Canvas::new(64, 64));
+        assert!(rectangle.bounding_box().x_low > rectangle.bounding_box().x_high);
+        assert!(rectangle.inside(32.32, 32.0));
+        assert!(!rectangle.inside_pixel(32, 32));
+    }
 }
@@ -0,0 +1,388 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use super::{
+    ellipse::Ellipse, lune::Lune, polygon::Polygon, rectangle::Rectangle, Canvas, Shape, ShapeKind,
+};
+
+/// Mapping of the shape coordinates onto SVG coordinates of an image of size `nx` times `ny`
+///
+/// Pixel `(x, y)` of a phantom is the unit square `[x, x + 1] x [ny - y - 1, ny - y]` in SVG
+/// coordinates, whose center is the point sampled when rendering the phantom. The y axis of SVG
+/// points downwards, therefore rotations are reversed.
+struct SvgCanvas {
+    canvas: Canvas,
+}
+
+impl SvgCanvas {
+    fn new(nx: u32, ny: u32) -> Self {
+        SvgCanvas {
+            canvas: Canvas::new(nx, ny),
+        }
+    }
+
+    /// Maps the point `(x, y)` given in shape coordinates onto SVG coordinates.
+    fn map(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x, y) = self.canvas.map(x, y);
+        (x + 0.5, f64::from(self.canvas.ny) - 0.5 - y)
+    }
+
+    /// Maps a length given in shape coordinates onto SVG coordinates.
+    fn length(&self, length: f64) -> f64 {
+        (length * self.canvas.scale).abs()
+    }
+}
+
+/// Returns the `transform` attribute for a rotation by `theta` degrees (counterclockwise on the
+/// canvas) around `(cx, cy)`.
+fn rotation(theta: f64, cx: f64, cy: f64) -> String {
+    if theta == 0.0 {
+        String::new()
+    } else {
+        format!(r#" transform="rotate({} {} {})""#, -theta, cx, cy)
+    }
+}
+
+/// Path data of a full circle
+fn circle_path(cx: f64, cy: f64, r: f64) -> String {
+    format!(
+        "M {} {} A {r} {r} 0 1 0 {} {} A {r} {r} 0 1 0 {} {} Z",
+        cx - r,
+        cy,
+        cx + r,
+        cy,
+        cx - r,
+        cy,
+    )
+}
+
+impl Ellipse {
+    fn to_svg(&self, svg: &SvgCanvas, attributes: &str) -> String {
+        let (cx, cy) = svg.map(self.center_x, self.center_y);
+        format!(
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}{}/>"#,
+            cx,
+            cy,
+            svg.length(self.major_axis),
+            svg.length(self.minor_axis),
+            rotation(self.theta, cx, cy),
+            attributes,
+        )
+    }
+}
+
+impl Rectangle {
+    fn to_svg(&self, svg: &SvgCanvas, attributes: &str) -> String {
+        let (cx, cy) = svg.map(self.center_x, self.center_y);
+        let width = svg.length(self.width);
+        let height = svg.length(self.height);
+        format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}"{}{}/>"#,
+            cx - width / 2.0,
+            cy - height / 2.0,
+            width,
+            height,
+            rotation(self.theta, cx, cy),
+            attributes,
+        )
+    }
+}
+
+impl Lune {
+    fn to_svg(&self, svg: &SvgCanvas, attributes: &str) -> String {
+        let (ox, oy) = svg.map(self.outer.center_x, self.outer.center_y);
+        let (ix, iy) = svg.map(self.inner.center_x, self.inner.center_y);
+        let r_outer = svg.length(self.outer.major_axis);
+        let r_inner = svg.length(self.inner.major_axis);
+        let d = (ix - ox).hypot(iy - oy);
+
+        let path = if d >= r_outer + r_inner {
+            // The circles are disjoint
+            circle_path(ox, oy, r_outer)
+        } else if d <= r_inner - r_outer {
+            // The inner circle covers the outer circle
+            String::new()
+        } else if d <= r_outer - r_inner {
+            // The outer circle contains the inner circle, which is cut out as a hole
+            format!(
+                "{} {}",
+                circle_path(ox, oy, r_outer),
+                circle_path(ix, iy, r_inner)
+            )
+        } else {
+            // The lune is bounded by an arc of the outer circle facing away from the inner circle
+            // and an arc of the inner circle between the two intersections of the circles.
+            let e = ((ix - ox) / d, (iy - oy) / d);
+            let n = (-e.1, e.0);
+            // Distance of the chord through the intersections from the center of the outer circle
+            let a = (d * d + r_outer * r_outer - r_inner * r_inner) / (2.0 * d);
+            let h = (r_outer * r_outer - a * a).max(0.0).sqrt();
+            let p1 = (ox + a * e.0 + h * n.0, oy + a * e.1 + h * n.1);
+            let p2 = (ox + a * e.0 - h * n.0, oy + a * e.1 - h * n.1);
+            format!(
+                "M {} {} A {r_outer} {r_outer} 0 {} 1 {} {} A {r_inner} {r_inner} 0 {} 0 {} {} Z",
+                p1.0,
+                p1.1,
+                u8::from(a > 0.0),
+                p2.0,
+                p2.1,
+                u8::from(d - a < 0.0),
+                p1.0,
+                p1.1,
+            )
+        };
+        format!(r#"<path d="{}" fill-rule="evenodd"{}/>"#, path, attributes)
+    }
+}
+
+impl Polygon {
+    fn to_svg(&self, svg: &SvgCanvas, attributes: &str) -> String {
+        let points = self
+            .rotated_vertices()
+            .into_iter()
+            .map(|(x, y)| {
+                let (x, y) = svg.map(x, y);
+                format!("{},{}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(r#"<polygon points="{}"{}/>"#, points, attributes)
+    }
+}
+
+impl Shape {
+    /// Returns an SVG element describing the shape in the pixel coordinates of a phantom of size
+    /// `nx` times `ny`.
+    ///
+    /// Ellipses are described by an `ellipse` element, rectangles by a `rect` element, lunes by a
+    /// `path` element, polygons by a `polygon` element and groups by a `g` element containing
+    /// their members. The coordinates match
+    /// an image of the phantom (e.g. created from `into_vec`) with one unit per pixel, where the
+    /// pixel in row `r` (counted from the top) and column `c` covers `[c, c + 1] x [r, r + 1]`.
+    /// No fill or stroke is specified, which means that the element is filled in black by
+    /// default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use shepplogan::Shape;
+    /// let circle = Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0);
+    /// assert_eq!(
+    ///     circle.to_svg_path(64, 64),
+    ///     r#"<ellipse cx="32.5" cy="31.5" rx="16" ry="16"/>"#
+    /// );
+    /// ```
+    pub fn to_svg_path(&self, nx: u32, ny: u32) -> String {
+        self.svg_element(&SvgCanvas::new(nx, ny), "")
+    }
+
+    /// Returns an SVG element describing the shape with additional `attributes`.
+    ///
+    /// The attributes are inserted verbatim into the element and need to start with a space.
+    fn svg_element(&self, svg: &SvgCanvas, attributes: &str) -> String {
+        match &self.kind {
+            ShapeKind::Ellipse(shape) => shape.to_svg(svg, attributes),
+            ShapeKind::Rectangle(shape) => shape.to_svg(svg, attributes),
+            ShapeKind::Lune(shape) => shape.to_svg(svg, attributes),
+            ShapeKind::Polygon(shape) => shape.to_svg(svg, attributes),
+            ShapeKind::Group(shapes) => {
+                let members = shapes
+                    .iter()
+                    .map(|shape| shape.svg_element(svg, ""))
+                    .collect::<String>();
+                format!("<g{}>{}</g>", attributes, members)
+            }
+        }
+    }
+}
+
+/// Creates an SVG document showing `shapes` on a phantom of size `nx` times `ny`.
+///
+/// The document contains a black background followed by one element per shape (see
+/// `Shape::to_svg_path`) in the order of `shapes`. Shapes with a positive intensity are filled in
+/// white, shapes with a negative intensity in black, where the fill opacity is proportional to the
+/// absolute value of the intensity: The shape with the largest absolute intensity is opaque. Note
+/// that this only approximates the rendered phantom, since overlapping shapes are blended instead
+/// of added. The `viewBox` is `0 0 nx ny`, which means that one unit corresponds to one pixel.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{phantom_to_svg, Shape};
+/// let shapes = [
+///     Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+///     Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.98),
+/// ];
+/// let svg = phantom_to_svg(&shapes, 256, 256);
+/// assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>"));
+/// ```
+pub fn phantom_to_svg(shapes: &[Shape], nx: u32, ny: u32) -> String {
+    let svg = SvgCanvas::new(nx, ny);
+    let max_intensity = shapes
+        .iter()
+        .map(|shape| shape.intensity.abs())
+        .fold(0.0, f64::max);
+    let mut document = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{nx}" height="{ny}" viewBox="0 0 {nx} {ny}">"#
+    );
+    document.push_str(&format!(
+        r#"<rect x="0" y="0" width="{nx}" height="{ny}" fill="black"/>"#
+    ));
+    for shape in shapes {
+        let fill = if shape.intensity < 0.0 {
+            "black"
+        } else {
+            "white"
+        };
+        let opacity = if max_intensity > 0.0 {
+            shape.intensity.abs() / max_intensity
+        } else {
+            0.0
+        };
+        let attributes = format!(r#" fill="{}" fill-opacity="{}""#, fill, opacity);
+        document.push_str(&shape.svg_element(&svg, &attributes));
+    }
+    document.push_str("</svg>");
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{phantom_to_svg, Shape};
+
+    /// Parses `element` as SVG and returns the root element's name and attributes.
+    fn parse(element: &str) -> (String, Vec<(String, String)>) {
+        let document = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg">{}</svg>"#,
+            element
+        );
+        let document = roxmltree::Document::parse(&document).unwrap();
+        let node = document.root_element().first_element_child().unwrap();
+        let attributes = node
+            .attributes()
+            .map(|a| (a.name().to_string(), a.value().to_string()))
+            .collect();
+        (node.tag_name().name().to_string(), attributes)
+    }
+
+    fn attribute(attributes: &[(String, String)], name: &str) -> f64 {
+        attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .unwrap()
+            .1
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_to_svg_path() {
+        // The canvas is scaled by 50 onto the smaller dimension and shifted by (100, 50)
+        let (nx, ny) = (200, 100);
+        let ellipse = Shape::ellipse(0.2, -0.4, 0.6, 0.3, 30.0, 1.0);
+        let (name, attributes) = parse(&ellipse.to_svg_path(nx, ny));
+        assert_eq!(name, "ellipse");
+        assert_eq!(attribute(&attributes, "cx"), 0.2 * 50.0 + 100.0 + 0.5);
+        assert_eq!(
+            attribute(&attributes, "cy"),
+            100.0 - 0.5 - (-0.4 * 50.0 + 50.0)
+        );
+        assert_eq!(attribute(&attributes, "rx"), 30.0);
+        assert_eq!(attribute(&attributes, "ry"), 15.0);
+        assert!(attributes
+            .iter()
+            .any(|(n, v)| n == "transform" && v.starts_with("rotate(-30 ")));
+
+        let rectangle = Shape::rectangle(0.0, 0.0, 0.4, -0.2, 0.0, 1.0);
+        let (name, attributes) = parse(&rectangle.to_svg_path(nx, ny));
+        assert_eq!(name, "rect");
+        assert_eq!(attribute(&attributes, "x"), 100.5 - 10.0);
+        assert_eq!(attribute(&attributes, "y"), 49.5 - 5.0);
+        assert_eq!(attribute(&attributes, "width"), 20.0);
+        assert_eq!(attribute(&attributes, "height"), 10.0);
+        assert!(!attributes.iter().any(|(n, _)| n == "transform"));
+
+        let triangle = Shape::polygon(&[(0.0, 0.0), (0.4, 0.0), (0.0, 0.2)], 0.0, 1.0).unwrap();
+        let (name, attributes) = parse(&triangle.to_svg_path(nx, ny));
+        assert_eq!(name, "polygon");
+        assert_eq!(
+            attributes.iter().find(|(n, _)| n == "points").unwrap().1,
+            "100.5,49.5 120.5,49.5 100.5,39.5"
+        );
+
+        let group = Shape::group(vec![ellipse, rectangle], 1.0);
+        assert!(group.to_svg_path(nx, ny).starts_with("<g><ellipse "));
+        assert!(group.to_svg_path(nx, ny).ends_with("/></g>"));
+        let (name, _) = parse(&group.to_svg_path(nx, ny));
+        assert_eq!(name, "g");
+    }
+
+    #[test]
+    fn test_lune_to_svg_path() {
+        let path = |shape: Shape| {
+            let (name, attributes) = parse(&shape.to_svg_path(64, 64));
+            assert_eq!(name, "path");
+            attributes.into_iter().find(|(n, _)| n == "d").unwrap().1
+        };
+        // Intersecting circles: two arcs
+        let d = path(Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 1.0));
+        assert_eq!(d.matches('A').count(), 2);
+        assert!(d.starts_with("M ") && d.ends_with(" Z"));
+        // Disjoint circles: the outer circle
+        let d = path(Shape::lune(0.0, 0.0, 0.25, 0.75, 0.0, 0.25, 1.0));
+        assert!(d.starts_with("M 24.5 31.5 A 8 8 "));
+        assert_eq!(d.matches('M').count(), 1);
+        // Inner circle inside of the outer circle: hole
+        let d = path(Shape::lune(0.0, 0.0, 0.5, 0.1, 0.0, 0.2, 1.0));
+        assert_eq!(d.matches('M').count(), 2);
+        // Inner circle covers the outer circle: empty
+        assert!(path(Shape::lune(0.0, 0.0, 0.2, 0.0, 0.0, 0.5, 1.0)).is_empty());
+    }
+
+    #[test]
+    fn test_phantom_to_svg() {
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+            Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.98),
+            Shape::rectangle(0.2, 0.1, 0.3, 0.2, 10.0, 0.5),
+            Shape::lune(0.0, 0.0, 0.5, 0.25, 0.0, 0.5, 1.0),
+            Shape::group(
+                vec![
+                    Shape::ellipse(-0.2, 0.0, 0.1, 0.1, 0.0, 0.0),
+                    Shape::ellipse(0.2, 0.0, 0.1, 0.1, 0.0, 0.0),
+                ],
+                -1.0,
+            ),
+        ];
+        let svg = phantom_to_svg(&shapes, 256, 128);
+        let document = roxmltree::Document::parse(&svg).unwrap();
+        let root = document.root_element();
+        assert_eq!(root.tag_name().name(), "svg");
+        assert_eq!(root.attribute("viewBox"), Some("0 0 256 128"));
+        assert_eq!(root.attribute("width"), Some("256"));
+        assert_eq!(root.attribute("height"), Some("128"));
+
+        // Background followed by one element per shape
+        let elements = root
+            .children()
+            .filter(|n| n.is_element())
+            .collect::<Vec<_>>();
+        assert_eq!(elements.len(), shapes.len() + 1);
+        let names = elements
+            .iter()
+            .map(|n| n.tag_name().name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["rect", "ellipse", "ellipse", "rect", "path", "g"]);
+        let opacities = elements[1..]
+            .iter()
+            .map(|n| n.attribute("fill-opacity").unwrap().parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(opacities, [1.0, 0.49, 0.25, 0.5, 0.5]);
+        assert_eq!(elements[2].attribute("fill"), Some("black"));
+        assert_eq!(elements[3].attribute("fill"), Some("white"));
+
+        assert!(phantom_to_svg(&[], 8, 8).contains(r#"viewBox="0 0 8 8""#));
+    }
+}
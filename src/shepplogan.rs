@@ -3,7 +3,46 @@
 // http://opensource.org/licenses/MIT>, at your option. This file may not be
 // copied, modified, or distributed except according to those terms.
 
-use crate::{Phantom, Shape};
+use crate::{shape::sinogram, Phantom, Shape};
+
+/// The standard phantoms which can be re-rendered with the other intensity table
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Preset {
+    /// Original Shepp-Logan phantom, see `shepplogan`
+    Original,
+    /// Modified Shepp-Logan phantom, see `shepplogan_modified`
+    Modified,
+}
+
+impl Preset {
+    /// Returns the ellipses of the preset.
+    pub(crate) fn shapes(self) -> [Shape; 10] {
+        // The tables only differ in their intensities
+        let intensities = match self {
+            Preset::Original => [0.01, 0.01, 0.01, 0.01, 0.01, 0.01, -0.02, -0.02, -0.98, 2.0],
+            Preset::Modified => [0.1, 0.1, 0.1, 0.1, 0.1, 0.1, -0.2, -0.2, -0.8, 1.0],
+        };
+        [
+            Shape::ellipse(0.0, 0.35, 0.21, 0.25, 0.0, intensities[0]),
+            Shape::ellipse(0.0, 0.1, 0.046, 0.046, 0.0, intensities[1]),
+            Shape::ellipse(0.0, -0.1, 0.046, 0.046, 0.0, intensities[2]),
+            Shape::ellipse(-0.08, -0.605, 0.046, 0.023, 0.0, intensities[3]),
+            Shape::ellipse(0.0, -0.605, 0.023, 0.023, 0.0, intensities[4]),
+            Shape::ellipse(0.06, -0.605, 0.023, 0.046, 0.0, intensities[5]),
+            Shape::ellipse(0.22, 0.0, 0.11, 0.31, -18.0, intensities[6]),
+            Shape::ellipse(-0.22, 0.0, 0.16, 0.41, 18.0, intensities[7]),
+            Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, intensities[8]),
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, intensities[9]),
+        ]
+    }
+
+    /// Renders the preset with size `nx` times `ny`.
+    pub(crate) fn render(self, nx: u32, ny: u32) -> Phantom {
+        let mut phantom = Phantom::new(nx, ny, &self.shapes());
+        phantom.preset = Some(self);
+        phantom
+    }
+}
 
 /// Original Shepp-Logan phantom
 ///
@@ -15,19 +54,7 @@ use crate::{Phantom, Shape};
 /// The parameters `nx` and `ny` define the number of pixels in `x` and `y` direction.
 /// The dynamic range of the values is between `0.0` and `2.0`.
 pub fn shepplogan(nx: u32, ny: u32) -> Phantom {
-    let ellipses = [
-        Shape::ellipse(0.0, 0.35, 0.21, 0.25, 0.0, 0.01),
-        Shape::ellipse(0.0, 0.1, 0.046, 0.046, 0.0, 0.01),
-        Shape::ellipse(0.0, -0.1, 0.046, 0.046, 0.0, 0.01),
-        Shape::ellipse(-0.08, -0.605, 0.046, 0.023, 0.0, 0.01),
-        Shape::ellipse(0.0, -0.605, 0.023, 0.023, 0.0, 0.01),
-        Shape::ellipse(0.06, -0.605, 0.023, 0.046, 0.0, 0.01),
-        Shape::ellipse(0.22, 0.0, 0.11, 0.31, -18.0, -0.02),
-        Shape::ellipse(-0.22, 0.0, 0.16, 0.41, 18.0, -0.02),
-        Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.98),
-        Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
-    ];
-    Phantom::new(nx, ny, &ellipses)
+    Preset::Original.render(nx, ny)
 }
 
 /// Modified Shepp-Logan phantom with increased contrast
@@ -40,25 +67,108 @@ pub fn shepplogan(nx: u32, ny: u32) -> Phantom {
 /// The parameters `nx` and `ny` define the number of pixels in `x` and `y` direction.
 /// The dynamic range of the values is between `0.0` and `1.0`.
 pub fn shepplogan_modified(nx: u32, ny: u32) -> Phantom {
-    let ellipses = [
-        Shape::ellipse(0.0, 0.35, 0.21, 0.25, 0.0, 0.1),
-        Shape::ellipse(0.0, 0.1, 0.046, 0.046, 0.0, 0.1),
-        Shape::ellipse(0.0, -0.1, 0.046, 0.046, 0.0, 0.1),
-        Shape::ellipse(-0.08, -0.605, 0.046, 0.023, 0.0, 0.1),
-        Shape::ellipse(0.0, -0.605, 0.023, 0.023, 0.0, 0.1),
-        Shape::ellipse(0.06, -0.605, 0.023, 0.046, 0.0, 0.1),
-        Shape::ellipse(0.22, 0.0, 0.11, 0.31, -18.0, -0.2),
-        Shape::ellipse(-0.22, 0.0, 0.16, 0.41, 18.0, -0.2),
-        Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.8),
-        Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 1.0),
-    ];
-    Phantom::new(nx, ny, &ellipses)
+    Preset::Modified.render(nx, ny)
+}
+
+/// Original Shepp-Logan phantom with `f32` values
+///
+/// This is the same as `shepplogan`, but the values are stored and accumulated as `f32`, which
+/// halves the memory required. See `Phantom::new_typed` for details.
+pub fn shepplogan_f32(nx: u32, ny: u32) -> Phantom<f32> {
+    Phantom::new_typed(nx, ny, &Preset::Original.shapes())
+}
+
+/// Modified Shepp-Logan phantom with `f32` values
+///
+/// This is the same as `shepplogan_modified`, but the values are stored and accumulated as `f32`,
+/// which halves the memory required. See `Phantom::new_typed` for details.
+pub fn shepplogan_modified_f32(nx: u32, ny: u32) -> Phantom<f32> {
+    Phantom::new_typed(nx, ny, &Preset::Modified.shapes())
+}
+
+/// Original Shepp-Logan phantom in approximate Hounsfield units
+///
+/// Constructs the original Shepp-Logan phantom (see `shepplogan`) and interprets its values as
+/// attenuation coefficients relative to water, i.e. a value of `1.0` corresponds to water. The
+/// values are converted to Hounsfield units (HU) via `HU = 1000 * (value - 1.0)`, which yields:
+///
+/// Region               | value | HU
+/// ---------------------|-------|------
+/// background (air)     | 0.0   | -1000
+/// skull (bone)         | 2.0   | 1000
+/// brain (soft tissue)  | 1.02  | 20
+/// ventricles (water)   | 1.0   | 0
+/// tumors (soft tissue) | 1.03  | 30
+///
+/// The parameters `nx` and `ny` define the number of pixels in `x` and `y` direction.
+pub fn shepplogan_hu(nx: u32, ny: u32) -> Phantom {
+    let mut phantom = shepplogan(nx, ny);
+    // The intensities do not match any of the presets anymore
+    phantom.preset = None;
+    phantom
+        .data
+        .iter_mut()
+        .for_each(|x| *x = 1000.0 * (*x - 1.0));
+    phantom
+}
+
+/// Original Shepp-Logan phantom at multiple resolutions
+///
+/// Constructs square versions of the original Shepp-Logan phantom (see `shepplogan`) with the
+/// dimensions `base`, `base / 2`, `base / 4`, ... for `levels` levels. Levels which would have a
+/// dimension of zero are omitted.
+///
+/// If the `rayon` feature is enabled, the levels are computed in parallel.
+pub fn shepplogan_pyramid(base: u32, levels: u32) -> Vec<Phantom> {
+    let dims = (0..levels)
+        .map_while(|level| base.checked_shr(level))
+        .take_while(|&n| n > 0)
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        dims.into_par_iter().map(|n| shepplogan(n, n)).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        dims.into_iter().map(|n| shepplogan(n, n)).collect()
+    }
+}
+
+/// Original Shepp-Logan phantom together with its analytic sinogram
+///
+/// Returns the original Shepp-Logan phantom (see `shepplogan`) with `nx` times `ny` pixels and
+/// the exact line integrals of its ellipses for `n_angles` angles equally distributed over
+/// `[0, π)` and `n_detectors` detector elements equally distributed over `[-1, 1]`. The line
+/// integrals of angle `i` are stored at `i * n_detectors..(i + 1) * n_detectors`. Since the
+/// sinogram is computed analytically, it is free of discretization errors, which makes this a
+/// ground truth and measurement pair for testing reconstruction algorithms.
+///
+/// A ray at angle `theta` and detector position `s` consists of all points `(x, y)` with
+/// `x * cos(theta) + y * sin(theta) = s` in the coordinates of the shapes, where the phantom
+/// covers `[-1, 1]` along its shorter side. The lengths are measured in these coordinates as
+/// well.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::make_ct_testcase;
+/// let (phantom, sinogram) = make_ct_testcase(256, 256, 180, 256);
+/// assert_eq!(sinogram.len(), 180 * 256);
+/// ```
+pub fn make_ct_testcase(nx: u32, ny: u32, n_angles: u32, n_detectors: u32) -> (Phantom, Vec<f64>) {
+    let sinogram = sinogram(&Preset::Original.shapes(), n_angles, n_detectors);
+    (shepplogan(nx, ny), sinogram)
 }
 #[cfg(test)]
 mod tests {
     use approx::assert_abs_diff_eq;
 
-    use crate::{shepplogan, shepplogan_modified, Shape};
+    use crate::{
+        make_ct_testcase, shepplogan, shepplogan_f32, shepplogan_hu, shepplogan_modified,
+        shepplogan_modified_f32, shepplogan_pyramid, Phantom, Shape,
+    };
 
     #[derive(Debug, Copy, Clone)]
     struct Dimension(u32);
@@ -139,4 +249,98 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_shepplogan_pyramid() {
+        let pyramid = shepplogan_pyramid(256, 4);
+
+        assert_eq!(pyramid.len(), 4);
+
+        let center = |data: &[f64], n: u32| data[((n - n / 2 - 1) * n + n / 2) as usize];
+
+        let finest = shepplogan(256, 256).into_vec();
+        for (level, phantom) in pyramid.into_iter().enumerate() {
+            let n = 256 >> level;
+            let data = phantom.into_vec();
+            assert_eq!(data.len(), (n * n) as usize);
+            if level == 0 {
+                assert_eq!(data, finest);
+            }
+            assert_abs_diff_eq!(center(&data, n), center(&finest, 256));
+        }
+
+        // Levels which would be empty are omitted
+        assert_eq!(shepplogan_pyramid(4, 10).len(), 3);
+        assert!(shepplogan_pyramid(256, 0).is_empty());
+    }
+
+    #[test]
+    fn test_shepplogan_hu() {
+        let (nx, ny) = (128, 128);
+        let phantom = shepplogan_hu(nx, ny).into_vec();
+        let value = |x: u32, y: u32| phantom[((ny - y - 1) * nx + x) as usize];
+
+        // background
+        assert_eq!(value(0, 0).to_ne_bytes(), (-1000.0f64).to_ne_bytes());
+        assert_eq!(
+            value(nx - 1, ny - 1).to_ne_bytes(),
+            (-1000.0f64).to_ne_bytes()
+        );
+        // skull at the top of the head
+        assert_eq!(value(nx / 2, 121).to_ne_bytes(), 1000.0f64.to_ne_bytes());
+        // brain between the ventricles
+        assert_abs_diff_eq!(value(nx / 2, ny / 2), 20.0, epsilon = 1e-9);
+        // ventricle
+        assert_abs_diff_eq!(value(nx / 2 + 14, ny / 2), 0.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_shepplogan_conversion() {
+        let (nx, ny) = (64, 96);
+        let original = shepplogan(nx, ny);
+        let modified = shepplogan_modified(nx, ny);
+
+        let converted = original.to_modified().unwrap().into_vec();
+        assert_eq!(converted, modified.clone().into_vec());
+        let converted = modified.to_original().unwrap().into_vec();
+        assert_eq!(converted, original.clone().into_vec());
+        assert_eq!(
+            original.to_original().unwrap().into_vec(),
+            original.clone().into_vec()
+        );
+
+        // Phantoms which were not created from a preset cannot be converted
+        assert!(original.transpose().to_modified().is_none());
+        assert!(shepplogan_hu(nx, ny).to_modified().is_none());
+        assert!(Phantom::new(nx, ny, &[]).to_original().is_none());
+    }
+
+    #[test]
+    fn test_make_ct_testcase() {
+        let (phantom, sinogram) = make_ct_testcase(64, 96, 18, 32);
+        assert_eq!(phantom.into_vec(), shepplogan(64, 96).into_vec());
+        assert_eq!(sinogram.len(), 18 * 32);
+        // The phantom is non-negative everywhere, therefore so are its line integrals
+        assert!(sinogram.iter().all(|v| v.is_finite() && *v >= 0.0));
+        assert!(sinogram[..32].iter().any(|&v| v > 0.0));
+        assert!(make_ct_testcase(8, 8, 0, 8).1.is_empty());
+    }
+
+    #[test]
+    fn test_shepplogan_f32() {
+        let (nx, ny) = (96, 128);
+        for (single, double) in [
+            (shepplogan_f32(nx, ny), shepplogan(nx, ny)),
+            (shepplogan_modified_f32(nx, ny), shepplogan_modified(nx, ny)),
+        ] {
+            assert_eq!(single.dimensions(), (nx, ny));
+            let (single, double) = (single.into_vec(), double.into_vec());
+            assert_eq!(single.len(), double.len());
+            for (&a, &b) in single.iter().zip(double.iter()) {
+                assert_abs_diff_eq!(f64::from(a), b, epsilon = 1e-6);
+                // The same pixels are covered
+                assert_eq!(a == 0.0, b == 0.0);
+            }
+        }
+    }
 }
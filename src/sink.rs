@@ -0,0 +1,151 @@
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+use crate::{phantom::check_dimensions, Error, Phantom, Shape};
+
+/// Destination for the pixels rendered by `render_to`
+///
+/// Implementing this trait allows writing the pixels of a phantom directly to an arbitrary
+/// destination (e.g. a file or a buffer of another library) without allocating an intermediate
+/// phantom.
+pub trait PixelSink {
+    /// Receives the final `value` of pixel `(x, y)`, where `(0, 0)` is the lower left pixel.
+    fn put(&mut self, x: u32, y: u32, value: f64);
+}
+
+/// Sink which collects the pixels in a `Vec` with the same layout as `Phantom`
+#[derive(Clone, PartialEq, Debug)]
+pub struct VecSink {
+    nx: u32,
+    ny: u32,
+    data: Vec<f64>,
+}
+
+impl VecSink {
+    /// Creates a sink for a phantom of size `nx` times `ny`, initialized with zeros.
+    ///
+    /// Returns `Error::DimensionOverflow` if the number of pixels does not fit into a `u32`.
+    pub fn new(nx: u32, ny: u32) -> Result<Self, Error> {
+        Ok(VecSink {
+            nx,
+            ny,
+            data: vec![0.0; check_dimensions(nx, ny)?],
+        })
+    }
+
+    /// Returns the collected pixels as flattened `Vec<f64>` (see `Phantom::into_vec`).
+    pub fn into_vec(self) -> Vec<f64> {
+        self.data
+    }
+
+    /// Converts the collected pixels into a `Phantom`.
+    pub fn into_phantom(self) -> Phantom {
+        Phantom::from_raw(self.nx, self.ny, self.data)
+    }
+}
+
+impl PixelSink for VecSink {
+    #[inline(always)]
+    fn put(&mut self, x: u32, y: u32, value: f64) {
+        self.data[(self.ny - y - 1) as usize * self.nx as usize + x as usize] = value;
+    }
+}
+
+/// Renders a phantom of size `nx` times `ny` given a set of `shapes` into `sink`.
+///
+/// The result is the same as for `Phantom::new`, but instead of storing the pixels, each pixel is
+/// passed to the `sink` exactly once in the order in which they are stored in a `Phantom`: The
+/// topmost row first and each row from left to right. This allows streaming a phantom row by row.
+///
+/// # Example
+///
+/// ```
+/// # use shepplogan::{render_to, Phantom, Shape, VecSink};
+/// let shapes = [Shape::ellipse(0.0, 0.0, 0.5, 0.5, 0.0, 1.0)];
+/// let mut sink = VecSink::new(64, 64).unwrap();
+/// render_to(64, 64, &shapes, &mut sink);
+/// assert_eq!(sink.into_vec(), Phantom::new(64, 64, &shapes).into_vec());
+/// ```
+pub fn render_to<S: PixelSink + ?Sized>(nx: u32, ny: u32, shapes: &[Shape], sink: &mut S) {
+    let shapes = shapes
+        .iter()
+        .map(|shape| {
+            let shape = shape.on_canvas(nx, ny);
+            (shape.bounding_box(), shape)
+        })
+        .collect::<Vec<_>>();
+    let mut row_shapes = Vec::with_capacity(shapes.len());
+    for y in (0..ny).rev() {
+        row_shapes.clear();
+        row_shapes.extend(
+            shapes
+                .iter()
+                .filter(|(bbox, _)| bbox.y_low <= y && y <= bbox.y_high),
+        );
+        for x in 0..nx {
+            // The intensities are added in the same order as in `Phantom::new`, which leads to
+            // identical results.
+            let value = row_shapes
+                .iter()
+                .filter(|(bbox, shape)| bbox.contains(x, y) && shape.inside_pixel(x, y))
+                .fold(0.0, |acc, (_, shape)| acc + shape.intensity());
+            sink.put(x, y, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_to, PixelSink, VecSink};
+    use crate::{Error, Phantom, Shape};
+
+    /// Sink which records the order of the pixels
+    struct OrderSink(Vec<(u32, u32)>);
+
+    impl PixelSink for OrderSink {
+        fn put(&mut self, x: u32, y: u32, _value: f64) {
+            self.0.push((x, y));
+        }
+    }
+
+    #[test]
+    fn test_render_to() {
+        let (nx, ny) = (48, 64);
+        let shapes = [
+            Shape::ellipse(0.0, 0.0, 0.69, 0.92, 0.0, 2.0),
+            Shape::ellipse(0.0, -0.0184, 0.6624, 0.874, 0.0, -0.98),
+            Shape::rectangle(0.2, -0.3, 0.3, 0.2, 30.0, 0.5),
+            Shape::lune(-0.2, 0.3, 0.3, -0.1, 0.3, 0.25, 0.7),
+        ];
+        let mut sink = VecSink::new(nx, ny).unwrap();
+        render_to(nx, ny, &shapes, &mut sink);
+        let expected = Phantom::new(nx, ny, &shapes).into_vec();
+        assert_eq!(
+            sink.clone()
+                .into_vec()
+                .iter()
+                .map(|x| x.to_ne_bytes())
+                .collect::<Vec<_>>(),
+            expected.iter().map(|x| x.to_ne_bytes()).collect::<Vec<_>>()
+        );
+        assert_eq!(sink.into_phantom().into_vec(), expected);
+
+        let mut sink = OrderSink(Vec::new());
+        render_to(3, 2, &shapes, &mut sink);
+        assert_eq!(sink.0, [(0, 1), (1, 1), (2, 1), (0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_vec_sink_dimensions() {
+        assert!(matches!(
+            VecSink::new(1 << 16, 1 << 16),
+            Err(Error::DimensionOverflow)
+        ));
+        let mut sink = VecSink::new(3, 2).unwrap();
+        sink.put(2, 0, 1.0);
+        sink.put(0, 1, 2.0);
+        assert_eq!(sink.into_vec(), [2.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+}